@@ -89,6 +89,9 @@ class ValidationErrorKind:
     class FromUtf8:
         error: str
 
+    class MaxDepth:
+        limit: int
+
     class MaxItems:
         limit: int
 
@@ -113,6 +113,7 @@ enum ValidationErrorKind {
     FalseSchema {},
     Format { format: String },
     FromUtf8 { error: String },
+    MaxDepth { limit: usize },
     MaxItems { limit: u64 },
     Maximum { limit: PyObject },
     MaxLength { limit: u64 },
@@ -150,7 +151,7 @@ impl ValidationErrorKind {
                     unexpected: PyList::new(py, unexpected)?.unbind(),
                 }
             }
-            jsonschema::error::ValidationErrorKind::AnyOf => ValidationErrorKind::AnyOf {},
+            jsonschema::error::ValidationErrorKind::AnyOf { .. } => ValidationErrorKind::AnyOf {},
             jsonschema::error::ValidationErrorKind::BacktrackLimitExceeded { error } => {
                 ValidationErrorKind::BacktrackLimitExceeded {
                     error: error.to_string(),
@@ -195,6 +196,9 @@ impl ValidationErrorKind {
                     error: error.to_string(),
                 }
             }
+            jsonschema::error::ValidationErrorKind::MaxDepth { limit } => {
+                ValidationErrorKind::MaxDepth { limit }
+            }
             jsonschema::error::ValidationErrorKind::MaxItems { limit } => {
                 ValidationErrorKind::MaxItems { limit }
             }
@@ -232,7 +236,7 @@ impl ValidationErrorKind {
             jsonschema::error::ValidationErrorKind::OneOfMultipleValid => {
                 ValidationErrorKind::OneOfMultipleValid {}
             }
-            jsonschema::error::ValidationErrorKind::OneOfNotValid => {
+            jsonschema::error::ValidationErrorKind::OneOfNotValid { .. } => {
                 ValidationErrorKind::OneOfNotValid {}
             }
             jsonschema::error::ValidationErrorKind::Pattern { pattern } => {
@@ -26,6 +26,14 @@ impl AnchorKey {
     pub(crate) fn new(uri: Uri<String>, name: String) -> Self {
         Self { uri, name }
     }
+
+    pub(crate) fn uri(&self) -> &Uri<String> {
+        &self.uri
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
 }
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
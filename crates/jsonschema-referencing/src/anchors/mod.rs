@@ -108,7 +108,7 @@ pub(crate) fn legacy_anchor_in_dollar_id(
         contents
             .as_object()
             .and_then(|schema| schema.get("$id").and_then(Value::as_str))
-            .and_then(|id| id.strip_prefix('#'))
+            .and_then(legacy_anchor_name)
             .map(move |id| Anchor::Default {
                 draft,
                 name: id.to_string(),
@@ -126,7 +126,7 @@ pub(crate) fn legacy_anchor_in_id<'a>(
         contents
             .as_object()
             .and_then(|schema| schema.get("id").and_then(Value::as_str))
-            .and_then(|id| id.strip_prefix('#'))
+            .and_then(legacy_anchor_name)
             .map(move |id| Anchor::Default {
                 draft,
                 name: id.to_string(),
@@ -136,10 +136,22 @@ pub(crate) fn legacy_anchor_in_id<'a>(
     )
 }
 
+/// The plain-name fragment of a legacy identifier, e.g. `foo` in `#foo` or
+/// `http://example.com/bar#foo`.
+fn legacy_anchor_name(id: &str) -> Option<&str> {
+    let (_, fragment) = id.split_once('#')?;
+    if fragment.is_empty() || fragment.starts_with('/') {
+        None
+    } else {
+        Some(fragment)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Draft, Registry};
     use serde_json::json;
+    use test_case::test_case;
 
     #[test]
     fn test_lookup_trivial_dynamic_ref() {
@@ -394,4 +406,63 @@ mod tests {
             .expect("Lookup failed");
         assert_eq!(fourth.contents(), two.contents());
     }
+
+    #[test_case(
+        json!({
+            "allOf": [{"$ref": "#foo"}],
+            "definitions": {"A": {"id": "#foo", "type": "integer"}}
+        }),
+        "http://localhost:1234/root",
+        "#foo",
+        &json!({"id": "#foo", "type": "integer"})
+        ; "fragment anchor"
+    )]
+    #[test_case(
+        json!({
+            "allOf": [{"$ref": "http://localhost:1234/bar#foo"}],
+            "definitions": {"A": {"id": "http://localhost:1234/bar#foo", "type": "integer"}}
+        }),
+        "http://localhost:1234/root",
+        "http://localhost:1234/bar#foo",
+        &json!({"id": "http://localhost:1234/bar#foo", "type": "integer"})
+        ; "absolute uri with fragment anchor"
+    )]
+    #[test_case(
+        json!({
+            "id": "http://localhost:1234/root",
+            "allOf": [{"$ref": "http://localhost:1234/nested.json#foo"}],
+            "definitions": {
+                "A": {
+                    "id": "nested.json",
+                    "definitions": {"B": {"id": "#foo", "type": "integer"}}
+                }
+            }
+        }),
+        "http://localhost:1234/root",
+        "http://localhost:1234/nested.json#foo",
+        &json!({"id": "#foo", "type": "integer"})
+        ; "fragment anchor under changed base uri"
+    )]
+    #[test_case(
+        json!({
+            "id": "http://localhost:1234/root",
+            "definitions": {"A": {"id": "nested.json#bar", "type": "string"}}
+        }),
+        "http://localhost:1234/root",
+        "http://localhost:1234/nested.json#bar",
+        &json!({"id": "nested.json#bar", "type": "string"})
+        ; "relative uri with fragment anchor"
+    )]
+    fn test_lookup_draft4_id_anchor(
+        schema: serde_json::Value,
+        base_uri: &str,
+        reference: &str,
+        expected: &serde_json::Value,
+    ) {
+        let registry = Registry::try_new(base_uri, Draft::Draft4.create_resource(schema))
+            .expect("Invalid resources");
+        let resolver = registry.try_resolver(base_uri).expect("Invalid base URI");
+        let resolved = resolver.lookup(reference).expect("Lookup failed");
+        assert_eq!(resolved.contents(), expected);
+    }
 }
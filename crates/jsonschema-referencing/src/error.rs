@@ -14,8 +14,18 @@ pub enum Error {
         uri: String,
         source: Box<dyn std::error::Error + Send + Sync>,
     },
+    /// A retrieved resource is not valid JSON, or not a JSON object or boolean when
+    /// [`crate::RegistryOptions::require_schema_documents`] is enabled.
+    InvalidRetrievedSchema {
+        uri: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
     /// A JSON Pointer leads to a part of a document that does not exist.
     PointerToNowhere { pointer: String },
+    /// A reference points to a value that is neither a JSON object nor a boolean.
+    ///
+    /// `uri` is the reference resolved against its base URI.
+    InvalidReferenceTarget { reference: String, uri: String },
     /// JSON Pointer contains invalid percent-encoded data.
     InvalidPercentEncoding { pointer: String, source: Utf8Error },
     /// Failed to parse array index in JSON Pointer.
@@ -32,6 +42,14 @@ pub enum Error {
     InvalidUri(UriError),
     /// An unknown JSON Schema specification was encountered.
     UnknownSpecification { specification: String },
+    /// Retrieving a resource with this URI scheme is not allowed.
+    SchemeNotAllowed { scheme: String },
+    /// A serialized registry index is malformed or does not match the provided resources.
+    InvalidIndex { message: String },
+    /// Some references could not be resolved while crawling a registry.
+    ///
+    /// Each entry holds the reference, resolved against its base URI, and the error it caused.
+    UnresolvableReferences { references: Vec<(String, Error)> },
 }
 
 impl Error {
@@ -73,6 +91,18 @@ impl Error {
         }
     }
 
+    pub(crate) fn scheme_not_allowed(scheme: impl Into<String>) -> Error {
+        Error::SchemeNotAllowed {
+            scheme: scheme.into(),
+        }
+    }
+
+    pub(crate) fn invalid_index(message: impl Into<String>) -> Error {
+        Error::InvalidIndex {
+            message: message.into(),
+        }
+    }
+
     pub(crate) fn unretrievable(
         uri: impl Into<String>,
         source: Box<dyn std::error::Error + Send + Sync>,
@@ -83,6 +113,20 @@ impl Error {
         }
     }
 
+    pub(crate) fn invalid_retrieved_schema(
+        uri: impl Into<String>,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    ) -> Error {
+        Error::InvalidRetrievedSchema {
+            uri: uri.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn unresolvable_references(references: Vec<(String, Error)>) -> Error {
+        Error::UnresolvableReferences { references }
+    }
+
     pub(crate) fn uri_parsing_error(uri: impl Into<String>, error: ParseError) -> Error {
         Error::InvalidUri(UriError::Parse {
             uri: uri.into(),
@@ -118,9 +162,15 @@ impl fmt::Display for Error {
             Error::Unretrievable { uri, source } => {
                 f.write_fmt(format_args!("Resource '{uri}' is not present in a registry and retrieving it failed: {source}"))
             },
+            Error::InvalidRetrievedSchema { uri, source } => {
+                f.write_fmt(format_args!("Resource '{uri}' was retrieved but is not a valid JSON Schema: {source}"))
+            }
             Error::PointerToNowhere { pointer } => {
                 f.write_fmt(format_args!("Pointer '{pointer}' does not exist"))
             }
+            Error::InvalidReferenceTarget { reference, uri } => {
+                f.write_fmt(format_args!("Reference '{reference}' points to '{uri}', which is not an object or a boolean and can't be used as a schema"))
+            }
             Error::InvalidPercentEncoding { pointer, .. } => {
                 f.write_fmt(format_args!("Invalid percent encoding in pointer '{pointer}': the decoded bytes do not represent valid UTF-8"))
             }
@@ -137,6 +187,22 @@ impl fmt::Display for Error {
             Error::UnknownSpecification { specification } => {
                 f.write_fmt(format_args!("Unknown specification: {specification}"))
             }
+            Error::SchemeNotAllowed { scheme } => {
+                f.write_fmt(format_args!("Retrieving resources with the '{scheme}' scheme is not allowed"))
+            }
+            Error::InvalidIndex { message } => {
+                f.write_fmt(format_args!("Invalid registry index: {message}"))
+            }
+            Error::UnresolvableReferences { references } => {
+                f.write_str("Unresolvable references: ")?;
+                for (idx, (reference, error)) in references.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("; ")?;
+                    }
+                    f.write_fmt(format_args!("'{reference}': {error}"))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -144,7 +210,9 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Error::Unretrievable { source, .. } => Some(&**source),
+            Error::Unretrievable { source, .. } | Error::InvalidRetrievedSchema { source, .. } => {
+                Some(&**source)
+            }
             Error::InvalidUri(error) => Some(error),
             Error::InvalidPercentEncoding { source, .. } => Some(source),
             Error::InvalidArrayIndex { source, .. } => Some(source),
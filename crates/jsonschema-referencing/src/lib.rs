@@ -21,7 +21,9 @@ pub use list::List;
 pub use registry::{Registry, RegistryOptions, SPECIFICATIONS};
 pub use resolver::{Resolved, Resolver};
 pub use resource::{Resource, ResourceRef};
-pub use retriever::{DefaultRetriever, Retrieve};
-pub(crate) use segments::Segments;
-pub use specification::Draft;
+pub use retriever::{
+    DefaultRetriever, MapRetriever, RecordingRetriever, Retrieval, RetrievalLog, Retrieve,
+};
+pub use segments::{Segment, Segments};
+pub use specification::{Draft, KeywordKind};
 pub use vocabularies::{Vocabulary, VocabularySet};
@@ -8,14 +8,14 @@ use std::{
 use ahash::{AHashMap, AHashSet, AHasher};
 use fluent_uri::Uri;
 use once_cell::sync::Lazy;
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use crate::{
     anchors::{AnchorKey, AnchorKeyRef},
     list::List,
     meta, uri,
     vocabularies::{self, VocabularySet},
-    Anchor, DefaultRetriever, Draft, Error, Resolver, Resource, Retrieve,
+    Anchor, DefaultRetriever, Draft, Error, Resolver, Resource, ResourceRef, Retrieve,
 };
 
 type ResourceMap = AHashMap<Uri<String>, Arc<Resource>>;
@@ -30,17 +30,26 @@ pub static SPECIFICATIONS: Lazy<Registry> = Lazy::new(|| {
     // The capacity is known upfront
     let mut resources = ResourceMap::with_capacity(18);
     let mut anchors = AHashMap::with_capacity(8);
+    let mut documents = AHashSet::with_capacity(18);
     process_resources(
         pairs,
         &DefaultRetriever,
         &mut resources,
         &mut anchors,
+        &mut documents,
         Draft::default(),
+        None,
+        &[],
+        false,
+        false,
+        None,
     )
     .expect("Failed to process meta schemas");
     Registry {
         resources,
         anchors,
+        documents,
+        allowed_schemes: None,
         resolving_cache: RwLock::new(AHashMap::new()),
     }
 });
@@ -55,6 +64,10 @@ pub static SPECIFICATIONS: Lazy<Registry> = Lazy::new(|| {
 pub struct Registry {
     resources: ResourceMap,
     anchors: AHashMap<AnchorKey, Anchor>,
+    /// URIs of top-level documents, i.e. added or retrieved resources.
+    documents: AHashSet<Uri<String>>,
+    /// URI schemes that external resources may be retrieved with, if restricted.
+    allowed_schemes: Option<Arc<[String]>>,
     resolving_cache: RwLock<AHashMap<u64, Arc<Uri<String>>>>,
 }
 
@@ -63,6 +76,8 @@ impl Clone for Registry {
         Self {
             resources: self.resources.clone(),
             anchors: self.anchors.clone(),
+            documents: self.documents.clone(),
+            allowed_schemes: self.allowed_schemes.clone(),
             resolving_cache: RwLock::new(AHashMap::new()),
         }
     }
@@ -72,6 +87,12 @@ impl Clone for Registry {
 pub struct RegistryOptions {
     retriever: Box<dyn Retrieve>,
     draft: Draft,
+    default_draft: Option<Draft>,
+    allowed_schemes: Option<Vec<String>>,
+    rewrites: Vec<(String, String)>,
+    dedup_retrieved: bool,
+    require_schema_documents: bool,
+    crawl: bool,
 }
 
 impl RegistryOptions {
@@ -81,6 +102,12 @@ impl RegistryOptions {
         Self {
             retriever: Box::new(DefaultRetriever),
             draft: Draft::default(),
+            default_draft: None,
+            allowed_schemes: None,
+            rewrites: Vec::new(),
+            dedup_retrieved: false,
+            require_schema_documents: false,
+            crawl: false,
         }
     }
     /// Set a custom retriever for the [`Registry`].
@@ -95,13 +122,167 @@ impl RegistryOptions {
         self.draft = draft;
         self
     }
+    /// Set the draft of resources that don't declare one via `$schema`.
+    ///
+    /// The draft of each resource in the registry is determined in this order:
+    ///
+    /// 1. The draft the resource was explicitly created with, e.g. via [`Draft::create_resource`].
+    /// 2. The draft declared by its `$schema` keyword.
+    /// 3. This default draft.
+    ///
+    /// It applies to resources created with [`Resource::from_contents`] and to retrieved ones,
+    /// which otherwise fall back to Draft 2020-12 and the draft set by [`RegistryOptions::draft`]
+    /// respectively.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use referencing::{Draft, Registry, Resource};
+    /// use serde_json::json;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let registry = Registry::options()
+    ///     .default_draft(Draft::Draft7)
+    ///     .try_from_resources(
+    ///         [
+    ///             ("https://example.com/a", Resource::from_contents(json!({}))?),
+    ///             (
+    ///                 "https://example.com/b",
+    ///                 Draft::Draft4.create_resource(json!({})),
+    ///             ),
+    ///         ]
+    ///         .into_iter(),
+    ///     )?;
+    /// let drafts: Vec<_> = ["https://example.com/a", "https://example.com/b"]
+    ///     .iter()
+    ///     .map(|uri| registry.try_resolver(uri).and_then(|resolver| resolver.lookup("")))
+    ///     .map(|resolved| resolved.map(|resolved| resolved.draft()))
+    ///     .collect::<Result<_, _>>()?;
+    /// assert_eq!(drafts, [Draft::Draft7, Draft::Draft4]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn default_draft(mut self, draft: Draft) -> Self {
+        self.default_draft = Some(draft);
+        self
+    }
+    /// Restrict URI schemes of external resources that may be retrieved.
+    ///
+    /// Any reference to a resource that is not present in the registry and uses a scheme outside
+    /// of this list fails with [`Error::SchemeNotAllowed`] before retrieval is attempted.
+    /// Schemes are compared case-insensitively. All schemes are allowed by default.
+    #[must_use]
+    pub fn allowed_schemes(mut self, schemes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.allowed_schemes = Some(
+            schemes
+                .into_iter()
+                .map(|scheme| scheme.as_ref().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+    /// Redirect external resources whose URI starts with `from` to the same path under `to`.
+    ///
+    /// Rules apply to every external resource that is not present in the registry, before the
+    /// scheme check and retrieval. If the rewritten URI points to a resource that is already in
+    /// the registry, it is used as is, otherwise it is fetched with the retriever. Either way,
+    /// the resource is stored under its original URI, so references to it resolve unchanged.
+    /// Rules are checked in the order they were added and the first matching one is applied.
+    ///
+    /// Rules also apply to `$ref`s to the bundled meta-schemas, e.g. a rule for
+    /// `https://json-schema.org/draft/2020-12/` replaces them with the rewritten copies. Declaring
+    /// a draft with `$schema` does not retrieve its meta-schema.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use referencing::{Registry, Resource};
+    /// use serde_json::json;
+    ///
+    /// let registry = Registry::options()
+    ///     .rewrite("https://example.com/schemas/", "file:///mirror/schemas/")
+    ///     .try_from_resources(
+    ///         [
+    ///             (
+    ///                 "https://example.com/root",
+    ///                 Resource::from_contents(json!({"$ref": "https://example.com/schemas/string"}))
+    ///                     .expect("Invalid resource"),
+    ///             ),
+    ///             (
+    ///                 "file:///mirror/schemas/string",
+    ///                 Resource::from_contents(json!({"type": "string"}))
+    ///                     .expect("Invalid resource"),
+    ///             ),
+    ///         ]
+    ///         .into_iter(),
+    ///     )
+    ///     .expect("Invalid registry");
+    /// let resolver = registry.try_resolver("").expect("Invalid base URI");
+    /// let resolved = resolver
+    ///     .lookup("https://example.com/schemas/string")
+    ///     .expect("Resolvable reference");
+    /// assert_eq!(resolved.contents(), &json!({"type": "string"}));
+    /// ```
+    #[must_use]
+    pub fn rewrite(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.rewrites.push((from.into(), to.into()));
+        self
+    }
+    /// Store retrieved documents with identical contents only once.
+    ///
+    /// Useful when mirrors serve the same document under different URIs. Every URI still
+    /// resolves, including anchors and subresources under it, but all of them point to a single
+    /// shared [`Resource`]. Disabled by default, as it requires hashing every retrieved document.
+    #[must_use]
+    pub fn dedup_retrieved(mut self, yes: bool) -> Self {
+        self.dedup_retrieved = yes;
+        self
+    }
+    /// Reject retrieved documents that are not JSON objects or booleans.
+    ///
+    /// Retrieved documents that fail to parse as JSON are always reported as
+    /// [`Error::InvalidRetrievedSchema`]. With this option, the same error is returned for
+    /// documents that can't be schemas, e.g. a JSON string returned instead of an error page.
+    /// Disabled by default, as other documents can still contain schemas referenced via a
+    /// JSON Pointer, e.g. `schemas.json#/0` for an array of schemas.
+    #[must_use]
+    pub fn require_schema_documents(mut self, yes: bool) -> Self {
+        self.require_schema_documents = yes;
+        self
+    }
+    /// Resolve every `$ref` in the registry at build time and report all failures at once.
+    ///
+    /// By default, building a registry stops at the first external resource that can't be
+    /// retrieved, and references are only checked when they are resolved. With crawling enabled,
+    /// all retrieval failures are collected, every `$ref` in the stored documents is resolved,
+    /// and [`Error::UnresolvableReferences`] lists each one that fails. Disabled by default.
+    #[must_use]
+    pub fn crawl(mut self, yes: bool) -> Self {
+        self.crawl = yes;
+        self
+    }
     /// Create a [`Registry`] with a single resource using these options.
     ///
     /// # Errors
     ///
     /// Returns an error if the URI is invalid or if there's an issue processing the resource.
     pub fn try_new(self, uri: impl Into<String>, resource: Resource) -> Result<Registry, Error> {
-        Registry::try_new_impl(uri, resource, &*self.retriever, self.draft)
+        let resource = match self.default_draft {
+            Some(draft) => resource.with_default_draft(draft),
+            None => resource,
+        };
+        Registry::try_new_impl(
+            uri,
+            resource,
+            &*self.retriever,
+            self.default_draft.unwrap_or(self.draft),
+            self.allowed_schemes.as_deref(),
+            &self.rewrites,
+            self.dedup_retrieved,
+            self.require_schema_documents,
+            self.crawl,
+        )
     }
     /// Create a [`Registry`] from multiple resources using these options.
     ///
@@ -112,7 +293,21 @@ impl RegistryOptions {
         self,
         pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
     ) -> Result<Registry, Error> {
-        Registry::try_from_resources_impl(pairs, &*self.retriever, self.draft)
+        let default_draft = self.default_draft;
+        let pairs = pairs.map(|(uri, resource)| match default_draft {
+            Some(draft) => (uri, resource.with_default_draft(draft)),
+            None => (uri, resource),
+        });
+        Registry::try_from_resources_impl(
+            pairs,
+            &*self.retriever,
+            self.default_draft.unwrap_or(self.draft),
+            self.allowed_schemes.as_deref(),
+            &self.rewrites,
+            self.dedup_retrieved,
+            self.require_schema_documents,
+            self.crawl,
+        )
     }
 }
 
@@ -139,7 +334,17 @@ impl Registry {
     ///
     /// Returns an error if the URI is invalid or if there's an issue processing the resource.
     pub fn try_new(uri: impl Into<String>, resource: Resource) -> Result<Self, Error> {
-        Self::try_new_impl(uri, resource, &DefaultRetriever, Draft::default())
+        Self::try_new_impl(
+            uri,
+            resource,
+            &DefaultRetriever,
+            Draft::default(),
+            None,
+            &[],
+            false,
+            false,
+            false,
+        )
     }
     /// Create a new [`Registry`] from an iterator of (URI, Resource) pairs.
     ///
@@ -153,29 +358,131 @@ impl Registry {
     pub fn try_from_resources(
         pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
     ) -> Result<Self, Error> {
-        Self::try_from_resources_impl(pairs, &DefaultRetriever, Draft::default())
+        Self::try_from_resources_impl(
+            pairs,
+            &DefaultRetriever,
+            Draft::default(),
+            None,
+            &[],
+            false,
+            false,
+            false,
+        )
     }
     fn try_new_impl(
         uri: impl Into<String>,
         resource: Resource,
         retriever: &dyn Retrieve,
         draft: Draft,
+        allowed_schemes: Option<&[String]>,
+        rewrites: &[(String, String)],
+        dedup_retrieved: bool,
+        require_schema_documents: bool,
+        crawl: bool,
     ) -> Result<Self, Error> {
-        Self::try_from_resources_impl([(uri, resource)].into_iter(), retriever, draft)
+        Self::try_from_resources_impl(
+            [(uri, resource)].into_iter(),
+            retriever,
+            draft,
+            allowed_schemes,
+            rewrites,
+            dedup_retrieved,
+            require_schema_documents,
+            crawl,
+        )
     }
     fn try_from_resources_impl(
         pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
         retriever: &dyn Retrieve,
         draft: Draft,
+        allowed_schemes: Option<&[String]>,
+        rewrites: &[(String, String)],
+        dedup_retrieved: bool,
+        require_schema_documents: bool,
+        crawl: bool,
     ) -> Result<Self, Error> {
         let mut resources = ResourceMap::new();
         let mut anchors = AHashMap::new();
-        process_resources(pairs, retriever, &mut resources, &mut anchors, draft)?;
-        Ok(Registry {
+        let mut documents = AHashSet::new();
+        let mut unresolvable = crawl.then(Vec::new);
+        process_resources(
+            pairs,
+            retriever,
+            &mut resources,
+            &mut anchors,
+            &mut documents,
+            draft,
+            allowed_schemes,
+            rewrites,
+            dedup_retrieved,
+            require_schema_documents,
+            unresolvable.as_mut(),
+        )?;
+        let registry = Registry {
             resources,
             anchors,
+            documents,
+            allowed_schemes: allowed_schemes.map(Arc::from),
             resolving_cache: RwLock::new(AHashMap::new()),
-        })
+        };
+        if let Some(mut unresolvable) = unresolvable {
+            registry.crawl_references(&mut unresolvable)?;
+            if !unresolvable.is_empty() {
+                return Err(Error::unresolvable_references(unresolvable));
+            }
+        }
+        Ok(registry)
+    }
+    /// Resolve every `$ref` in the stored documents and collect the ones that fail.
+    ///
+    /// `unresolvable` already holds documents that failed to be retrieved, references to them
+    /// are not reported again.
+    fn crawl_references(&self, unresolvable: &mut Vec<(String, Error)>) -> Result<(), Error> {
+        let unretrievable: AHashSet<String> =
+            unresolvable.iter().map(|(uri, _)| uri.clone()).collect();
+        let mut documents: Vec<_> = self.documents.iter().collect();
+        documents.sort_unstable_by(|left, right| left.as_str().cmp(right.as_str()));
+        for uri in documents {
+            self.crawl_resource(
+                uri.clone(),
+                Resource::as_ref(&self.resources[uri]),
+                &unretrievable,
+                unresolvable,
+            )?;
+        }
+        Ok(())
+    }
+    fn crawl_resource(
+        &self,
+        mut base: Uri<String>,
+        resource: ResourceRef<'_>,
+        unretrievable: &AHashSet<String>,
+        unresolvable: &mut Vec<(String, Error)>,
+    ) -> Result<(), Error> {
+        if let Some(id) = resource.id() {
+            base = uri::resolve_against(&base.borrow(), id)?;
+        }
+        if let Some(reference) = resource.contents().get("$ref").and_then(Value::as_str) {
+            if !is_specification_reference(reference) {
+                if let Err(error) = self.resolver(base.clone()).lookup(reference) {
+                    let is_reported = matches!(
+                        &error,
+                        Error::Unretrievable { uri, .. } if unretrievable.contains(uri)
+                    );
+                    if !is_reported {
+                        let reference = uri::resolve_against(&base.borrow(), reference)
+                            .map_or_else(|_| reference.to_string(), |uri| uri.as_str().to_string());
+                        unresolvable.push((reference, error));
+                    }
+                }
+            }
+        }
+        let draft = resource.draft();
+        for contents in draft.subresources_of(resource.contents()) {
+            let subresource = ResourceRef::new(contents, draft.detect(contents)?);
+            self.crawl_resource(base.clone(), subresource, unretrievable, unresolvable)?;
+        }
+        Ok(())
     }
     /// Create a new registry with a new resource.
     ///
@@ -218,6 +525,9 @@ impl Registry {
     }
     /// Create a new registry with new resources and using the given retriever.
     ///
+    /// External resources are retrieved with the same scheme restrictions as the ones this
+    /// registry was created with, see [`RegistryOptions::allowed_schemes`].
+    ///
     /// # Errors
     ///
     /// Returns an error if any URI is invalid or if there's an issue processing the resources.
@@ -226,16 +536,100 @@ impl Registry {
         pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
         retriever: &dyn Retrieve,
         draft: Draft,
+    ) -> Result<Registry, Error> {
+        let mut options = RegistryOptions::new().draft(draft);
+        options.allowed_schemes = self.allowed_schemes.as_deref().map(<[String]>::to_vec);
+        self.try_with_resources_impl(pairs, retriever, &options)
+    }
+    /// Create a new registry with new resources, processing them with the given options.
+    ///
+    /// External resources are fetched with the retriever from `options`, and the draft set
+    /// there applies to resources without `$schema`. If `options` don't restrict URI schemes,
+    /// the restriction of this registry is kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use referencing::{Error, Registry, Resource, SPECIFICATIONS};
+    /// use serde_json::json;
+    ///
+    /// let result = SPECIFICATIONS.clone().try_with_resources_and_options(
+    ///     [(
+    ///         "https://example.com/root",
+    ///         Resource::from_contents(json!({"$ref": "file:///etc/schema.json"}))
+    ///             .expect("Invalid resource"),
+    ///     )]
+    ///     .into_iter(),
+    ///     Registry::options().allowed_schemes(["https"]),
+    /// );
+    /// assert!(matches!(result, Err(Error::SchemeNotAllowed { .. })));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any URI is invalid or if there's an issue processing the resources.
+    pub fn try_with_resources_and_options(
+        self,
+        pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
+        mut options: RegistryOptions,
+    ) -> Result<Registry, Error> {
+        let default_draft = options.default_draft;
+        let pairs = pairs.map(|(uri, resource)| match default_draft {
+            Some(draft) => (uri, resource.with_default_draft(draft)),
+            None => (uri, resource),
+        });
+        if options.allowed_schemes.is_none() {
+            options.allowed_schemes = self.allowed_schemes.as_deref().map(<[String]>::to_vec);
+        }
+        let registry = self.try_with_resources_impl(pairs, &*options.retriever, &options)?;
+        if options.crawl {
+            let mut unresolvable = Vec::new();
+            registry.crawl_references(&mut unresolvable)?;
+            if !unresolvable.is_empty() {
+                return Err(Error::unresolvable_references(unresolvable));
+            }
+        }
+        Ok(registry)
+    }
+    fn try_with_resources_impl(
+        self,
+        pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
+        retriever: &dyn Retrieve,
+        options: &RegistryOptions,
     ) -> Result<Registry, Error> {
         let mut resources = self.resources;
         let mut anchors = self.anchors;
-        process_resources(pairs, retriever, &mut resources, &mut anchors, draft)?;
+        let mut documents = self.documents;
+        process_resources(
+            pairs,
+            retriever,
+            &mut resources,
+            &mut anchors,
+            &mut documents,
+            options.default_draft.unwrap_or(options.draft),
+            options.allowed_schemes.as_deref(),
+            &options.rewrites,
+            options.dedup_retrieved,
+            options.require_schema_documents,
+            None,
+        )?;
         Ok(Registry {
             resources,
             anchors,
+            documents,
+            allowed_schemes: options.allowed_schemes.as_deref().map(Arc::from),
             resolving_cache: RwLock::new(AHashMap::new()),
         })
     }
+    /// Iterate over all stored resources together with their canonical URIs.
+    ///
+    /// Includes embedded resources that have their own `$id` and retrieved external resources.
+    /// The iteration order is unspecified.
+    pub fn resources(&self) -> impl Iterator<Item = (&Uri<String>, &Resource)> {
+        self.resources
+            .iter()
+            .map(|(uri, resource)| (uri, resource.as_ref()))
+    }
     /// Create a new [`Resolver`] for this registry with the given base URI.
     ///
     /// # Errors
@@ -316,6 +710,30 @@ impl Registry {
             Ok(new)
         }
     }
+    /// Detect the draft of `contents`, resolving unknown `$schema` values via this registry.
+    ///
+    /// If `$schema` points to a custom meta-schema stored in the registry, the closest base draft
+    /// is inferred from the core vocabulary declared in its `$vocabulary` keyword.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownSpecification`] if `$schema` is unknown and no vocabulary hint is
+    /// available.
+    pub fn detect_draft(&self, draft: Draft, contents: &Value) -> Result<Draft, Error> {
+        match draft.detect(contents) {
+            Err(Error::UnknownSpecification { specification }) => {
+                if let Ok(Some(resource)) =
+                    uri::from_str(&specification).map(|uri| self.resources.get(&uri))
+                {
+                    if let Some(draft) = vocabularies::infer_draft(resource.contents()) {
+                        return Ok(draft);
+                    }
+                }
+                Err(Error::UnknownSpecification { specification })
+            }
+            result => result,
+        }
+    }
     #[must_use]
     pub fn find_vocabularies(&self, draft: Draft, contents: &Value) -> VocabularySet {
         match draft.detect(contents) {
@@ -336,22 +754,366 @@ impl Registry {
     }
 }
 
+/// Version of the format produced by [`Registry::to_index_bytes`].
+const INDEX_VERSION: u64 = 1;
+
+impl Registry {
+    /// Serialize the index of this registry, so it can be restored without re-indexing.
+    ///
+    /// The index describes where every resource and anchor is located within the top-level
+    /// documents (added or retrieved resources) and which draft applies to it. The documents
+    /// themselves are not included and should be passed to [`Registry::from_index_bytes`]
+    /// alongside the index.
+    ///
+    /// Bundled meta-schemas are recorded as well but don't need to be passed on load.
+    ///
+    /// # Panics
+    ///
+    /// Never panics in practice: every stored resource originates from one of the documents.
+    #[must_use]
+    pub fn to_index_bytes(&self) -> Vec<u8> {
+        let mut documents: Vec<_> = self
+            .documents
+            .iter()
+            .filter_map(|uri| self.resources.get(uri).map(|resource| (uri, resource)))
+            .collect();
+        documents.sort_unstable_by(|(left, _), (right, _)| left.as_str().cmp(right.as_str()));
+        let locations = DocumentLocations::new(documents.iter().map(|(_, doc)| doc.contents()));
+        let locate = |contents: &Value, draft: Draft, entry: &mut Map<String, Value>| {
+            let (document, pointer) = locations
+                .find(contents)
+                .expect("Every resource is a part of some document");
+            entry.insert("document".into(), document.into());
+            entry.insert("pointer".into(), pointer.into());
+            entry.insert("draft".into(), draft_to_index(draft).into());
+        };
+
+        let documents_index: Vec<Value> = documents
+            .iter()
+            .map(|(uri, document)| {
+                let mut entry = Map::new();
+                entry.insert("uri".into(), uri.as_str().into());
+                entry.insert("hash".into(), stable_hash(document.contents()).into());
+                entry.insert("draft".into(), draft_to_index(document.draft()).into());
+                Value::Object(entry)
+            })
+            .collect();
+
+        let mut resources: Vec<_> = self.resources.iter().collect();
+        resources.sort_unstable_by(|(left, _), (right, _)| left.as_str().cmp(right.as_str()));
+        let resources_index: Vec<Value> = resources
+            .into_iter()
+            .map(|(uri, resource)| {
+                let mut entry = Map::new();
+                entry.insert("uri".into(), uri.as_str().into());
+                locate(resource.contents(), resource.draft(), &mut entry);
+                Value::Object(entry)
+            })
+            .collect();
+
+        let mut anchors: Vec<_> = self.anchors.iter().collect();
+        anchors.sort_unstable_by(|(left, _), (right, _)| {
+            (left.uri().as_str(), left.name()).cmp(&(right.uri().as_str(), right.name()))
+        });
+        let anchors_index: Vec<Value> = anchors
+            .into_iter()
+            .map(|(key, anchor)| {
+                let (dynamic, resource) = match anchor {
+                    Anchor::Default { resource, .. } => (false, resource),
+                    Anchor::Dynamic { resource, .. } => (true, resource),
+                };
+                let mut entry = Map::new();
+                entry.insert("uri".into(), key.uri().as_str().into());
+                entry.insert("name".into(), key.name().into());
+                entry.insert("dynamic".into(), dynamic.into());
+                locate(resource.contents(), resource.draft(), &mut entry);
+                Value::Object(entry)
+            })
+            .collect();
+
+        let mut index = Map::new();
+        index.insert("version".into(), INDEX_VERSION.into());
+        index.insert("documents".into(), documents_index.into());
+        index.insert("resources".into(), resources_index.into());
+        index.insert("anchors".into(), anchors_index.into());
+        serde_json::to_vec(&Value::Object(index)).expect("Index is always serializable")
+    }
+    /// Restore a registry from an index produced by [`Registry::to_index_bytes`].
+    ///
+    /// `pairs` should contain the same top-level documents the original registry was built
+    /// from, including retrieved ones. Bundled meta-schemas may be omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidIndex`] if the index is malformed, or if the documents do not
+    /// match it: a document is missing, unexpected, or its contents or draft differ from the
+    /// ones the index was built from.
+    pub fn from_index_bytes(
+        pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
+        bytes: &[u8],
+    ) -> Result<Registry, Error> {
+        let index: Value =
+            serde_json::from_slice(bytes).map_err(|err| Error::invalid_index(err.to_string()))?;
+        if index.get("version").and_then(Value::as_u64) != Some(INDEX_VERSION) {
+            return Err(Error::invalid_index("unsupported version"));
+        }
+        let mut provided = AHashMap::new();
+        for (uri, resource) in pairs {
+            let uri = uri::from_str(uri.into().trim_end_matches('#'))?;
+            provided.insert(uri, Arc::new(resource));
+        }
+
+        let mut documents = Vec::new();
+        let mut document_uris = AHashSet::new();
+        for entry in index_entries(&index, "documents")? {
+            let uri = index_uri(entry)?;
+            let document = match provided.remove(&uri) {
+                Some(document) => document,
+                None => SPECIFICATIONS
+                    .documents
+                    .get(&uri)
+                    .and_then(|uri| SPECIFICATIONS.resources.get(uri))
+                    .cloned()
+                    .ok_or_else(|| Error::invalid_index(format!("document '{uri}' is missing")))?,
+            };
+            if Some(stable_hash(document.contents())) != entry.get("hash").and_then(Value::as_u64)
+                || document.draft() != index_draft(entry)?
+            {
+                return Err(Error::invalid_index(format!(
+                    "document '{uri}' does not match the index"
+                )));
+            }
+            documents.push(document);
+            document_uris.insert(uri);
+        }
+        if let Some(uri) = provided.keys().next() {
+            return Err(Error::invalid_index(format!(
+                "document '{uri}' is not in the index"
+            )));
+        }
+        let locate = |entry: &Value| -> Result<Arc<Resource>, Error> {
+            let document = entry
+                .get("document")
+                .and_then(Value::as_u64)
+                .and_then(|idx| documents.get(usize::try_from(idx).ok()?))
+                .ok_or_else(|| Error::invalid_index("unknown document"))?;
+            let pointer = index_str(entry, "pointer")?;
+            let draft = index_draft(entry)?;
+            if pointer.is_empty() && draft == document.draft() {
+                return Ok(Arc::clone(document));
+            }
+            let contents = document.contents().pointer(pointer).ok_or_else(|| {
+                Error::invalid_index(format!("pointer '{pointer}' does not exist"))
+            })?;
+            Ok(Arc::new(Resource::new(contents.clone(), draft)))
+        };
+
+        let mut resources = ResourceMap::new();
+        for entry in index_entries(&index, "resources")? {
+            resources.insert(index_uri(entry)?, locate(entry)?);
+        }
+        let mut anchors = AHashMap::new();
+        for entry in index_entries(&index, "anchors")? {
+            let name = index_str(entry, "name")?.to_string();
+            let resource = locate(entry)?;
+            let draft = resource.draft();
+            let anchor = match entry.get("dynamic").and_then(Value::as_bool) {
+                Some(false) => Anchor::Default {
+                    draft,
+                    name: name.clone(),
+                    resource,
+                },
+                Some(true) => Anchor::Dynamic {
+                    draft,
+                    name: name.clone(),
+                    resource,
+                },
+                None => return Err(Error::invalid_index("missing field 'dynamic'")),
+            };
+            anchors.insert(AnchorKey::new(index_uri(entry)?, name), anchor);
+        }
+        Ok(Registry {
+            resources,
+            anchors,
+            documents: document_uris,
+            allowed_schemes: None,
+            resolving_cache: RwLock::new(AHashMap::new()),
+        })
+    }
+}
+
+fn index_entries<'a>(index: &'a Value, name: &str) -> Result<&'a Vec<Value>, Error> {
+    index
+        .get(name)
+        .and_then(Value::as_array)
+        .ok_or_else(|| Error::invalid_index(format!("missing field '{name}'")))
+}
+
+fn index_str<'a>(entry: &'a Value, name: &str) -> Result<&'a str, Error> {
+    entry
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::invalid_index(format!("missing field '{name}'")))
+}
+
+fn index_uri(entry: &Value) -> Result<Uri<String>, Error> {
+    uri::from_str(index_str(entry, "uri")?)
+}
+
+fn index_draft(entry: &Value) -> Result<Draft, Error> {
+    entry
+        .get("draft")
+        .and_then(Value::as_u64)
+        .and_then(|idx| Draft::all().get(usize::try_from(idx).ok()?))
+        .copied()
+        .ok_or_else(|| Error::invalid_index("unknown draft"))
+}
+
+fn draft_to_index(draft: Draft) -> usize {
+    Draft::all()
+        .iter()
+        .position(|known| *known == draft)
+        .expect("All drafts are listed")
+}
+
+/// Locations of all values within a list of documents, grouped by the hash of the value.
+struct DocumentLocations<'a> {
+    by_hash: AHashMap<u64, Vec<(usize, String, &'a Value)>>,
+}
+
+impl<'a> DocumentLocations<'a> {
+    fn new(documents: impl Iterator<Item = &'a Value>) -> DocumentLocations<'a> {
+        let mut by_hash: AHashMap<u64, Vec<_>> = AHashMap::new();
+        for (document, contents) in documents.enumerate() {
+            let mut stack = vec![(String::new(), contents)];
+            while let Some((pointer, value)) = stack.pop() {
+                match value {
+                    Value::Array(items) => {
+                        for (idx, item) in items.iter().enumerate() {
+                            stack.push((format!("{pointer}/{idx}"), item));
+                        }
+                    }
+                    Value::Object(object) => {
+                        for (key, item) in object {
+                            let key = key.replace('~', "~0").replace('/', "~1");
+                            stack.push((format!("{pointer}/{key}"), item));
+                        }
+                    }
+                    _ => {}
+                }
+                let mut hasher = AHasher::default();
+                hash_value(value, &mut hasher);
+                by_hash
+                    .entry(hasher.finish())
+                    .or_default()
+                    .push((document, pointer, value));
+            }
+        }
+        DocumentLocations { by_hash }
+    }
+
+    fn find(&self, contents: &Value) -> Option<(usize, &str)> {
+        let mut hasher = AHasher::default();
+        hash_value(contents, &mut hasher);
+        self.by_hash
+            .get(&hasher.finish())?
+            .iter()
+            .find(|(_, _, value)| *value == contents)
+            .map(|(document, pointer, _)| (*document, pointer.as_str()))
+    }
+}
+
+/// Hash `value` independently of the platform, so the hash can be persisted.
+fn stable_hash(value: &Value) -> u64 {
+    let mut hasher = StableHasher(0xcbf2_9ce4_8422_2325);
+    hash_value(value, &mut hasher);
+    hasher.finish()
+}
+
+/// FNV-1a with all integers written in little-endian order.
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// Check the result of a retriever, distinguishing failed retrieval from unusable contents.
+fn check_retrieved(
+    uri: &str,
+    retrieved: Result<Value, Box<dyn std::error::Error + Send + Sync>>,
+    require_schema_documents: bool,
+) -> Result<Value, Error> {
+    match retrieved {
+        Ok(contents @ (Value::Object(_) | Value::Bool(_))) => Ok(contents),
+        Ok(contents) if require_schema_documents => {
+            let found = match contents {
+                Value::Null => "null",
+                Value::Number(_) => "a number",
+                Value::String(_) => "a string",
+                _ => "an array",
+            };
+            Err(Error::invalid_retrieved_schema(
+                uri,
+                format!("expected a JSON object or boolean, found {found}").into(),
+            ))
+        }
+        Ok(contents) => Ok(contents),
+        // Reading the document succeeded, but its contents are not JSON
+        Err(error)
+            if error
+                .downcast_ref::<serde_json::Error>()
+                .is_some_and(|error| !error.is_io()) =>
+        {
+            Err(Error::invalid_retrieved_schema(uri, error))
+        }
+        Err(error) => Err(Error::unretrievable(uri, error)),
+    }
+}
+
 fn process_resources(
     pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
     retriever: &dyn Retrieve,
     resources: &mut ResourceMap,
     anchors: &mut AHashMap<AnchorKey, Anchor>,
+    documents: &mut AHashSet<Uri<String>>,
     default_draft: Draft,
+    allowed_schemes: Option<&[String]>,
+    rewrites: &[(String, String)],
+    dedup_retrieved: bool,
+    require_schema_documents: bool,
+    mut unretrievable: Option<&mut Vec<(String, Error)>>,
 ) -> Result<(), Error> {
     let mut queue = VecDeque::with_capacity(32);
     let mut seen = AHashSet::new();
     let mut external = AHashSet::new();
+    // Retrieved resources grouped by the hash of their contents
+    let mut retrieved_by_hash: AHashMap<u64, Vec<Arc<Resource>>> = AHashMap::new();
+    // Bundled meta-schemas replaced by rewritten copies
+    let mut replaced = AHashSet::new();
 
     // Populate the resources & queue from the input
     for (uri, resource) in pairs {
         let uri = uri::from_str(uri.into().trim_end_matches('#'))?;
         let resource = Arc::new(resource);
         resources.insert(uri.clone(), Arc::clone(&resource));
+        documents.insert(uri.clone());
         queue.push_back((uri, resource));
     }
 
@@ -375,7 +1137,13 @@ fn process_resources(
             }
 
             // Collect references to external resources in this resource
-            collect_external_resources(&base, resource.contents(), &mut external, &mut seen)?;
+            collect_external_resources(
+                &base,
+                resource.contents(),
+                rewrites,
+                &mut external,
+                &mut seen,
+            )?;
 
             // Process subresources
             for subresource in resource.subresources() {
@@ -386,6 +1154,7 @@ fn process_resources(
                     collect_external_resources(
                         &base,
                         subresource.contents(),
+                        rewrites,
                         &mut external,
                         &mut seen,
                     )?;
@@ -393,6 +1162,7 @@ fn process_resources(
                     collect_external_resources(
                         &base,
                         subresource.contents(),
+                        rewrites,
                         &mut external,
                         &mut seen,
                     )?;
@@ -407,15 +1177,65 @@ fn process_resources(
         for uri in external.drain() {
             let mut fragmentless = uri.clone();
             fragmentless.set_fragment(None);
-            if !resources.contains_key(&fragmentless) {
-                let retrieved = retriever
-                    .retrieve(&fragmentless.borrow())
-                    .map_err(|err| Error::unretrievable(fragmentless.as_str(), err))?;
-                let resource = Arc::new(Resource::from_contents_and_specification(
-                    retrieved,
-                    default_draft,
-                )?);
+            let target = rewrite_uri(&fragmentless, rewrites)?;
+            let replaces_specification = target != fragmentless
+                && is_specification_reference(fragmentless.as_str())
+                && replaced.insert(fragmentless.clone());
+            if !resources.contains_key(&fragmentless) || replaces_specification {
+                let resource = if let Some(local) = resources.get(&target) {
+                    // Redirected to a resource that is already known
+                    Arc::clone(local)
+                } else {
+                    if let Some(allowed_schemes) = allowed_schemes {
+                        let scheme = target.scheme().as_str();
+                        if !allowed_schemes
+                            .iter()
+                            .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+                        {
+                            let error = Error::scheme_not_allowed(scheme);
+                            // When crawling, failures are collected and reported together
+                            if let Some(unretrievable) = unretrievable.as_mut() {
+                                unretrievable.push((fragmentless.as_str().to_string(), error));
+                                continue;
+                            }
+                            return Err(error);
+                        }
+                    }
+                    let retrieved = match check_retrieved(
+                        fragmentless.as_str(),
+                        retriever.retrieve(&target.borrow()),
+                        require_schema_documents,
+                    ) {
+                        Ok(retrieved) => retrieved,
+                        Err(error) => {
+                            if let Some(unretrievable) = unretrievable.as_mut() {
+                                unretrievable.push((fragmentless.as_str().to_string(), error));
+                                continue;
+                            }
+                            return Err(error);
+                        }
+                    };
+                    let mut resource = Arc::new(Resource::from_contents_and_specification(
+                        retrieved,
+                        default_draft,
+                    )?);
+                    if dedup_retrieved {
+                        let mut hasher = AHasher::default();
+                        hash_value(resource.contents(), &mut hasher);
+                        let copies = retrieved_by_hash.entry(hasher.finish()).or_default();
+                        if let Some(existing) = copies.iter().find(|existing| {
+                            existing.draft() == resource.draft()
+                                && existing.contents() == resource.contents()
+                        }) {
+                            resource = Arc::clone(existing);
+                        } else {
+                            copies.push(Arc::clone(&resource));
+                        }
+                    }
+                    resource
+                };
                 resources.insert(fragmentless.clone(), Arc::clone(&resource));
+                documents.insert(fragmentless.clone());
                 if let Some(fragment) = uri.fragment() {
                     // The original `$ref` could have a fragment that points to a place that won't
                     // be discovered via the regular sub-resources discovery. Therefore we need to
@@ -438,9 +1258,65 @@ fn process_resources(
     Ok(())
 }
 
+/// Apply the first rule in `rewrites` whose prefix matches `uri`.
+fn rewrite_uri(uri: &Uri<String>, rewrites: &[(String, String)]) -> Result<Uri<String>, Error> {
+    for (from, to) in rewrites {
+        if let Some(rest) = uri.as_str().strip_prefix(from.as_str()) {
+            return uri::from_str(&format!("{to}{rest}"));
+        }
+    }
+    Ok(uri.clone())
+}
+
+/// Hash `value` consistently with its `PartialEq` implementation, regardless of key order.
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    match value {
+        Value::Null => 0u8.hash(state),
+        Value::Bool(boolean) => {
+            1u8.hash(state);
+            boolean.hash(state);
+        }
+        Value::Number(number) => {
+            2u8.hash(state);
+            number.hash(state);
+        }
+        Value::String(string) => {
+            3u8.hash(state);
+            string.hash(state);
+        }
+        Value::Array(items) => {
+            4u8.hash(state);
+            items.len().hash(state);
+            for item in items {
+                hash_value(item, state);
+            }
+        }
+        Value::Object(object) => {
+            5u8.hash(state);
+            object.len().hash(state);
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            for (key, value) in entries {
+                key.hash(state);
+                hash_value(value, state);
+            }
+        }
+    }
+}
+
+/// Whether `reference` points to one of the bundled meta-schemas.
+fn is_specification_reference(reference: &str) -> bool {
+    reference.starts_with("https://json-schema.org/draft/2020-12/")
+        || reference.starts_with("https://json-schema.org/draft/2019-09/")
+        || reference.starts_with("http://json-schema.org/draft-07/")
+        || reference.starts_with("http://json-schema.org/draft-06/")
+        || reference.starts_with("http://json-schema.org/draft-04/")
+}
+
 fn collect_external_resources(
     base: &Uri<String>,
     contents: &Value,
+    rewrites: &[(String, String)],
     collected: &mut AHashSet<Uri<String>>,
     seen: &mut AHashSet<u64>,
 ) -> Result<(), Error> {
@@ -449,12 +1325,13 @@ fn collect_external_resources(
     }
     for key in ["$ref", "$schema"] {
         if let Some(reference) = contents.get(key).and_then(Value::as_str) {
+            // Declaring a draft in `$schema` does not need its meta-schema
             if reference.starts_with('#')
-                || reference.starts_with("https://json-schema.org/draft/2020-12/")
-                || reference.starts_with("https://json-schema.org/draft/2019-09/")
-                || reference.starts_with("http://json-schema.org/draft-07/")
-                || reference.starts_with("http://json-schema.org/draft-06/")
-                || reference.starts_with("http://json-schema.org/draft-04/")
+                || (is_specification_reference(reference)
+                    && (key == "$schema"
+                        || !rewrites
+                            .iter()
+                            .any(|(from, _)| reference.starts_with(from.as_str()))))
             {
                 // Not an external resource
                 return Ok(());
@@ -486,7 +1363,7 @@ mod tests {
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    use crate::{uri::from_str, Draft, Registry, Resource, Retrieve};
+    use crate::{uri::from_str, Draft, Error, Registry, Resource, Retrieve};
 
     use super::{RegistryOptions, SPECIFICATIONS};
 
@@ -783,6 +1660,587 @@ mod tests {
             .expect("Invalid resources");
     }
 
+    #[test]
+    fn test_allowed_scheme() {
+        let retriever =
+            create_test_retriever(&[("https://example.com/schema", json!({"type": "string"}))]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .allowed_schemes(["HTTPS"])
+            .try_new(
+                "http://example.com/root",
+                Resource::from_contents(json!({"$ref": "https://example.com/schema"}))
+                    .expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        assert!(resolver.lookup("https://example.com/schema").is_ok());
+    }
+
+    #[test_case("file:///etc/passwd", "file"; "file")]
+    #[test_case("http://example.com/schema", "http"; "http")]
+    fn test_blocked_scheme(reference: &str, scheme: &str) {
+        let retriever = create_test_retriever(&[(reference, json!({"type": "string"}))]);
+        let error = Registry::options()
+            .retriever(Box::new(retriever))
+            .allowed_schemes(["https"])
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({"$ref": reference})).expect("Invalid resource"),
+            )
+            .expect_err("Should fail");
+        assert!(
+            matches!(&error, crate::Error::SchemeNotAllowed { scheme: actual } if actual == scheme)
+        );
+        assert_eq!(
+            error.to_string(),
+            format!("Retrieving resources with the '{scheme}' scheme is not allowed")
+        );
+    }
+
+    #[test]
+    fn test_blocked_scheme_in_extended_registry() {
+        let registry = Registry::options()
+            .allowed_schemes(["https"])
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({})).expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        let retriever = create_test_retriever(&[("file:///schema", json!({"type": "string"}))]);
+        let error = registry
+            .try_with_resources_and_retriever(
+                [(
+                    "https://example.com/other",
+                    Resource::from_contents(json!({"$ref": "file:///schema"}))
+                        .expect("Invalid resource"),
+                )]
+                .into_iter(),
+                &retriever,
+                Draft::default(),
+            )
+            .expect_err("Should fail");
+        assert!(matches!(&error, crate::Error::SchemeNotAllowed { scheme } if scheme == "file"));
+    }
+
+    #[test_case(true; "dedup")]
+    #[test_case(false; "no dedup")]
+    fn test_dedup_retrieved(dedup: bool) {
+        let document = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": {"name": {"$anchor": "name", "type": "string"}}
+        });
+        let retriever = create_test_retriever(&[
+            ("https://a.example.com/schema", document.clone()),
+            ("https://b.example.com/schema", document),
+        ]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .dedup_retrieved(dedup)
+            .try_new(
+                "https://example.com/root",
+                Draft::Draft202012.create_resource(json!({
+                    "$defs": {
+                        "a": {"$ref": "https://a.example.com/schema"},
+                        "b": {"$ref": "https://b.example.com/schema"}
+                    }
+                })),
+            )
+            .expect("Invalid registry");
+        let find = |uri: &str| {
+            registry
+                .resources()
+                .find(|(key, _)| key.as_str() == uri)
+                .map(|(_, resource)| resource)
+                .expect("Resource is missing")
+        };
+        assert_eq!(
+            std::ptr::eq(
+                find("https://a.example.com/schema"),
+                find("https://b.example.com/schema")
+            ),
+            dedup
+        );
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        for base in [
+            "https://a.example.com/schema",
+            "https://b.example.com/schema",
+        ] {
+            for reference in ["#name", "#/$defs/name"] {
+                let resolved = resolver
+                    .lookup(&format!("{base}{reference}"))
+                    .expect("Lookup failed");
+                assert_eq!(
+                    resolved.contents(),
+                    &json!({"$anchor": "name", "type": "string"})
+                );
+                assert_eq!(resolved.resolver().base_uri().as_str(), base);
+            }
+        }
+    }
+
+    struct BytesRetriever(&'static [u8]);
+
+    impl Retrieve for BytesRetriever {
+        fn retrieve(
+            &self,
+            _: &Uri<&str>,
+        ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(serde_json::from_slice(self.0)?)
+        }
+    }
+
+    fn registry_with_retrieved(
+        retrieved: &'static [u8],
+        require_schema_documents: bool,
+    ) -> Result<Registry, Error> {
+        Registry::options()
+            .retriever(Box::new(BytesRetriever(retrieved)))
+            .require_schema_documents(require_schema_documents)
+            .try_new(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/remote"})),
+            )
+    }
+
+    #[test_case(b"<!DOCTYPE html><html>Not Found</html>", false; "html page")]
+    #[test_case(b"{\"type\": ", false; "truncated json")]
+    #[test_case(b"\"Not Found\"", true; "string")]
+    #[test_case(b"[{\"type\": \"string\"}]", true; "array")]
+    fn test_invalid_retrieved_schema(retrieved: &'static [u8], require_schema_documents: bool) {
+        let error =
+            registry_with_retrieved(retrieved, require_schema_documents).expect_err("Should fail");
+        let Error::InvalidRetrievedSchema { uri, .. } = &error else {
+            panic!("Unexpected error: {error}");
+        };
+        assert_eq!(uri, "http://example.com/remote");
+        assert!(error.source().is_some());
+        assert!(error.to_string().starts_with(
+            "Resource 'http://example.com/remote' was retrieved but is not a valid JSON Schema: "
+        ));
+    }
+
+    #[test_case(b"\"Not Found\""; "string")]
+    #[test_case(b"[{\"type\": \"string\"}]"; "array")]
+    #[test_case(b"false"; "boolean")]
+    fn test_non_schema_documents_allowed_by_default(retrieved: &'static [u8]) {
+        assert!(registry_with_retrieved(retrieved, false).is_ok());
+        assert_eq!(
+            registry_with_retrieved(retrieved, true).is_ok(),
+            retrieved == b"false"
+        );
+    }
+
+    #[test]
+    fn test_retrieval_failure_is_unretrievable() {
+        let error = Registry::options()
+            .retriever(Box::new(create_test_retriever(&[])))
+            .require_schema_documents(true)
+            .try_new(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/remote"})),
+            )
+            .expect_err("Should fail");
+        assert!(matches!(error, Error::Unretrievable { .. }));
+    }
+
+    fn drafts(registry: &Registry, uris: &[&str]) -> Vec<Draft> {
+        uris.iter()
+            .map(|uri| {
+                registry
+                    .try_resolver(uri)
+                    .and_then(|resolver| resolver.lookup(""))
+                    .expect("Lookup failed")
+                    .draft()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_default_draft() {
+        let retriever = create_test_retriever(&[
+            ("https://example.com/retrieved", json!({"type": "string"})),
+            (
+                "https://example.com/declared",
+                json!({"$schema": "https://json-schema.org/draft/2019-09/schema"}),
+            ),
+        ]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .draft(Draft::Draft6)
+            .default_draft(Draft::Draft4)
+            .try_from_resources(
+                [
+                    (
+                        "https://example.com/a",
+                        Resource::from_contents(json!({
+                            "properties": {
+                                "retrieved": {"$ref": "https://example.com/retrieved"},
+                                "declared": {"$ref": "https://example.com/declared"}
+                            }
+                        }))
+                        .expect("Invalid resource"),
+                    ),
+                    (
+                        "https://example.com/b",
+                        Resource::from_contents(json!({"minimum": 5})).expect("Invalid resource"),
+                    ),
+                    (
+                        "https://example.com/c",
+                        Resource::from_contents(json!({
+                            "$schema": "http://json-schema.org/draft-07/schema#"
+                        }))
+                        .expect("Invalid resource"),
+                    ),
+                    (
+                        "https://example.com/d",
+                        Draft::Draft202012.create_resource(json!({})),
+                    ),
+                ]
+                .into_iter(),
+            )
+            .expect("Invalid registry");
+        assert_eq!(
+            drafts(
+                &registry,
+                &[
+                    "https://example.com/a",
+                    "https://example.com/b",
+                    "https://example.com/c",
+                    "https://example.com/d",
+                    "https://example.com/retrieved",
+                    "https://example.com/declared"
+                ]
+            ),
+            [
+                Draft::Draft4,
+                Draft::Draft4,
+                Draft::Draft7,
+                Draft::Draft202012,
+                Draft::Draft4,
+                Draft::Draft201909
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_default_draft() {
+        let registry = Registry::options()
+            .draft(Draft::Draft6)
+            .try_new(
+                "https://example.com/a",
+                Resource::from_contents(json!({})).expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        assert_eq!(
+            drafts(&registry, &["https://example.com/a"]),
+            [Draft::Draft202012]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_to_local_resources() {
+        let registry = Registry::options()
+            .rewrite("https://json-schema.org/", "file:///mirror/")
+            .try_from_resources(
+                [
+                    (
+                        "https://example.com/root",
+                        json!({
+                            "$schema": "https://json-schema.org/draft/2020-12/schema",
+                            "$ref": "https://json-schema.org/learn/examples/address.schema.json"
+                        }),
+                    ),
+                    (
+                        "file:///mirror/learn/examples/address.schema.json",
+                        json!({
+                            "$schema": "https://json-schema.org/draft/2020-12/schema",
+                            "properties": {"city": {"$ref": "city.schema.json"}}
+                        }),
+                    ),
+                    (
+                        "file:///mirror/learn/examples/city.schema.json",
+                        json!({
+                            "$schema": "https://json-schema.org/draft/2020-12/schema",
+                            "$defs": {"name": {"$anchor": "name", "type": "string"}}
+                        }),
+                    ),
+                ]
+                .into_iter()
+                .map(|(uri, contents)| {
+                    (
+                        uri,
+                        Resource::from_contents(contents).expect("Invalid resource"),
+                    )
+                }),
+            )
+            .expect("Invalid registry");
+        // The default retriever fails, so every reference is served by the local copies
+        let resolver = registry
+            .try_resolver("https://example.com/root")
+            .expect("Invalid base URI");
+        let address = resolver
+            .lookup("https://json-schema.org/learn/examples/address.schema.json")
+            .expect("Lookup failed");
+        assert_eq!(
+            address.resolver().base_uri().as_str(),
+            "https://json-schema.org/learn/examples/address.schema.json"
+        );
+        let name = address
+            .resolver()
+            .lookup("city.schema.json#name")
+            .expect("Lookup failed");
+        assert_eq!(
+            name.contents(),
+            &json!({"$anchor": "name", "type": "string"})
+        );
+    }
+
+    #[test]
+    fn test_rewrite_specification() {
+        let root = json!({"$ref": "https://json-schema.org/draft/2020-12/schema"});
+        let mirror = json!({"title": "mirror", "$ref": "meta/core"});
+        let core = json!({"title": "mirror core"});
+        let resources = || {
+            [
+                ("https://example.com/root", root.clone()),
+                ("file:///mirror/2020-12/schema", mirror.clone()),
+                ("file:///mirror/2020-12/meta/core", core.clone()),
+            ]
+            .into_iter()
+            .map(|(uri, contents)| (uri, Draft::Draft202012.create_resource(contents)))
+        };
+        let options = || {
+            Registry::options().rewrite(
+                "https://json-schema.org/draft/2020-12/",
+                "file:///mirror/2020-12/",
+            )
+        };
+        // Both in a registry with the bundled meta-schemas and in an empty one
+        for registry in [
+            SPECIFICATIONS
+                .clone()
+                .try_with_resources_and_options(resources(), options())
+                .expect("Invalid registry"),
+            options()
+                .try_from_resources(resources())
+                .expect("Invalid registry"),
+        ] {
+            let resolver = registry.try_resolver("").expect("Invalid base URI");
+            let resolved = resolver
+                .lookup("https://json-schema.org/draft/2020-12/schema")
+                .expect("Lookup failed");
+            assert_eq!(resolved.contents(), &mirror);
+            let resolved = resolved
+                .resolver()
+                .lookup("meta/core")
+                .expect("Lookup failed");
+            assert_eq!(resolved.contents(), &core);
+        }
+        // Other drafts keep the bundled copies
+        let registry = SPECIFICATIONS
+            .clone()
+            .try_with_resources_and_options(resources(), options())
+            .expect("Invalid registry");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        let resolved = resolver
+            .lookup("http://json-schema.org/draft-07/schema#")
+            .expect("Lookup failed");
+        assert_eq!(
+            resolved.contents().get("title"),
+            Some(&json!("Core schema meta-schema"))
+        );
+    }
+
+    #[test]
+    fn test_rewrite_retrieves_target() {
+        let retriever =
+            create_test_retriever(&[("file:///mirror/schema", json!({"type": "string"}))]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .allowed_schemes(["file"])
+            .rewrite("https://example.com/unused/", "file:///unused/")
+            .rewrite("https://example.com/", "file:///mirror/")
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({"$ref": "https://example.com/schema"}))
+                    .expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        let resolved = resolver
+            .lookup("https://example.com/schema")
+            .expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!({"type": "string"}));
+    }
+
+    #[test]
+    fn test_rewrite_without_match() {
+        let retriever =
+            create_test_retriever(&[("https://example.com/schema", json!({"type": "string"}))]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .rewrite("https://json-schema.org/", "file:///mirror/")
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({"$ref": "https://example.com/schema"}))
+                    .expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        assert!(resolver.lookup("https://example.com/schema").is_ok());
+    }
+
+    fn crawl(root: Value) -> Result<Registry, crate::Error> {
+        let retriever = create_test_retriever(&[
+            (
+                "https://example.com/valid",
+                json!({"$ref": "https://example.com/leaf"}),
+            ),
+            (
+                "https://example.com/broken",
+                json!({"$ref": "https://example.com/missing"}),
+            ),
+            (
+                "https://example.com/leaf",
+                json!({"$defs": {"name": {"type": "string"}}}),
+            ),
+        ]);
+        Registry::options()
+            .retriever(Box::new(retriever))
+            .crawl(true)
+            .try_new(
+                "https://example.com/root",
+                Draft::Draft202012.create_resource(root),
+            )
+    }
+
+    #[test]
+    fn test_crawl_valid() {
+        let registry = crawl(json!({
+            "properties": {
+                "a": {"$ref": "https://example.com/valid"},
+                "b": {"$ref": "https://example.com/leaf#/$defs/name"},
+                "c": {"$ref": "#/$defs/local"}
+            },
+            "$defs": {"local": {"type": "integer"}}
+        }))
+        .expect("All references are resolvable");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        assert!(resolver.lookup("https://example.com/leaf").is_ok());
+    }
+
+    #[test]
+    fn test_crawl_reports_every_unresolvable_reference() {
+        let error = crawl(json!({
+            "properties": {
+                "valid": {"$ref": "https://example.com/valid"},
+                "broken": {"$ref": "https://example.com/broken"},
+                "pointer": {"$ref": "https://example.com/leaf#/$defs/missing"},
+                "local": {"$ref": "#/$defs/missing"}
+            }
+        }))
+        .expect_err("Should fail");
+        let crate::Error::UnresolvableReferences { references } = &error else {
+            panic!("Unexpected error: {error}");
+        };
+        let mut references: Vec<_> = references
+            .iter()
+            .map(|(reference, error)| (reference.as_str(), error.to_string()))
+            .collect();
+        references.sort();
+        assert_eq!(
+            references,
+            vec![
+                (
+                    "https://example.com/leaf#/$defs/missing",
+                    "Pointer '/$defs/missing' does not exist".to_string()
+                ),
+                (
+                    "https://example.com/missing",
+                    "Resource 'https://example.com/missing' is not present in a registry and retrieving it failed: Failed to find https://example.com/missing".to_string()
+                ),
+                (
+                    "https://example.com/root#/$defs/missing",
+                    "Pointer '/$defs/missing' does not exist".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_crawl_stops_at_first_failure() {
+        let error = Registry::options()
+            .retriever(Box::new(create_test_retriever(&[])))
+            .try_new(
+                "https://example.com/root",
+                Draft::Draft202012.create_resource(json!({
+                    "properties": {
+                        "pointer": {"$ref": "#/$defs/missing"},
+                        "remote": {"$ref": "https://example.com/missing"}
+                    }
+                })),
+            )
+            .expect_err("Should fail");
+        assert!(matches!(error, crate::Error::Unretrievable { .. }));
+    }
+
+    #[test]
+    fn test_bundle_with_embedded_resources() {
+        // No retrievable documents, so every reference must resolve within the bundle
+        let retriever = create_test_retriever(&[]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .try_new(
+                "https://example.com/bundle",
+                Draft::Draft202012.create_resource(json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "$id": "https://example.com/bundle",
+                    "$ref": "customer",
+                    "$defs": {
+                        "customer": {
+                            "$id": "https://example.com/customer",
+                            "properties": {"address": {"$ref": "address"}}
+                        },
+                        "address": {
+                            "$id": "https://example.com/address",
+                            "properties": {"resident": {"$ref": "customer#/properties"}}
+                        }
+                    }
+                })),
+            )
+            .expect("Invalid registry");
+        let resolver = registry
+            .try_resolver("https://example.com/bundle")
+            .expect("Invalid base URI");
+
+        let customer = resolver.lookup("customer").expect("Lookup failed");
+        assert_eq!(
+            customer.contents()["$id"],
+            json!("https://example.com/customer")
+        );
+        let address = customer
+            .resolver()
+            .lookup("address")
+            .expect("Lookup failed");
+        assert_eq!(
+            address.contents()["$id"],
+            json!("https://example.com/address")
+        );
+        let properties = address
+            .resolver()
+            .lookup("customer#/properties")
+            .expect("Lookup failed");
+        assert_eq!(
+            properties.contents(),
+            &json!({"address": {"$ref": "address"}})
+        );
+        assert_eq!(
+            properties.resolver().base_uri().as_str(),
+            "https://example.com/customer"
+        );
+    }
+
     #[test]
     fn test_registry_with_base_uri_fragment() {
         let input_resources = vec![
@@ -864,6 +2322,71 @@ mod tests {
         );
     }
 
+    #[test_case("https://json-schema.org/draft/2020-12/vocab/core", Draft::Draft202012; "2020-12")]
+    #[test_case("https://json-schema.org/draft/2019-09/vocab/core", Draft::Draft201909; "2019-09")]
+    fn test_detect_draft_from_vocabularies(core: &str, expected: Draft) {
+        let meta_schema = json!({
+            "$id": "https://example.com/meta",
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$vocabulary": {
+                core: true,
+                "https://example.com/vocab/custom": false
+            }
+        });
+        let registry = Registry::try_new(
+            "https://example.com/meta",
+            Resource::from_contents(meta_schema).expect("Invalid resource"),
+        )
+        .expect("Invalid registry");
+        let schema = json!({"$schema": "https://example.com/meta", "type": "string"});
+
+        assert_eq!(
+            registry
+                .detect_draft(Draft::Draft4, &schema)
+                .expect("Draft should be detected"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_detect_draft_without_vocabularies() {
+        let meta_schema = json!({
+            "$id": "https://example.com/meta",
+            "$schema": "https://json-schema.org/draft/2020-12/schema"
+        });
+        let registry = Registry::try_new(
+            "https://example.com/meta",
+            Resource::from_contents(meta_schema).expect("Invalid resource"),
+        )
+        .expect("Invalid registry");
+
+        // Known specifications are detected as usual
+        let schema = json!({"$schema": "http://json-schema.org/draft-07/schema#"});
+        assert_eq!(
+            registry
+                .detect_draft(Draft::Draft4, &schema)
+                .expect("Known"),
+            Draft::Draft7
+        );
+        assert_eq!(
+            registry
+                .detect_draft(Draft::Draft6, &json!({}))
+                .expect("Default"),
+            Draft::Draft6
+        );
+
+        for specification in ["https://example.com/meta", "https://example.com/unknown"] {
+            let schema = json!({"$schema": specification});
+            let error = registry
+                .detect_draft(Draft::Draft4, &schema)
+                .expect_err("Should fail");
+            assert_eq!(
+                error.to_string(),
+                format!("Unknown specification: {specification}")
+            );
+        }
+    }
+
     #[test]
     fn test_resolver_debug() {
         let registry = SPECIFICATIONS
@@ -924,4 +2447,153 @@ mod tests {
             .expect("Lookup failed");
         assert_eq!(resolved.contents(), &json!({"type": "object"}));
     }
+
+    fn index_documents() -> Vec<(&'static str, Resource)> {
+        vec![
+            (
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({
+                    "$ref": "other",
+                    "$defs": {
+                        "a/b": {"$anchor": "ab", "type": "integer"},
+                        "nested": {
+                            "$id": "nested",
+                            "$dynamicAnchor": "meta",
+                            "items": [{"type": "string"}]
+                        }
+                    }
+                })),
+            ),
+            (
+                "http://example.com/other",
+                Draft::Draft7.create_resource(json!({
+                    "definitions": {"legacy": {"$id": "#legacy", "type": "null"}}
+                })),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        let registry = SPECIFICATIONS
+            .clone()
+            .try_with_resources(index_documents().into_iter(), Draft::Draft202012)
+            .expect("Invalid resources");
+        let bytes = registry.to_index_bytes();
+        let restored =
+            Registry::from_index_bytes(index_documents().into_iter(), &bytes).expect("Valid index");
+
+        assert_eq!(restored.resources.len(), registry.resources.len());
+        for (uri, resource) in &registry.resources {
+            assert_eq!(restored.resources.get(uri), Some(resource), "{uri}");
+        }
+        assert_eq!(restored.anchors, registry.anchors);
+        assert_eq!(restored.documents, registry.documents);
+        // Serialization is deterministic
+        assert_eq!(restored.to_index_bytes(), bytes);
+
+        let resolver = restored
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        for (reference, expected) in [
+            ("#ab", json!({"$anchor": "ab", "type": "integer"})),
+            ("#/$defs/a~1b/type", json!("integer")),
+            ("nested#/items/0", json!({"type": "string"})),
+            ("other#legacy", json!({"$id": "#legacy", "type": "null"})),
+        ] {
+            let resolved = resolver.lookup(reference).expect("Lookup failed");
+            assert_eq!(resolved.contents(), &expected, "{reference}");
+        }
+    }
+
+    #[test]
+    fn test_index_with_retrieved_document() {
+        let retriever =
+            create_test_retriever(&[("http://example.com/schema2", json!({"type": "object"}))]);
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .try_new(
+                "http://example.com",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/schema2"})),
+            )
+            .expect("Invalid resource");
+        let bytes = registry.to_index_bytes();
+        let documents = [
+            (
+                "http://example.com",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/schema2"})),
+            ),
+            (
+                "http://example.com/schema2",
+                Draft::Draft202012.create_resource(json!({"type": "object"})),
+            ),
+        ];
+        let restored =
+            Registry::from_index_bytes(documents.into_iter(), &bytes).expect("Valid index");
+        let resolver = restored.try_resolver("").expect("Invalid base URI");
+        let resolved = resolver
+            .lookup("http://example.com/schema2")
+            .expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!({"type": "object"}));
+    }
+
+    #[test_case(
+        vec![
+            ("http://example.com/root", Draft::Draft202012.create_resource(json!({"$defs": {}}))),
+            ("http://example.com/other", Draft::Draft7.create_resource(json!({}))),
+        ],
+        "Invalid registry index: document 'http://example.com/other' does not match the index";
+        "changed contents"
+    )]
+    #[test_case(
+        vec![
+            ("http://example.com/root", Draft::Draft201909.create_resource(json!({"$defs": {}}))),
+            ("http://example.com/other", Draft::Draft7.create_resource(json!({"definitions": {}}))),
+        ],
+        "Invalid registry index: document 'http://example.com/root' does not match the index";
+        "changed draft"
+    )]
+    #[test_case(
+        vec![("http://example.com/root", Draft::Draft202012.create_resource(json!({"$defs": {}})))],
+        "Invalid registry index: document 'http://example.com/other' is missing";
+        "missing document"
+    )]
+    #[test_case(
+        vec![
+            ("http://example.com/root", Draft::Draft202012.create_resource(json!({"$defs": {}}))),
+            ("http://example.com/other", Draft::Draft7.create_resource(json!({"definitions": {}}))),
+            ("http://example.com/extra", Draft::Draft7.create_resource(json!({}))),
+        ],
+        "Invalid registry index: document 'http://example.com/extra' is not in the index";
+        "unexpected document"
+    )]
+    fn test_index_mismatch(documents: Vec<(&str, Resource)>, expected: &str) {
+        let registry = Registry::try_from_resources(
+            [
+                (
+                    "http://example.com/root",
+                    Draft::Draft202012.create_resource(json!({"$defs": {}})),
+                ),
+                (
+                    "http://example.com/other",
+                    Draft::Draft7.create_resource(json!({"definitions": {}})),
+                ),
+            ]
+            .into_iter(),
+        )
+        .expect("Invalid resources");
+        let bytes = registry.to_index_bytes();
+        let error =
+            Registry::from_index_bytes(documents.into_iter(), &bytes).expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test_case(b"not json"; "not JSON")]
+    #[test_case(b"{\"version\": 0}"; "unknown version")]
+    #[test_case(b"{\"version\": 1, \"documents\": []}"; "missing resources")]
+    fn test_invalid_index(bytes: &[u8]) {
+        let error = Registry::from_index_bytes(std::iter::empty::<(&str, Resource)>(), bytes)
+            .expect_err("Should fail");
+        assert!(matches!(error, crate::Error::InvalidIndex { .. }));
+    }
 }
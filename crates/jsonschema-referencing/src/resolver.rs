@@ -174,6 +174,16 @@ impl<'r> Resolver<'r> {
     pub fn dynamic_scope(&self) -> List<Uri<String>> {
         self.scopes.clone()
     }
+    /// Iterate over the resolution scopes, from the outermost to the innermost.
+    ///
+    /// Yields the base URIs of the resources entered by following references, followed by the
+    /// current base URI. This is the chain that `$recursiveRef` and `$dynamicRef` are resolved
+    /// against.
+    pub fn scopes(&self) -> impl Iterator<Item = &Uri<String>> {
+        let mut scopes = self.scopes.iter().collect::<Vec<_>>();
+        scopes.reverse();
+        scopes.into_iter().chain(std::iter::once(&*self.base_uri))
+    }
     fn evolve(&self, base_uri: Arc<Uri<String>>) -> Resolver<'r> {
         if !self.base_uri.as_str().is_empty()
             && (self.scopes.is_empty() || base_uri != self.base_uri)
@@ -239,3 +249,148 @@ impl<'r> Resolved<'r> {
         (self.contents, self.resolver, self.draft)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{Draft, Registry};
+
+    fn nested_anchor() -> Value {
+        json!({
+            "$id": "http://example.com/root",
+            "$ref": "sub#foo",
+            "$defs": {
+                "sub": {
+                    "$id": "sub",
+                    "$defs": {"foo": {"$anchor": "foo", "type": "integer"}}
+                }
+            }
+        })
+    }
+
+    fn nested_anchor_registry() -> Registry {
+        Registry::try_new(
+            "http://example.com/root",
+            Draft::Draft202012.create_resource(nested_anchor()),
+        )
+        .expect("Invalid resources")
+    }
+
+    #[test]
+    fn test_anchor_in_nested_resource() {
+        let registry = nested_anchor_registry();
+        let resolver = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup("sub#foo").expect("Lookup failed");
+        assert_eq!(
+            resolved.contents(),
+            &json!({"$anchor": "foo", "type": "integer"})
+        );
+        assert_eq!(
+            resolved.resolver().base_uri().as_str(),
+            "http://example.com/sub"
+        );
+        // The anchor belongs to the nested resource, not to the root one
+        assert!(resolver.lookup("#foo").is_err());
+    }
+
+    #[test]
+    fn test_anchor_after_pointer_into_nested_resource() {
+        let registry = nested_anchor_registry();
+        let resolver = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        let sub = resolver.lookup("#/$defs/sub").expect("Lookup failed");
+        assert_eq!(sub.resolver().base_uri().as_str(), "http://example.com/sub");
+        let resolved = sub.resolver().lookup("#foo").expect("Lookup failed");
+        assert_eq!(
+            resolved.contents(),
+            &json!({"$anchor": "foo", "type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_anchor_in_subresource() {
+        let registry = nested_anchor_registry();
+        let resolver = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        let root = nested_anchor();
+        let sub = resolver
+            .in_subresource(Draft::Draft202012.create_resource_ref(&root["$defs"]["sub"]))
+            .expect("Invalid subresource");
+        assert_eq!(sub.base_uri().as_str(), "http://example.com/sub");
+        let resolved = sub.lookup("#foo").expect("Lookup failed");
+        assert_eq!(
+            resolved.contents(),
+            &json!({"$anchor": "foo", "type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_plain_name_id_in_nested_resource() {
+        let schema = Draft::Draft7.create_resource(json!({
+            "$id": "http://example.com/root",
+            "definitions": {
+                "sub": {
+                    "$id": "sub",
+                    "definitions": {"foo": {"$id": "#foo", "type": "integer"}}
+                }
+            }
+        }));
+        let registry =
+            Registry::try_new("http://example.com/root", schema).expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup("sub#foo").expect("Lookup failed");
+        assert_eq!(
+            resolved.contents(),
+            &json!({"$id": "#foo", "type": "integer"})
+        );
+        assert!(resolver.lookup("#foo").is_err());
+    }
+
+    #[test]
+    fn test_scopes() {
+        let schema = Draft::Draft202012.create_resource(json!({
+            "$id": "http://example.com/root",
+            "$defs": {
+                "a": {
+                    "$id": "a/",
+                    "$defs": {
+                        "b": {"$id": "b"}
+                    }
+                }
+            }
+        }));
+        let registry =
+            Registry::try_new("http://example.com/root", schema).expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        assert_eq!(
+            resolver
+                .scopes()
+                .map(fluent_uri::Uri::as_str)
+                .collect::<Vec<_>>(),
+            ["http://example.com/root"]
+        );
+        let a = resolver.lookup("a/").expect("Lookup failed");
+        let b = a.resolver().lookup("b").expect("Lookup failed");
+        assert_eq!(b.contents(), &json!({"$id": "b"}));
+        assert_eq!(
+            b.resolver()
+                .scopes()
+                .map(fluent_uri::Uri::as_str)
+                .collect::<Vec<_>>(),
+            [
+                "http://example.com/root",
+                "http://example.com/a/",
+                "http://example.com/a/b"
+            ]
+        );
+    }
+}
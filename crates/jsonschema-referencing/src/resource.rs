@@ -5,15 +5,29 @@ use serde_json::Value;
 use crate::{Anchor, Draft, Error, Resolved, Resolver, Segments};
 
 /// A document with a concrete interpretation under a JSON Schema specification.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Resource {
     contents: Value,
     draft: Draft,
+    /// Whether `draft` is a fallback because the contents don't declare `$schema`.
+    fallback: bool,
 }
 
+impl PartialEq for Resource {
+    fn eq(&self, other: &Self) -> bool {
+        self.contents == other.contents && self.draft == other.draft
+    }
+}
+
+impl Eq for Resource {}
+
 impl Resource {
     pub(crate) fn new(contents: Value, draft: Draft) -> Self {
-        Self { contents, draft }
+        Self {
+            contents,
+            draft,
+            fallback: false,
+        }
     }
     /// Resource contents.
     #[must_use]
@@ -38,7 +52,20 @@ impl Resource {
         contents: Value,
         draft: Draft,
     ) -> Result<Resource, Error> {
-        Ok(draft.detect(&contents)?.create_resource(contents))
+        let fallback = contents.get("$schema").and_then(Value::as_str).is_none();
+        Ok(Resource {
+            draft: draft.detect(&contents)?,
+            contents,
+            fallback,
+        })
+    }
+    /// Use `draft` if this resource's draft was not detected from `$schema`.
+    pub(crate) fn with_default_draft(self, draft: Draft) -> Resource {
+        if self.fallback {
+            Resource { draft, ..self }
+        } else {
+            self
+        }
     }
     /// Resource identifier.
     #[must_use]
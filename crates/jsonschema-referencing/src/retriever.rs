@@ -1,5 +1,7 @@
 use core::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
 
+use ahash::AHashMap;
 use fluent_uri::Uri;
 use serde_json::Value;
 
@@ -20,6 +22,12 @@ pub trait Retrieve: Send + Sync {
     fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>>;
 }
 
+impl<T: Retrieve + ?Sized> Retrieve for Arc<T> {
+    fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        (**self).retrieve(uri)
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DefaultRetrieverError;
 
@@ -39,3 +47,299 @@ impl Retrieve for DefaultRetriever {
         Err(Box::new(DefaultRetrieverError))
     }
 }
+
+#[derive(Debug, Clone)]
+struct MapRetrieverError {
+    uri: String,
+}
+
+impl fmt::Display for MapRetrieverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("Resource '{}' is not found", self.uri))
+    }
+}
+
+impl std::error::Error for MapRetrieverError {}
+
+/// A retriever serving resources from an in-memory map.
+///
+/// URIs are matched exactly, without the fragment part.
+///
+/// # Example
+///
+/// ```rust
+/// use referencing::{MapRetriever, Registry, Resource};
+/// use serde_json::json;
+///
+/// let retriever = MapRetriever::new()
+///     .with_resource("https://example.com/string", json!({"type": "string"}));
+/// let registry = Registry::options()
+///     .retriever(Box::new(retriever))
+///     .try_new(
+///         "https://example.com/root",
+///         Resource::from_contents(json!({"$ref": "https://example.com/string"}))
+///             .expect("Invalid resource"),
+///     )
+///     .expect("Invalid registry");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MapRetriever {
+    resources: AHashMap<String, Value>,
+}
+
+impl MapRetriever {
+    /// Create an empty [`MapRetriever`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a resource served under the given URI.
+    #[must_use]
+    pub fn with_resource(mut self, uri: impl Into<String>, contents: Value) -> Self {
+        self.insert(uri, contents);
+        self
+    }
+    fn insert(&mut self, uri: impl Into<String>, contents: Value) {
+        let mut uri = uri.into();
+        if uri.ends_with('#') {
+            uri.pop();
+        }
+        self.resources.insert(uri, contents);
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for MapRetriever {
+    fn from_iter<T: IntoIterator<Item = (K, Value)>>(iter: T) -> Self {
+        let mut retriever = MapRetriever::new();
+        for (uri, contents) in iter {
+            retriever.insert(uri, contents);
+        }
+        retriever
+    }
+}
+
+impl Retrieve for MapRetriever {
+    fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(contents) = self.resources.get(uri.as_str()) {
+            Ok(contents.clone())
+        } else {
+            Err(Box::new(MapRetrieverError {
+                uri: uri.as_str().to_string(),
+            }))
+        }
+    }
+}
+
+/// A single retrieval attempt recorded by [`RecordingRetriever`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retrieval {
+    /// The requested URI.
+    pub uri: String,
+    /// The error message if the retrieval failed.
+    pub error: Option<String>,
+}
+
+/// A shared log of retrievals made through a [`RecordingRetriever`].
+///
+/// Cloning the log is cheap, all clones observe the same entries.
+#[derive(Debug, Clone, Default)]
+pub struct RetrievalLog {
+    entries: Arc<Mutex<Vec<Retrieval>>>,
+}
+
+impl RetrievalLog {
+    /// All retrievals recorded so far, in the order they were made.
+    #[must_use]
+    pub fn entries(&self) -> Vec<Retrieval> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+    fn push(&self, retrieval: Retrieval) {
+        // Entries are only appended, so they stay consistent even if a holder panicked
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(retrieval);
+    }
+}
+
+/// A retriever that forwards requests to another retriever and records every requested URI.
+///
+/// Useful for auditing which external resources a schema pulls in. Take a handle to the log
+/// with [`RecordingRetriever::log`] before passing the retriever to a registry.
+///
+/// # Example
+///
+/// ```rust
+/// use referencing::{MapRetriever, RecordingRetriever, Registry, Resource};
+/// use serde_json::json;
+///
+/// let retriever = RecordingRetriever::new(
+///     MapRetriever::new().with_resource("https://example.com/string", json!({"type": "string"})),
+/// );
+/// let log = retriever.log();
+/// Registry::options()
+///     .retriever(Box::new(retriever))
+///     .try_new(
+///         "https://example.com/root",
+///         Resource::from_contents(json!({"$ref": "https://example.com/string"}))
+///             .expect("Invalid resource"),
+///     )
+///     .expect("Invalid registry");
+///
+/// let entries = log.entries();
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(entries[0].uri, "https://example.com/string");
+/// assert!(entries[0].error.is_none());
+/// ```
+#[derive(Debug)]
+pub struct RecordingRetriever<R> {
+    inner: R,
+    log: RetrievalLog,
+}
+
+impl<R: Retrieve> RecordingRetriever<R> {
+    /// Wrap `inner`, recording every retrieval it performs.
+    #[must_use]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            log: RetrievalLog::default(),
+        }
+    }
+    /// A handle to the log of retrievals made through this retriever.
+    #[must_use]
+    pub fn log(&self) -> RetrievalLog {
+        self.log.clone()
+    }
+    /// The wrapped retriever.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: Retrieve> Retrieve for RecordingRetriever<R> {
+    fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let result = self.inner.retrieve(uri);
+        self.log.push(Retrieval {
+            uri: uri.as_str().to_string(),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{MapRetriever, RecordingRetriever, Retrieval};
+    use crate::{Registry, Resource};
+
+    #[test]
+    fn test_map_retriever() {
+        let retriever: MapRetriever = [
+            (
+                "https://example.com/person.json",
+                json!({
+                    "properties": {"address": {"$ref": "address.json#/$defs/address"}}
+                }),
+            ),
+            (
+                "https://example.com/address.json#",
+                json!({"$defs": {"address": {"type": "object"}}}),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({"$ref": "https://example.com/person.json"}))
+                    .expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        let resolver = registry
+            .try_resolver("https://example.com/root")
+            .expect("Invalid base URI");
+        let resolved = resolver
+            .lookup("https://example.com/address.json#/$defs/address")
+            .expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!({"type": "object"}));
+    }
+
+    #[test]
+    fn test_map_retriever_missing() {
+        let retriever = MapRetriever::new()
+            .with_resource("https://example.com/string", json!({"type": "string"}));
+        let error = Registry::options()
+            .retriever(Box::new(retriever))
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({"$ref": "https://example.com/integer"}))
+                    .expect("Invalid resource"),
+            )
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Resource 'https://example.com/integer' is not present in a registry and retrieving it failed: Resource 'https://example.com/integer' is not found"
+        );
+    }
+
+    #[test]
+    fn test_recording_retriever() {
+        let retriever = RecordingRetriever::new(
+            MapRetriever::new()
+                .with_resource("https://example.com/string", json!({"type": "string"}))
+                .with_resource("https://example.com/integer", json!({"type": "integer"})),
+        );
+        let log = retriever.log();
+        let registry = Registry::options()
+            .retriever(Box::new(retriever))
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({
+                    "properties": {
+                        "name": {"$ref": "https://example.com/string"},
+                        "age": {"$ref": "https://example.com/integer#/type"}
+                    }
+                }))
+                .expect("Invalid resource"),
+            )
+            .expect("Invalid registry");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        for reference in ["https://example.com/string", "https://example.com/integer"] {
+            resolver.lookup(reference).expect("Lookup failed");
+        }
+        let mut uris: Vec<_> = log.entries().into_iter().map(|entry| entry.uri).collect();
+        uris.sort();
+        assert_eq!(
+            uris,
+            ["https://example.com/integer", "https://example.com/string"]
+        );
+    }
+
+    #[test]
+    fn test_recording_retriever_failure() {
+        let retriever = RecordingRetriever::new(MapRetriever::new());
+        let log = retriever.log();
+        Registry::options()
+            .retriever(Box::new(retriever))
+            .try_new(
+                "https://example.com/root",
+                Resource::from_contents(json!({"$ref": "https://example.com/missing"}))
+                    .expect("Invalid resource"),
+            )
+            .expect_err("Should fail");
+        assert_eq!(
+            log.entries(),
+            [Retrieval {
+                uri: "https://example.com/missing".to_string(),
+                error: Some("Resource 'https://example.com/missing' is not found".to_string()),
+            }]
+        );
+    }
+}
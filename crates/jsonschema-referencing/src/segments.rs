@@ -2,12 +2,25 @@ use std::borrow::Cow;
 
 /// Represents a sequence of segments in a JSON pointer.
 ///
-/// Used to track the path during JSON pointer resolution.
-pub(crate) struct Segments<'a>(Vec<Segment<'a>>);
+/// Used to track the path during JSON pointer resolution. Each segment is a single step of the
+/// pointer, i.e. an object key or an array index in the document, not a step in the schema path.
+/// While resolving, the sequence only holds the steps taken since the last subresource boundary,
+/// e.g. from `properties` to `foo` but not the steps before entering a subschema with its own `$id`.
+///
+/// ```rust
+/// use referencing::{Segment, Segments};
+///
+/// let segments = Segments::from(&[Segment::from("items"), Segment::from(0)][..]);
+/// let steps: Vec<_> = segments.iter().map(ToString::to_string).collect();
+/// assert_eq!(steps, ["items", "0"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Segments<'a>(Vec<Segment<'a>>);
 
 impl<'a> Segments<'a> {
     /// Creates a new, empty `Segments` instance.
-    pub(crate) fn new() -> Self {
+    #[must_use]
+    pub fn new() -> Self {
         Self(Vec::new())
     }
 
@@ -17,28 +30,105 @@ impl<'a> Segments<'a> {
     }
 
     /// Returns an iterator over the segments.
-    pub(crate) fn iter(&self) -> impl Iterator<Item = &Segment<'a>> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Segment<'a>> {
         self.0.iter()
     }
+
+    /// Returns the number of segments.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no segments.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> From<&[Segment<'a>]> for Segments<'a> {
+    fn from(segments: &[Segment<'a>]) -> Self {
+        Self(segments.to_vec())
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s Segments<'a> {
+    type Item = &'s Segment<'a>;
+    type IntoIter = std::slice::Iter<'s, Segment<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 /// Represents a single segment in a JSON pointer.
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub(crate) enum Segment<'a> {
+pub enum Segment<'a> {
     /// A string key for object properties.
     Key(Cow<'a, str>),
     /// A numeric index for array elements.
     Index(usize),
 }
 
+impl std::fmt::Display for Segment<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Segment::Key(key) => f.write_str(key),
+            Segment::Index(idx) => write!(f, "{idx}"),
+        }
+    }
+}
+
 impl<'a> From<Cow<'a, str>> for Segment<'a> {
     fn from(value: Cow<'a, str>) -> Self {
         Segment::Key(value)
     }
 }
 
+impl<'a> From<&'a str> for Segment<'a> {
+    fn from(value: &'a str) -> Self {
+        Segment::Key(Cow::Borrowed(value))
+    }
+}
+
 impl From<usize> for Segment<'_> {
     fn from(value: usize) -> Self {
         Segment::Index(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Segment, Segments};
+    use std::borrow::Cow;
+
+    #[test]
+    fn from_slice() {
+        let segments = Segments::from(
+            &[
+                Segment::from("properties"),
+                Segment::from(Cow::Owned("a/b".to_string())),
+                Segment::from(3),
+            ][..],
+        );
+        assert_eq!(segments.len(), 3);
+        assert!(!segments.is_empty());
+        assert_eq!(
+            (&segments).into_iter().collect::<Vec<_>>(),
+            [
+                &Segment::Key(Cow::Borrowed("properties")),
+                &Segment::Key(Cow::Borrowed("a/b")),
+                &Segment::Index(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let segments = Segments::new();
+        assert!(segments.is_empty());
+        assert_eq!(segments.iter().next(), None);
+        assert_eq!(segments, Segments::from(&[][..]));
+    }
+}
@@ -13,12 +13,10 @@ pub(crate) fn legacy_dollar_id(contents: &Value) -> Option<&str> {
     if object.contains_key("$ref") {
         return None;
     }
-    if let Some(id) = contents.get("$id").and_then(|id| id.as_str()) {
-        if !id.starts_with('#') {
-            return Some(id);
-        }
-    }
-    None
+    contents
+        .get("$id")
+        .and_then(|id| id.as_str())
+        .and_then(legacy_base)
 }
 
 pub(crate) fn legacy_id(contents: &Value) -> Option<&str> {
@@ -26,10 +24,18 @@ pub(crate) fn legacy_id(contents: &Value) -> Option<&str> {
     if object.contains_key("$ref") {
         return None;
     }
-    if let Some(id) = object.get("id").and_then(|id| id.as_str()) {
-        if !id.starts_with('#') {
-            return Some(id);
-        }
+    object
+        .get("id")
+        .and_then(|id| id.as_str())
+        .and_then(legacy_base)
+}
+
+/// Legacy identifiers may carry a plain-name fragment, e.g. `http://example.com/bar#foo`,
+/// that defines an anchor. Only the part before it changes the base URI.
+fn legacy_base(id: &str) -> Option<&str> {
+    if id.starts_with('#') {
+        None
+    } else {
+        Some(id.split_once('#').map_or(id, |(base, _)| base))
     }
-    None
 }
@@ -13,6 +13,21 @@ use crate::{
     Anchor, Error, Resolver, Resource, ResourceRef, Segments,
 };
 
+/// The role of a keyword in a schema, as classified by the JSON Schema specification.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum KeywordKind {
+    /// Applies subschemas to the instance or its parts, e.g. `properties` or `$ref`.
+    Applicator,
+    /// Produces a boolean result on its own, e.g. `minimum` or `required`.
+    Assertion,
+    /// Attaches information to the instance without affecting validity, e.g. `title`.
+    Annotation,
+    /// Identifies the schema or its dialect, e.g. `$id`, `$anchor` or `$schema`.
+    Identifier,
+    /// Reserves a location for other schemas or comments, e.g. `$defs` or `$comment`.
+    Reserved,
+}
+
 /// JSON Schema specification versions.
 #[non_exhaustive]
 #[derive(Debug, Default, PartialEq, Copy, Clone, Hash, Eq, PartialOrd, Ord)]
@@ -31,6 +46,22 @@ pub enum Draft {
 }
 
 impl Draft {
+    /// All supported drafts, from the oldest to the newest.
+    #[must_use]
+    pub const fn all() -> &'static [Draft] {
+        &[
+            Draft::Draft4,
+            Draft::Draft6,
+            Draft::Draft7,
+            Draft::Draft201909,
+            Draft::Draft202012,
+        ]
+    }
+    /// The newest supported draft.
+    #[must_use]
+    pub const fn latest() -> Draft {
+        Draft::Draft202012
+    }
     #[must_use]
     pub fn create_resource(self, contents: Value) -> Resource {
         Resource::new(contents, self)
@@ -176,6 +207,88 @@ impl Draft {
         }
     }
 
+    /// Classifies a keyword according to the vocabularies of this draft.
+    ///
+    /// Returns `None` for keywords that are not defined by the draft, e.g. `prefixItems` in
+    /// Draft 7. Besides the keywords recognized by [`Draft::is_known_keyword`], this covers
+    /// metadata keywords like `title` or `default`, which are always annotations.
+    ///
+    /// ```rust
+    /// use referencing::{Draft, KeywordKind};
+    ///
+    /// assert_eq!(Draft::Draft202012.keyword_kind("allOf"), Some(KeywordKind::Applicator));
+    /// assert_eq!(Draft::Draft7.keyword_kind("format"), Some(KeywordKind::Assertion));
+    /// assert_eq!(Draft::Draft202012.keyword_kind("format"), Some(KeywordKind::Annotation));
+    /// assert_eq!(Draft::Draft7.keyword_kind("$dynamicRef"), None);
+    /// ```
+    #[must_use]
+    pub fn keyword_kind(&self, keyword: &str) -> Option<KeywordKind> {
+        let kind = match keyword {
+            "$ref"
+            | "additionalItems"
+            | "additionalProperties"
+            | "allOf"
+            | "anyOf"
+            | "dependencies"
+            | "items"
+            | "not"
+            | "oneOf"
+            | "patternProperties"
+            | "properties" => KeywordKind::Applicator,
+            "enum" | "exclusiveMaximum" | "exclusiveMinimum" | "maxItems" | "maxLength"
+            | "maxProperties" | "maximum" | "minItems" | "minLength" | "minProperties"
+            | "minimum" | "multipleOf" | "pattern" | "required" | "type" | "uniqueItems" => {
+                KeywordKind::Assertion
+            }
+            "default" | "description" | "title" => KeywordKind::Annotation,
+            "$schema" => KeywordKind::Identifier,
+            "definitions" => KeywordKind::Reserved,
+
+            // Format validation is opt-in since Draft 2019-09
+            "format" if *self >= Draft::Draft201909 => KeywordKind::Annotation,
+            "format" => KeywordKind::Assertion,
+
+            "id" if *self == Draft::Draft4 => KeywordKind::Identifier,
+
+            "contains" | "propertyNames" if *self >= Draft::Draft6 => KeywordKind::Applicator,
+            "const" if *self >= Draft::Draft6 => KeywordKind::Assertion,
+            "contentEncoding" | "contentMediaType" | "examples" if *self >= Draft::Draft6 => {
+                KeywordKind::Annotation
+            }
+            "$id" if *self >= Draft::Draft6 => KeywordKind::Identifier,
+
+            "else" | "if" | "then" if *self >= Draft::Draft7 => KeywordKind::Applicator,
+            "readOnly" | "writeOnly" if *self >= Draft::Draft7 => KeywordKind::Annotation,
+            "$comment" if *self >= Draft::Draft7 => KeywordKind::Reserved,
+
+            "$recursiveRef"
+            | "dependentSchemas"
+            | "prefixItems"
+            | "unevaluatedItems"
+            | "unevaluatedProperties"
+                if *self >= Draft::Draft201909 =>
+            {
+                KeywordKind::Applicator
+            }
+            "dependentRequired" | "maxContains" | "minContains" if *self >= Draft::Draft201909 => {
+                KeywordKind::Assertion
+            }
+            "contentSchema" | "deprecated" if *self >= Draft::Draft201909 => {
+                KeywordKind::Annotation
+            }
+            "$anchor" | "$recursiveAnchor" | "$vocabulary" if *self >= Draft::Draft201909 => {
+                KeywordKind::Identifier
+            }
+            "$defs" if *self >= Draft::Draft201909 => KeywordKind::Reserved,
+
+            "$dynamicRef" if *self == Draft::Draft202012 => KeywordKind::Applicator,
+            "$dynamicAnchor" if *self == Draft::Draft202012 => KeywordKind::Identifier,
+
+            _ => return None,
+        };
+        Some(kind)
+    }
+
     pub(crate) fn default_vocabularies(self) -> VocabularySet {
         match self {
             Draft::Draft4 | Draft::Draft6 | Draft::Draft7 => VocabularySet::new(),
@@ -187,7 +300,7 @@ impl Draft {
 
 #[cfg(test)]
 mod tests {
-    use crate::Draft;
+    use crate::{Draft, KeywordKind};
     use serde_json::json;
     use test_case::test_case;
 
@@ -205,6 +318,35 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_all() {
+        let all = Draft::all();
+        for draft in [
+            Draft::Draft4,
+            Draft::Draft6,
+            Draft::Draft7,
+            Draft::Draft201909,
+            Draft::Draft202012,
+        ] {
+            // Fails to compile when a new variant is added, so the list above stays complete
+            match draft {
+                Draft::Draft4
+                | Draft::Draft6
+                | Draft::Draft7
+                | Draft::Draft201909
+                | Draft::Draft202012 => assert!(all.contains(&draft)),
+            }
+        }
+        assert_eq!(all.len(), 5);
+        assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_latest() {
+        assert_eq!(Draft::latest(), Draft::Draft202012);
+        assert_eq!(Draft::all().last(), Some(&Draft::latest()));
+    }
+
     #[test]
     fn test_unknown_specification() {
         let error = Draft::Draft7
@@ -223,4 +365,51 @@ mod tests {
         let result = draft.detect(&contents).expect("Failed to detect draft");
         assert_eq!(result, draft);
     }
+
+    #[test_case(Draft::Draft7, "properties", Some(KeywordKind::Applicator); "draft 7 applicator")]
+    #[test_case(Draft::Draft7, "if", Some(KeywordKind::Applicator); "draft 7 conditional")]
+    #[test_case(Draft::Draft7, "maxLength", Some(KeywordKind::Assertion); "draft 7 assertion")]
+    #[test_case(Draft::Draft7, "format", Some(KeywordKind::Assertion); "draft 7 format")]
+    #[test_case(Draft::Draft7, "readOnly", Some(KeywordKind::Annotation); "draft 7 annotation")]
+    #[test_case(Draft::Draft7, "$id", Some(KeywordKind::Identifier); "draft 7 identifier")]
+    #[test_case(Draft::Draft7, "definitions", Some(KeywordKind::Reserved); "draft 7 reserved")]
+    #[test_case(Draft::Draft7, "unevaluatedProperties", None; "draft 7 unknown")]
+    #[test_case(Draft::Draft4, "id", Some(KeywordKind::Identifier); "draft 4 legacy id")]
+    #[test_case(Draft::Draft4, "$id", None; "draft 4 dollar id")]
+    #[test_case(Draft::Draft202012, "unevaluatedProperties", Some(KeywordKind::Applicator); "draft 2020-12 applicator")]
+    #[test_case(Draft::Draft202012, "$dynamicRef", Some(KeywordKind::Applicator); "draft 2020-12 dynamic ref")]
+    #[test_case(Draft::Draft202012, "dependentRequired", Some(KeywordKind::Assertion); "draft 2020-12 assertion")]
+    #[test_case(Draft::Draft202012, "format", Some(KeywordKind::Annotation); "draft 2020-12 format")]
+    #[test_case(Draft::Draft202012, "deprecated", Some(KeywordKind::Annotation); "draft 2020-12 annotation")]
+    #[test_case(Draft::Draft202012, "$anchor", Some(KeywordKind::Identifier); "draft 2020-12 identifier")]
+    #[test_case(Draft::Draft202012, "$defs", Some(KeywordKind::Reserved); "draft 2020-12 reserved")]
+    #[test_case(Draft::Draft202012, "id", None; "draft 2020-12 legacy id")]
+    #[test_case(Draft::Draft202012, "x-custom", None; "draft 2020-12 unknown")]
+    fn test_keyword_kind(draft: Draft, keyword: &str, expected: Option<KeywordKind>) {
+        assert_eq!(draft.keyword_kind(keyword), expected);
+    }
+
+    #[test]
+    fn test_known_keywords_have_kind() {
+        let keywords = crate::meta::META_SCHEMAS
+            .iter()
+            .flat_map(|(_, schema)| {
+                schema["properties"]
+                    .as_object()
+                    .into_iter()
+                    .flat_map(|properties| properties.keys())
+            })
+            .map(String::as_str)
+            .chain(["$ref", "id", "$id", "$dynamicRef", "$recursiveRef"]);
+        for keyword in keywords {
+            for draft in Draft::all() {
+                if draft.is_known_keyword(keyword) {
+                    assert!(
+                        draft.keyword_kind(keyword).is_some(),
+                        "{keyword} in {draft:?}"
+                    );
+                }
+            }
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use fluent_uri::{
     encoding::{encoder::Fragment, EStr, Encoder},
-    Uri, UriRef,
+    error::ParseError,
+    IriRef, Uri, UriRef,
 };
 use once_cell::sync::Lazy;
 
-use crate::Error;
+use crate::{Error, UriError};
 pub use fluent_uri::encoding::encoder::Path;
 
 /// Resolves the URI reference against the given base URI and returns the target URI.
@@ -13,13 +14,60 @@ pub use fluent_uri::encoding::encoder::Path;
 ///
 /// Returns an error if base has not schema or there is a fragment.
 pub fn resolve_against(base: &Uri<&str>, uri: &str) -> Result<Uri<String>, Error> {
+    resolve(base, uri).map_err(Error::InvalidUri)
+}
+
+/// Joins a reference with a base URI, the same way the resolver does for `$ref` and `$id`.
+///
+/// Fragment-only references keep the base document and replace its fragment, relative
+/// references are resolved against the base path, and absolute references replace the base.
+/// A fragment in `base` is ignored.
+///
+/// ```rust
+/// use referencing::{uri, Uri};
+///
+/// let base = Uri::parse("https://example.com/schemas/root.json".to_string())?;
+/// assert_eq!(
+///     uri::join(&base, "#/$defs/name")?.as_str(),
+///     "https://example.com/schemas/root.json#/$defs/name"
+/// );
+/// assert_eq!(
+///     uri::join(&base, "person.json")?.as_str(),
+///     "https://example.com/schemas/person.json"
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if `reference` is not a valid URI reference or can't be resolved against
+/// `base`.
+pub fn join(base: &Uri<String>, reference: &str) -> Result<Uri<String>, UriError> {
+    if base.has_fragment() {
+        let mut base = base.clone();
+        base.set_fragment(None);
+        resolve(&base.borrow(), reference)
+    } else {
+        resolve(&base.borrow(), reference)
+    }
+}
+
+fn resolve(base: &Uri<&str>, uri: &str) -> Result<Uri<String>, UriError> {
     if uri.starts_with('#') && base.as_str().ends_with(uri) {
         return Ok(base.to_owned());
     }
-    Ok(UriRef::parse(uri)
-        .map_err(|error| Error::uri_reference_parsing_error(uri, error))?
+    Ok(parse_reference(uri)
+        .map_err(|error| UriError::Parse {
+            uri: uri.to_string(),
+            is_reference: true,
+            error,
+        })?
         .resolve_against(base)
-        .map_err(|error| Error::uri_resolving_error(uri, *base, error))?
+        .map_err(|error| UriError::Resolve {
+            uri: uri.to_string(),
+            base: base.to_owned(),
+            error,
+        })?
         .normalize())
 }
 
@@ -29,7 +77,7 @@ pub fn resolve_against(base: &Uri<&str>, uri: &str) -> Result<Uri<String>, Error
 ///
 /// Returns an error if the input string does not conform to URI-reference from RFC 3986.
 pub fn from_str(uri: &str) -> Result<Uri<String>, Error> {
-    let uriref = UriRef::parse(uri)
+    let uriref = parse_reference(uri)
         .map_err(|error| Error::uri_reference_parsing_error(uri, error))?
         .normalize();
     if uriref.has_scheme() {
@@ -43,6 +91,18 @@ pub fn from_str(uri: &str) -> Result<Uri<String>, Error> {
     }
 }
 
+/// Parses a URI reference, accepting IRI references as well.
+///
+/// Non-ASCII characters in IRI references are percent-encoded as described in RFC 3987,
+/// section 3.1, so `$id` and `$ref` values with such characters identify the same resources.
+fn parse_reference(reference: &str) -> Result<UriRef<String>, ParseError> {
+    if reference.is_ascii() {
+        UriRef::parse(reference).map(|uriref| uriref.to_owned())
+    } else {
+        IriRef::parse(reference).map(|iriref| iriref.to_uri_ref())
+    }
+}
+
 pub(crate) static DEFAULT_ROOT_URI: Lazy<Uri<String>> =
     Lazy::new(|| Uri::parse("json-schema:///".to_string()).expect("Invalid URI"));
 
@@ -75,3 +135,50 @@ pub fn encode_to(input: &str, buffer: &mut String) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{from_str, join};
+    use crate::{Uri, UriError};
+    use test_case::test_case;
+
+    #[test_case("https://example.com/root.json", "#/a", "https://example.com/root.json#/a"; "pointer fragment")]
+    #[test_case("https://example.com/root.json", "#name", "https://example.com/root.json#name"; "anchor fragment")]
+    #[test_case("https://example.com/root.json#/b", "#/a", "https://example.com/root.json#/a"; "fragment replaces fragment")]
+    #[test_case("https://example.com/root.json#/a", "#/a", "https://example.com/root.json#/a"; "same fragment")]
+    #[test_case("https://example.com/a/root.json", "other.json", "https://example.com/a/other.json"; "relative path")]
+    #[test_case("https://example.com/a/root.json", "../other.json#/a", "https://example.com/other.json#/a"; "parent path")]
+    #[test_case("https://example.com/a/root.json", "/other.json", "https://example.com/other.json"; "absolute path")]
+    #[test_case("https://example.com/root.json", "http://other.com/schema", "http://other.com/schema"; "absolute reference")]
+    #[test_case("urn:example:root", "#/a", "urn:example:root#/a"; "urn base")]
+    #[test_case("json-schema:///", "schema.json", "json-schema:///schema.json"; "default base")]
+    #[test_case("https://example.com/root.json", "名前", "https://example.com/%E5%90%8D%E5%89%8D"; "non-ASCII path")]
+    #[test_case("https://example.com/root.json", "#/$defs/名前", "https://example.com/root.json#/$defs/%E5%90%8D%E5%89%8D"; "non-ASCII fragment")]
+    fn test_join(base: &str, reference: &str, expected: &str) {
+        let base = Uri::parse(base.to_string()).expect("Invalid base URI");
+        let joined = join(&base, reference).expect("Failed to join");
+        assert_eq!(joined.as_str(), expected);
+    }
+
+    #[test]
+    fn test_from_str_iri() {
+        let uri = from_str("https://例え.test/schemas/ルート").expect("Invalid IRI");
+        assert_eq!(
+            uri.as_str(),
+            "https://%E4%BE%8B%E3%81%88.test/schemas/%E3%83%AB%E3%83%BC%E3%83%88"
+        );
+    }
+
+    #[test]
+    fn test_join_invalid_reference() {
+        let base = Uri::parse("https://example.com/root.json".to_string()).expect("Invalid URI");
+        let error = join(&base, "http://[invalid").expect_err("Should fail");
+        assert!(matches!(
+            error,
+            UriError::Parse {
+                is_reference: true,
+                ..
+            }
+        ));
+    }
+}
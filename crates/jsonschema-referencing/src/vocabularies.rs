@@ -1,7 +1,7 @@
 use core::fmt;
 use std::str::FromStr;
 
-use crate::{uri, Error};
+use crate::{uri, Draft, Error};
 use ahash::AHashSet;
 use fluent_uri::Uri;
 use serde_json::Value;
@@ -179,6 +179,18 @@ pub(crate) fn find(document: &Value) -> Result<Option<VocabularySet>, Error> {
     }
 }
 
+/// Infer the closest base draft from the core vocabulary declared in `$vocabulary`.
+pub(crate) fn infer_draft(document: &Value) -> Option<Draft> {
+    let vocabularies = document.get("$vocabulary")?.as_object()?;
+    if vocabularies.contains_key("https://json-schema.org/draft/2020-12/vocab/core") {
+        Some(Draft::Draft202012)
+    } else if vocabularies.contains_key("https://json-schema.org/draft/2019-09/vocab/core") {
+        Some(Draft::Draft201909)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
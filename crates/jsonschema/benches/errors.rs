@@ -1,6 +1,6 @@
 use benchmark::run_error_formatting_benchmarks;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 
 fn bench_error_formatting(c: &mut Criterion, name: &str, schema: &Value, instance: &Value) {
     let validator = jsonschema::validator_for(schema).expect("Valid schema");
@@ -13,10 +13,80 @@ fn bench_error_formatting(c: &mut Criterion, name: &str, schema: &Value, instanc
     );
 }
 
+fn bench_first_error(c: &mut Criterion) {
+    // A wide object where every property is an invalid additional one
+    let instance: Value = (0..1000)
+        .map(|idx| (format!("property{idx}"), json!(idx)))
+        .collect::<Map<_, _>>()
+        .into();
+    for (name, schema) in [
+        (
+            "additional_properties",
+            json!({"additionalProperties": {"type": "string"}}),
+        ),
+        (
+            "additional_properties_false",
+            json!({"additionalProperties": false, "properties": {"foo": {}}}),
+        ),
+        (
+            "additional_properties_patterns",
+            json!({"additionalProperties": false, "patternProperties": {"^x-": {}}}),
+        ),
+    ] {
+        let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+        c.bench_with_input(
+            BenchmarkId::new(format!("first_error/{name}"), "validate"),
+            &instance,
+            |b, instance| {
+                b.iter(|| {
+                    let _ = validator.validate(instance);
+                })
+            },
+        );
+        c.bench_with_input(
+            BenchmarkId::new(format!("first_error/{name}"), "iter_errors"),
+            &instance,
+            |b, instance| b.iter(|| validator.iter_errors(instance).next()),
+        );
+    }
+}
+
+fn bench_validate_patch(c: &mut Criterion) {
+    // A wide object where a single property changes between the two instances
+    let properties: Map<_, _> = (0..1000)
+        .map(|idx| (format!("property{idx}"), json!({"$ref": "#/$defs/entry"})))
+        .collect();
+    let schema = json!({
+        "type": "object",
+        "properties": properties,
+        "$defs": {"entry": {"type": "object", "properties": {"value": {"minimum": 0}}}}
+    });
+    let previous: Value = (0..1000)
+        .map(|idx| (format!("property{idx}"), json!({"value": idx})))
+        .collect::<Map<_, _>>()
+        .into();
+    let mut new = previous.clone();
+    new["property500"]["value"] = json!(-1);
+    let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+    c.bench_function("validate_patch/patch", |b| {
+        b.iter(|| validator.validate_patch(&previous, "/property500/value", &new))
+    });
+    c.bench_function("validate_patch/complete", |b| {
+        b.iter(|| {
+            (
+                validator.iter_errors(&previous).count(),
+                validator.iter_errors(&new).count(),
+            )
+        })
+    });
+}
+
 fn run_benchmarks(c: &mut Criterion) {
     run_error_formatting_benchmarks(&mut |name, schema, instance| {
         bench_error_formatting(c, name, schema, instance);
     });
+    bench_first_error(c);
+    bench_validate_patch(c);
 }
 
 criterion_group!(error_formatting, run_benchmarks);
@@ -43,6 +43,29 @@ fn bench_apply(c: &mut Criterion, name: &str, schema: &Value, instance: &Value)
     });
 }
 
+#[cfg(feature = "rayon")]
+fn bench_validate_batch(c: &mut Criterion, name: &str, schema: &Value, instance: &Value) {
+    let validator = jsonschema::validator_for(schema).expect("Valid schema");
+    let instances = vec![instance.clone(); 256];
+    c.bench_with_input(
+        BenchmarkId::new("validate_batch/sequential", name),
+        &instances,
+        |b, instances| {
+            b.iter(|| {
+                instances
+                    .iter()
+                    .map(|instance| validator.validate(instance))
+                    .collect::<Vec<_>>()
+            })
+        },
+    );
+    c.bench_with_input(
+        BenchmarkId::new("validate_batch/parallel", name),
+        &instances,
+        |b, instances| b.iter(|| validator.validate_par(instances)),
+    );
+}
+
 fn run_benchmarks(c: &mut Criterion) {
     for benchmark in Benchmark::iter() {
         benchmark.run(&mut |name, schema, instances| {
@@ -52,6 +75,8 @@ fn run_benchmarks(c: &mut Criterion) {
                 bench_is_valid(c, &name, schema, &instance.data);
                 bench_validate(c, &name, schema, &instance.data);
                 bench_apply(c, &name, schema, &instance.data);
+                #[cfg(feature = "rayon")]
+                bench_validate_batch(c, &name, schema, &instance.data);
             }
         });
     }
@@ -1,25 +1,33 @@
 use crate::{
     content_encoding::{ContentEncodingCheckType, ContentEncodingConverterType},
     content_media_type::ContentMediaTypeCheckType,
+    ecma::{self, RegexError},
     keywords::{
         self,
-        custom::{CustomKeyword, KeywordFactory},
+        custom::{CustomKeyword, KeywordContext, KeywordFactory},
+        enum_::EnumVariants,
         format::Format,
-        BoxedValidator, BuiltinKeyword, Keyword,
+        BoxedValidator, BuiltinKeyword, Keyword, Subschemas,
     },
     node::SchemaNode,
     options::ValidationOptions,
     paths::{Location, LocationSegment},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    scalar::ScalarSchema,
     ValidationError, Validator,
 };
 use ahash::{AHashMap, AHashSet};
+use once_cell::sync::OnceCell;
 use referencing::{
     uri, Draft, List, Registry, Resolved, Resolver, Resource, ResourceRef, Uri, Vocabulary,
     VocabularySet, SPECIFICATIONS,
 };
-use serde_json::Value;
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use serde_json::{Map, Value};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::Arc,
+};
 
 const DEFAULT_SCHEME: &str = "json-schema";
 pub(crate) const DEFAULT_ROOT_URL: &str = "json-schema:///";
@@ -38,6 +46,10 @@ pub(crate) struct Context<'a> {
     location: Location,
     pub(crate) draft: Draft,
     seen: Rc<RefCell<AHashSet<Arc<Uri<String>>>>>,
+    // `enum` variants keyed by their serialized form, shared by identical keywords
+    enums: Rc<RefCell<AHashMap<String, Arc<EnumVariants>>>>,
+    // Number of references compiled lazily so far, see `RefValidator`
+    lazy_references: Rc<Cell<usize>>,
 }
 
 impl<'a> Context<'a> {
@@ -57,6 +69,8 @@ impl<'a> Context<'a> {
             vocabularies,
             draft,
             seen: Rc::new(RefCell::new(AHashSet::new())),
+            enums: Rc::new(RefCell::new(AHashMap::new())),
+            lazy_references: Rc::new(Cell::new(0)),
         }
     }
     pub(crate) fn draft(&self) -> Draft {
@@ -80,6 +94,8 @@ impl<'a> Context<'a> {
             draft: resource.draft(),
             location: self.location.clone(),
             seen: Rc::clone(&self.seen),
+            enums: Rc::clone(&self.enums),
+            lazy_references: Rc::clone(&self.lazy_references),
         })
     }
     pub(crate) fn as_resource_ref<'r>(&'a self, contents: &'r Value) -> ResourceRef<'r> {
@@ -91,7 +107,13 @@ impl<'a> Context<'a> {
 
     #[inline]
     pub(crate) fn new_at_location(&'a self, chunk: impl Into<LocationSegment<'a>>) -> Self {
-        let location = self.location.join(chunk);
+        self.at_location(self.location.join(chunk))
+    }
+    /// Create a context for a descendant at an already computed `location`.
+    ///
+    /// The descendant must belong to the same resource as `self`.
+    #[inline]
+    pub(crate) fn at_location(&self, location: Location) -> Context<'a> {
         Context {
             config: Arc::clone(&self.config),
             registry: Arc::clone(&self.registry),
@@ -100,6 +122,8 @@ impl<'a> Context<'a> {
             location,
             draft: self.draft,
             seen: Rc::clone(&self.seen),
+            enums: Rc::clone(&self.enums),
+            lazy_references: Rc::clone(&self.lazy_references),
         }
     }
 
@@ -123,7 +147,10 @@ impl<'a> Context<'a> {
         self.draft.is_known_keyword(keyword)
     }
     pub(crate) fn supports_adjacent_validation(&self) -> bool {
-        !matches!(self.draft, Draft::Draft4 | Draft::Draft6 | Draft::Draft7)
+        self.config.applies_ref_siblings().unwrap_or(!matches!(
+            self.draft,
+            Draft::Draft4 | Draft::Draft6 | Draft::Draft7
+        ))
     }
     pub(crate) fn supports_integer_valued_numbers(&self) -> bool {
         !matches!(self.draft, Draft::Draft4)
@@ -134,9 +161,36 @@ impl<'a> Context<'a> {
             Draft::Draft4 | Draft::Draft6 | Draft::Draft7
         ))
     }
+    /// Translate an ECMA 262 `pattern` and compile it with the configured size limits.
+    pub(crate) fn compile_regex(&self, pattern: &str) -> Result<fancy_regex::Regex, RegexError> {
+        let pattern = ecma::to_rust_regex(pattern).map_err(|()| RegexError::Invalid)?;
+        let mut builder = fancy_regex::RegexBuilder::new(&pattern);
+        if let Some(limit) = self.config.regex_size_limit() {
+            builder.delegate_size_limit(limit);
+        }
+        if let Some(limit) = self.config.regex_dfa_size_limit() {
+            builder.delegate_dfa_size_limit(limit);
+        }
+        builder.build().map_err(|error| match error {
+            fancy_regex::Error::CompileError(fancy_regex::CompileError::InnerError(error)) => error
+                .size_limit()
+                .map_or(RegexError::Invalid, RegexError::TooLarge),
+            _ => RegexError::Invalid,
+        })
+    }
+    /// Whether compiled regular expressions depend on the configuration.
+    pub(crate) fn has_regex_limits(&self) -> bool {
+        self.config.regex_size_limit().is_some() || self.config.regex_dfa_size_limit().is_some()
+    }
     pub(crate) fn are_unknown_formats_ignored(&self) -> bool {
         self.config.are_unknown_formats_ignored()
     }
+    pub(crate) fn are_formats_strict(&self) -> bool {
+        self.config.are_formats_strict()
+    }
+    pub(crate) fn validates_content(&self) -> bool {
+        self.config.validates_content()
+    }
     pub(crate) fn with_resolver_and_draft(
         &'a self,
         resolver: Resolver<'a>,
@@ -152,6 +206,8 @@ impl<'a> Context<'a> {
             vocabularies,
             location,
             seen: Rc::clone(&self.seen),
+            enums: Rc::clone(&self.enums),
+            lazy_references: Rc::clone(&self.lazy_references),
         }
     }
     pub(crate) fn get_content_media_type_check(
@@ -179,22 +235,47 @@ impl<'a> Context<'a> {
     pub(crate) fn get_format(&self, format: &str) -> Option<(&String, &Arc<dyn Format>)> {
         self.config.get_format(format)
     }
+    /// Resolve `reference` against the base URI of the current resource.
+    pub(crate) fn resolve_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Arc<Uri<String>>, referencing::Error> {
+        self.resolver
+            .resolve_against(&self.resolver.base_uri().borrow(), reference)
+    }
     pub(crate) fn is_circular_reference(
         &self,
         reference: &str,
     ) -> Result<bool, referencing::Error> {
-        let uri = self
-            .resolver
-            .resolve_against(&self.resolver.base_uri().borrow(), reference)?;
+        let uri = self.resolve_reference(reference)?;
         Ok(self.seen.borrow().contains(&*uri))
     }
     pub(crate) fn mark_seen(&self, reference: &str) -> Result<(), referencing::Error> {
-        let uri = self
-            .resolver
-            .resolve_against(&self.resolver.base_uri().borrow(), reference)?;
+        let uri = self.resolve_reference(reference)?;
         self.seen.borrow_mut().insert(uri);
         Ok(())
     }
+    pub(crate) fn mark_lazy_reference(&self) {
+        self.lazy_references.set(self.lazy_references.get() + 1);
+    }
+    pub(crate) fn lazy_references(&self) -> usize {
+        self.lazy_references.get()
+    }
+    /// Variants of the `enum` keyword with the value `schema`, built once per compilation.
+    pub(crate) fn enum_variants(&self, schema: &Value, items: &[Value]) -> Arc<EnumVariants> {
+        Arc::clone(
+            self.enums
+                .borrow_mut()
+                .entry(schema.to_string())
+                .or_insert_with(|| {
+                    Arc::new(EnumVariants::new(
+                        schema,
+                        items,
+                        self.config.ignores_string_case(),
+                    ))
+                }),
+        )
+    }
 
     pub(crate) fn lookup_recursive_reference(&self) -> Result<Resolved<'_>, referencing::Error> {
         self.resolver.lookup_recursive_ref()
@@ -258,15 +339,12 @@ pub(crate) fn build_validator(
         resources.push((uri, resource));
     }
 
-    // Get retriever for external resources
-    let retriever = Arc::clone(&config.retriever);
-
     // Build a registry & resolver needed for validator compilation
-    let registry = Arc::new(SPECIFICATIONS.clone().try_with_resources_and_retriever(
-        resources.into_iter(),
-        &*retriever,
-        draft,
-    )?);
+    let registry =
+        Arc::new(SPECIFICATIONS.clone().try_with_resources_and_options(
+            resources.into_iter(),
+            config.registry_options(draft),
+        )?);
     let vocabularies = registry.find_vocabularies(draft, schema);
     let resolver = Rc::new(registry.try_resolver(&base_uri)?);
 
@@ -280,6 +358,259 @@ pub(crate) fn build_validator(
         Location::new(),
     );
 
+    check_schema(&config, draft, schema)?;
+
+    // Finally, compile the validator
+    let root = compile(&ctx, resource_ref).map_err(|err| err.to_owned())?;
+    let scalar = ScalarSchema::compile(&ctx, schema);
+    let base_uri: Arc<str> = base_uri.into();
+    let required = RequiredKeys::new(&ctx, &base_uri, "#".to_string());
+    Ok(Validator {
+        root: Arc::new(root),
+        scalar,
+        required,
+        config,
+        registry,
+        base_uri,
+        root_reference: Some("#".into()),
+        #[cfg(feature = "profile")]
+        last_profile: std::sync::Mutex::default(),
+    })
+}
+
+/// Build a validator for the resource stored in `registry` under `uri`.
+pub(crate) fn build_registry_validator(
+    mut config: ValidationOptions,
+    registry: &Registry,
+    uri: &Uri<String>,
+    anchor: Option<&str>,
+) -> Result<Validator, ValidationError<'static>> {
+    let registry = Arc::new(registry.clone());
+    let resolver = registry.try_resolver(uri.as_str())?;
+    let reference = match anchor {
+        // Pointers are not anchors, and an empty one would resolve to the whole document
+        Some(anchor) if anchor.is_empty() || anchor.starts_with('/') => {
+            return Err(referencing::Error::InvalidAnchor {
+                anchor: anchor.to_string(),
+            }
+            .into());
+        }
+        Some(anchor) => format!("#{anchor}"),
+        None => "#".to_string(),
+    };
+    let (schema, resolver, resource_draft) = resolver.lookup(&reference)?.into_inner();
+    // An explicitly set draft takes precedence over the one detected by the registry
+    let draft = *config.draft.get_or_insert(resource_draft);
+    let vocabularies = registry.find_vocabularies(draft, schema);
+
+    let config = Arc::new(config);
+    let ctx = Context::new(
+        Arc::clone(&config),
+        Arc::clone(&registry),
+        Rc::new(resolver),
+        vocabularies,
+        draft,
+        Location::new(),
+    );
+
+    check_schema(&config, draft, schema)?;
+
+    let root = compile(&ctx, ctx.as_resource_ref(schema)).map_err(|err| err.to_owned())?;
+    let scalar = ScalarSchema::compile(&ctx, schema);
+    let base_uri: Arc<str> = uri.as_str().into();
+    let required = RequiredKeys::new(&ctx, &base_uri, reference.clone());
+    Ok(Validator {
+        root: Arc::new(root),
+        scalar,
+        required,
+        config,
+        registry,
+        base_uri,
+        root_reference: Some(reference.into()),
+        #[cfg(feature = "profile")]
+        last_profile: std::sync::Mutex::default(),
+    })
+}
+
+/// Build a standalone validator for a subschema of an already compiled validator.
+pub(crate) fn build_subschema_validator(
+    validator: &Validator,
+    pointer: &str,
+) -> Result<Validator, ValidationError<'static>> {
+    let resolver = validator.registry.try_resolver(&validator.base_uri)?;
+    let root = resolver.lookup("#")?;
+    let vocabularies = validator
+        .registry
+        .find_vocabularies(root.draft(), root.contents());
+    let reference = if pointer.starts_with('#') {
+        pointer.to_string()
+    } else {
+        format!("#{pointer}")
+    };
+    let (contents, resolver, draft) = resolver.lookup(&reference)?.into_inner();
+    let ctx = Context::new(
+        Arc::clone(&validator.config),
+        Arc::clone(&validator.registry),
+        Rc::new(resolver),
+        vocabularies,
+        draft,
+        Location::new(),
+    );
+    let root = compile(&ctx, ctx.as_resource_ref(contents)).map_err(|err| err.to_owned())?;
+    let scalar = ScalarSchema::compile(&ctx, contents);
+    let required = RequiredKeys::new(&ctx, &validator.base_uri, reference);
+    Ok(Validator {
+        root: Arc::new(root),
+        scalar,
+        required,
+        config: Arc::clone(&validator.config),
+        registry: Arc::clone(&validator.registry),
+        base_uri: Arc::clone(&validator.base_uri),
+        // The subschema may be compiled with a draft different from the validator's one
+        root_reference: None,
+        #[cfg(feature = "profile")]
+        last_profile: std::sync::Mutex::default(),
+    })
+}
+
+/// Property names that a schema requires regardless of the instance values.
+///
+/// Collecting them follows `allOf` and `$ref`, so it is deferred until
+/// [`Validator::check_required`] needs them.
+#[derive(Debug)]
+pub(crate) struct RequiredKeys {
+    source: RequiredSource,
+    keys: OnceCell<Box<[String]>>,
+}
+
+#[derive(Debug)]
+enum RequiredSource {
+    /// The schema at `reference` within the `base_uri` resource.
+    Schema {
+        config: Arc<ValidationOptions>,
+        registry: Arc<Registry>,
+        base_uri: Arc<str>,
+        reference: String,
+        draft: Draft,
+    },
+    /// Keys of two validators combined with [`Validator::and`].
+    Both(Arc<RequiredKeys>, Arc<RequiredKeys>),
+}
+
+impl RequiredKeys {
+    fn new(ctx: &Context, base_uri: &Arc<str>, reference: String) -> Arc<RequiredKeys> {
+        Arc::new(RequiredKeys {
+            source: RequiredSource::Schema {
+                config: Arc::clone(&ctx.config),
+                registry: Arc::clone(&ctx.registry),
+                base_uri: Arc::clone(base_uri),
+                reference,
+                draft: ctx.draft(),
+            },
+            keys: OnceCell::new(),
+        })
+    }
+
+    pub(crate) fn both(left: &Arc<RequiredKeys>, right: &Arc<RequiredKeys>) -> Arc<RequiredKeys> {
+        Arc::new(RequiredKeys {
+            source: RequiredSource::Both(Arc::clone(left), Arc::clone(right)),
+            keys: OnceCell::new(),
+        })
+    }
+
+    pub(crate) fn get(&self) -> &[String] {
+        self.keys.get_or_init(|| match &self.source {
+            RequiredSource::Schema {
+                config,
+                registry,
+                base_uri,
+                reference,
+                draft,
+            } => {
+                let mut keys = Vec::new();
+                if let Ok(resolved) = registry
+                    .try_resolver(base_uri)
+                    .and_then(|resolver| resolver.lookup(reference))
+                {
+                    let (contents, resolver, _) = resolved.into_inner();
+                    let draft = draft.detect(contents).unwrap_or_default();
+                    if let Ok(resolver) =
+                        resolver.in_subresource(draft.create_resource_ref(contents))
+                    {
+                        collect_required(
+                            config,
+                            &resolver,
+                            draft,
+                            contents,
+                            &mut AHashSet::new(),
+                            &mut keys,
+                        );
+                    }
+                }
+                keys.into_boxed_slice()
+            }
+            RequiredSource::Both(left, right) => {
+                let left = left.get();
+                left.iter()
+                    .chain(right.get().iter().filter(|key| !left.contains(key)))
+                    .cloned()
+                    .collect()
+            }
+        })
+    }
+}
+
+/// Collect names from `required` in `schema` itself, its `allOf` subschemas and `$ref` targets.
+fn collect_required(
+    config: &ValidationOptions,
+    resolver: &Resolver,
+    draft: Draft,
+    schema: &Value,
+    seen: &mut AHashSet<*const Value>,
+    keys: &mut Vec<String>,
+) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+    // Circular references don't require anything new
+    if !seen.insert(schema) {
+        return;
+    }
+    if let Some(Value::String(reference)) = object.get("$ref") {
+        if let Ok(resolved) = resolver.lookup(reference) {
+            let (contents, resolver, draft) = resolved.into_inner();
+            collect_required(config, &resolver, draft, contents, seen, keys);
+        }
+        if !config
+            .applies_ref_siblings()
+            .unwrap_or(draft >= Draft::Draft201909)
+        {
+            return;
+        }
+    }
+    if let Some(Value::Array(required)) = object.get("required") {
+        for key in required.iter().filter_map(Value::as_str) {
+            if !keys.iter().any(|existing| existing == key) {
+                keys.push(key.to_string());
+            }
+        }
+    }
+    if let Some(Value::Array(subschemas)) = object.get("allOf") {
+        for subschema in subschemas {
+            let draft = draft.detect(subschema).unwrap_or_default();
+            if let Ok(resolver) = resolver.in_subresource(draft.create_resource_ref(subschema)) {
+                collect_required(config, &resolver, draft, subschema, seen, keys);
+            }
+        }
+    }
+}
+
+/// Validate `schema` against the meta-schema of `draft` and apply other build-time checks.
+fn check_schema(
+    config: &ValidationOptions,
+    draft: Draft,
+    schema: &Value,
+) -> Result<(), ValidationError<'static>> {
     // Validate the schema itself
     if config.validate_schema {
         if let Err(error) = {
@@ -298,9 +629,78 @@ pub(crate) fn build_validator(
         }
     }
 
-    // Finally, compile the validator
-    let root = compile(&ctx, resource_ref).map_err(|err| err.to_owned())?;
-    Ok(Validator { root, config })
+    if config.are_comments_forbidden() {
+        let mut comments = Vec::new();
+        collect_comments(schema, &Location::new(), &mut comments);
+        if let Some(first) = comments.first() {
+            let message = format!(
+                "`$comment` is not allowed: {}",
+                comments
+                    .iter()
+                    .map(Location::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return Err(ValidationError::custom(
+                Location::new(),
+                first.clone(),
+                schema.pointer(first.as_str()).unwrap_or(schema),
+                message,
+            )
+            .to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Collect locations of all `$comment` keywords within the given schema.
+fn collect_comments(schema: &Value, location: &Location, comments: &mut Vec<Location>) {
+    walk_subschemas(schema, location, &mut |subschema, location| {
+        if subschema.contains_key("$comment") {
+            comments.push(location.join("$comment"));
+        }
+    });
+}
+
+/// Call `visit` for the given schema and all of its subschemas with their locations.
+pub(crate) fn walk_subschemas<'a>(
+    schema: &'a Value,
+    location: &Location,
+    visit: &mut impl FnMut(&'a Map<String, Value>, &Location),
+) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+    visit(schema, location);
+    for (keyword, value) in schema {
+        let subschemas = match keyword.as_str() {
+            // Subschemas that are not keywords on their own
+            "$defs" | "definitions" => Some(Subschemas::Map),
+            "then" | "else" => Some(Subschemas::Inline),
+            name => BuiltinKeyword::by_name(name).and_then(BuiltinKeyword::subschemas),
+        };
+        match subschemas {
+            Some(Subschemas::Map) => {
+                if let Value::Object(subschemas) = value {
+                    let location = location.join(keyword);
+                    for (name, subschema) in subschemas {
+                        walk_subschemas(subschema, &location.join(name), visit);
+                    }
+                }
+            }
+            Some(Subschemas::Inline) => {
+                let location = location.join(keyword);
+                if let Value::Array(subschemas) = value {
+                    for (idx, subschema) in subschemas.iter().enumerate() {
+                        walk_subschemas(subschema, &location.join(idx), visit);
+                    }
+                } else {
+                    walk_subschemas(value, &location, visit);
+                }
+            }
+            None => {}
+        }
+    }
 }
 
 /// Compile a JSON Schema instance to a tree of nodes.
@@ -366,8 +766,8 @@ pub(crate) fn compile_with<'a>(
             for (keyword, value) in schema {
                 // Check if this keyword is overridden, then check the standard definitions
                 if let Some(factory) = ctx.get_keyword_factory(keyword) {
-                    let path = ctx.location().join(keyword);
-                    let validator = CustomKeyword::new(factory.init(schema, value, path)?);
+                    let context = KeywordContext::new(ctx.location().join(keyword), ctx.base_uri());
+                    let validator = CustomKeyword::new(factory.init(schema, value, context)?);
                     let validator: BoxedValidator = Box::new(validator);
                     validators.push((Keyword::custom(keyword), validator));
                 } else if let Some((keyword, validator)) = keywords::get_for_draft(ctx, keyword)
@@ -379,6 +779,8 @@ pub(crate) fn compile_with<'a>(
                     annotations.insert(keyword.to_string(), value.clone());
                 }
             }
+            // Keep the evaluation and error order independent of the `Map` implementation
+            validators.sort_by(|(left, _), (right, _)| left.as_str().cmp(right.as_str()));
             let annotations = if annotations.is_empty() {
                 None
             } else {
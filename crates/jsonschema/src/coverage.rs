@@ -0,0 +1,140 @@
+//! Accumulation of keyword coverage over many instances.
+use crate::{paths::Location, TraceRecord, Validator};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Keyword coverage of a schema accumulated over many instances.
+///
+/// Feed it the records of [`Validator::trace`] for every instance of a corpus, then inspect
+/// which keywords were never satisfied. A keyword counts as covered once at least one instance
+/// passed it. Applicators evaluate all of their subschemas while tracing, e.g. every `anyOf`
+/// branch, so a branch that no instance takes is reported through its failing keywords.
+///
+/// Only keywords reported by [`Validator::trace`] are considered. Keywords that don't apply to
+/// an instance pass it, e.g. `minimum` for a string, and count as covered.
+///
+/// ```rust
+/// use jsonschema::CoverageCollector;
+/// use serde_json::json;
+///
+/// let schema = json!({"anyOf": [{"type": "string"}, {"type": "integer"}]});
+/// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+///
+/// let mut coverage = CoverageCollector::new();
+/// for instance in [json!("a"), json!("b")] {
+///     validator.trace(&instance, |record| coverage.record(record));
+/// }
+/// let uncovered: Vec<_> = coverage
+///     .uncovered()
+///     .map(|location| location.as_str())
+///     .collect();
+/// assert_eq!(uncovered, ["/anyOf/1/type"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CoverageCollector {
+    /// Evaluated keywords and whether any instance satisfied them, sorted by location.
+    keywords: BTreeMap<String, (Location, bool)>,
+}
+
+impl CoverageCollector {
+    /// Create an empty collector.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Account for a single keyword evaluation.
+    pub fn record(&mut self, record: TraceRecord) {
+        if let Some((_, covered)) = self.keywords.get_mut(record.keyword_location.as_str()) {
+            *covered |= record.valid;
+        } else {
+            self.keywords.insert(
+                record.keyword_location.as_str().to_string(),
+                (record.keyword_location, record.valid),
+            );
+        }
+    }
+    /// Trace `instance` with `validator` and account for every evaluated keyword.
+    ///
+    /// Returns whether the instance is valid.
+    pub fn observe(&mut self, validator: &Validator, instance: &Value) -> bool {
+        validator.trace(instance, |record| self.record(record))
+    }
+    /// Locations of keywords satisfied by at least one instance, sorted.
+    pub fn covered(&self) -> impl Iterator<Item = &Location> {
+        self.keywords
+            .values()
+            .filter(|(_, covered)| *covered)
+            .map(|(location, _)| location)
+    }
+    /// Locations of keywords that were evaluated but never satisfied, sorted.
+    pub fn uncovered(&self) -> impl Iterator<Item = &Location> {
+        self.keywords
+            .values()
+            .filter(|(_, covered)| !*covered)
+            .map(|(location, _)| location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CoverageCollector;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn uncovered(schema: &Value, instances: &[Value]) -> Vec<String> {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let mut coverage = CoverageCollector::new();
+        for instance in instances {
+            coverage.observe(&validator, instance);
+        }
+        coverage
+            .uncovered()
+            .map(|location| location.as_str().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn any_of_single_branch() {
+        let schema = json!({
+            "anyOf": [
+                {"type": "string", "minLength": 2},
+                {"type": "integer", "minimum": 0}
+            ]
+        });
+        assert_eq!(
+            uncovered(&schema, &[json!("ab"), json!("cd")]),
+            // `minimum` ignores strings and passes
+            ["/anyOf/1/type"]
+        );
+    }
+
+    #[test_case(&[json!("ab"), json!(5)]; "both branches")]
+    #[test_case(&[json!("ab"), json!(5), json!(-1)]; "invalid instance")]
+    fn any_of_all_branches(instances: &[Value]) {
+        let schema = json!({
+            "anyOf": [
+                {"type": "string", "minLength": 2},
+                {"type": "integer", "minimum": 0}
+            ]
+        });
+        assert!(uncovered(&schema, instances).is_empty());
+    }
+
+    #[test]
+    fn covered() {
+        let validator = crate::validator_for(&json!({"items": {"type": "integer"}, "maxItems": 1}))
+            .expect("Invalid schema");
+        let mut coverage = CoverageCollector::new();
+        assert!(!coverage.observe(&validator, &json!([1, "a"])));
+        let covered: Vec<_> = coverage
+            .covered()
+            .map(|location| location.as_str())
+            .collect();
+        assert_eq!(covered, ["/items/type"]);
+        let uncovered: Vec<_> = coverage
+            .uncovered()
+            .map(|location| location.as_str())
+            .collect();
+        assert_eq!(uncovered, ["/items", "/maxItems"]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Filling in `default` values of properties missing from an instance.
+use crate::{ValidationOptions, Validator};
+use referencing::{Draft, Resolver};
+use serde_json::Value;
+
+pub(crate) fn apply_defaults(validator: &Validator, instance: &Value) -> Value {
+    let mut instance = instance.clone();
+    let Some(reference) = validator.root_reference.as_deref() else {
+        return instance;
+    };
+    let Ok(resolver) = validator.registry.try_resolver(&validator.base_uri) else {
+        return instance;
+    };
+    let Ok(root) = resolver.lookup(reference) else {
+        return instance;
+    };
+    let (schema, resolver, draft) = root.into_inner();
+    if let Some(scope) = Scope::enter(schema, &resolver, draft) {
+        let schemas = applicable(&validator.config, scope);
+        fill(&validator.config, &schemas, &mut instance, &mut Vec::new());
+    }
+    instance
+}
+
+/// A schema together with the resolver for its resource.
+struct Scope<'r> {
+    schema: &'r Value,
+    resolver: Resolver<'r>,
+    draft: Draft,
+}
+
+impl<'r> Scope<'r> {
+    /// Enter `schema` the same way as the compiler does for any subschema.
+    fn enter(schema: &'r Value, resolver: &Resolver<'r>, draft: Draft) -> Option<Scope<'r>> {
+        let draft = draft.detect(schema).unwrap_or_default();
+        let resolver = resolver
+            .in_subresource(draft.create_resource_ref(schema))
+            .ok()?;
+        Some(Scope {
+            schema,
+            resolver,
+            draft,
+        })
+    }
+}
+
+/// Collect `scope` and all schemas applied to the same value through `$ref` and `allOf`.
+fn applicable<'r>(config: &ValidationOptions, scope: Scope<'r>) -> Vec<Scope<'r>> {
+    let mut schemas = Vec::new();
+    let mut seen: Vec<*const Value> = Vec::new();
+    let mut pending = vec![scope];
+    while let Some(scope) = pending.pop() {
+        // Circular references don't apply new keywords
+        let key: *const Value = scope.schema;
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        let Value::Object(object) = scope.schema else {
+            continue;
+        };
+        let mut next = Vec::new();
+        let mut applies_siblings = true;
+        if let Some(Value::String(reference)) = object.get("$ref") {
+            if let Ok(resolved) = scope.resolver.lookup(reference) {
+                let (contents, resolver, draft) = resolved.into_inner();
+                next.push(Scope {
+                    schema: contents,
+                    resolver,
+                    draft,
+                });
+            }
+            applies_siblings = config
+                .applies_ref_siblings()
+                .unwrap_or(scope.draft >= Draft::Draft201909);
+        }
+        if applies_siblings {
+            if let Some(Value::Array(subschemas)) = object.get("allOf") {
+                next.extend(
+                    subschemas.iter().filter_map(|subschema| {
+                        Scope::enter(subschema, &scope.resolver, scope.draft)
+                    }),
+                );
+            }
+            schemas.push(scope);
+        }
+        // Keep the schema order, so the first `default` found is the closest one
+        pending.extend(next.into_iter().rev());
+    }
+    schemas
+}
+
+/// Fill in defaults within `instance` for the `properties` and `items` of `schemas`.
+///
+/// `inserted` holds the property subschemas whose defaults are being filled in, so that
+/// recursive schemas with defaults don't grow the instance endlessly.
+fn fill(
+    config: &ValidationOptions,
+    schemas: &[Scope<'_>],
+    instance: &mut Value,
+    inserted: &mut Vec<*const Value>,
+) {
+    for scope in schemas {
+        match instance {
+            Value::Object(object) => {
+                let Some(Value::Object(properties)) = scope.schema.get("properties") else {
+                    continue;
+                };
+                for (name, subschema) in properties {
+                    let Some(subscope) = Scope::enter(subschema, &scope.resolver, scope.draft)
+                    else {
+                        continue;
+                    };
+                    let subschemas = applicable(config, subscope);
+                    if let Some(value) = object.get_mut(name) {
+                        fill(config, &subschemas, value, inserted);
+                    } else if let Some(default) = subschemas
+                        .iter()
+                        .find_map(|subscope| subscope.schema.get("default"))
+                    {
+                        let mut value = default.clone();
+                        let key: *const Value = subschema;
+                        if !inserted.contains(&key) {
+                            inserted.push(key);
+                            fill(config, &subschemas, &mut value, inserted);
+                            inserted.pop();
+                        }
+                        object.insert(name.clone(), value);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                // Only a single schema for all items applies to every item unambiguously
+                let Some(subschema @ (Value::Object(_) | Value::Bool(_))) =
+                    scope.schema.get("items")
+                else {
+                    continue;
+                };
+                let Some(subscope) = Scope::enter(subschema, &scope.resolver, scope.draft) else {
+                    continue;
+                };
+                // Items covered by `prefixItems` are not evaluated against `items`
+                let skip = match scope.schema.get("prefixItems") {
+                    Some(Value::Array(prefix)) if scope.draft >= Draft::Draft202012 => prefix.len(),
+                    _ => 0,
+                };
+                let subschemas = applicable(config, subscope);
+                for item in items.iter_mut().skip(skip) {
+                    fill(config, &subschemas, item, inserted);
+                }
+            }
+            _ => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn apply(schema: &Value, instance: &Value) -> Value {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        validator.apply_defaults(instance)
+    }
+
+    #[test_case(&json!({"properties": {"a": {"default": 1}, "b": {"default": "x"}}}), &json!({}), &json!({"a": 1, "b": "x"}); "top level")]
+    #[test_case(&json!({"properties": {"a": {"default": 1}}}), &json!({"a": null}), &json!({"a": null}); "existing value")]
+    #[test_case(&json!({"properties": {"a": {"properties": {"b": {"default": true}}}}}), &json!({"a": {}}), &json!({"a": {"b": true}}); "nested")]
+    #[test_case(&json!({"properties": {"a": {"properties": {"b": {"default": true}}}}}), &json!({}), &json!({}); "nested without parent")]
+    #[test_case(&json!({"properties": {"a": {"default": {"c": 0}, "properties": {"b": {"default": true}}}}}), &json!({}), &json!({"a": {"b": true, "c": 0}}); "within inserted default")]
+    #[test_case(&json!({"properties": {"a": {"default": 1}}}), &json!([{}]), &json!([{}]); "not an object")]
+    #[test_case(&json!({"items": {"properties": {"a": {"default": 1}}}}), &json!([{}, {"a": 2}, 3]), &json!([{"a": 1}, {"a": 2}, 3]); "items")]
+    #[test_case(&json!({"prefixItems": [{}], "items": {"properties": {"a": {"default": 1}}}}), &json!([{}, {}]), &json!([{}, {"a": 1}]); "prefix items")]
+    #[test_case(&json!({"allOf": [{"properties": {"a": {"default": 1}}}, {"properties": {"b": {"default": 2}}}]}), &json!({}), &json!({"a": 1, "b": 2}); "all of")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/a"}}, "$defs": {"a": {"default": 1}}}), &json!({}), &json!({"a": 1}); "reference")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/a", "default": 2}}, "$defs": {"a": {"default": 1}}}), &json!({}), &json!({"a": 2}); "closest default")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "properties": {"a": {"$ref": "#/definitions/a", "default": 2}}, "definitions": {"a": {"default": 1}}}), &json!({}), &json!({"a": 1}); "reference siblings in draft 7")]
+    #[test_case(&json!({"anyOf": [{"properties": {"a": {"default": 1}}}]}), &json!({}), &json!({}); "conditional applicators")]
+    fn apply_defaults(schema: &Value, instance: &Value, expected: &Value) {
+        assert_eq!(apply(schema, instance), *expected);
+    }
+
+    #[test]
+    fn recursive_defaults() {
+        let schema = json!({
+            "properties": {
+                "name": {"default": "node"},
+                "child": {"$ref": "#", "default": {}}
+            }
+        });
+        assert_eq!(
+            apply(&schema, &json!({})),
+            json!({"name": "node", "child": {"name": "node", "child": {}}})
+        );
+        assert_eq!(
+            apply(&schema, &json!({"child": {"child": {"name": "leaf"}}})),
+            json!({
+                "name": "node",
+                "child": {
+                    "name": "node",
+                    "child": {"name": "leaf", "child": {"name": "node", "child": {}}}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn circular_reference() {
+        let schema = json!({"$ref": "#", "properties": {"a": {"default": 1}}});
+        assert_eq!(apply(&schema, &json!({})), json!({"a": 1}));
+    }
+}
@@ -0,0 +1,170 @@
+//! Graphviz export of the schema structure.
+use crate::{
+    migration::{ARRAY, MAP, SINGLE},
+    Validator,
+};
+use ahash::AHashMap;
+use referencing::{Draft, Resolver};
+use serde_json::Value;
+use std::fmt::Write;
+
+pub(crate) fn to_dot(validator: &Validator) -> String {
+    let mut graph = Graph {
+        ids: AHashMap::new(),
+        output: String::from("digraph schema {\n"),
+    };
+    if let Some(reference) = validator.root_reference.as_deref() {
+        if let Ok(resolver) = validator.registry.try_resolver(&validator.base_uri) {
+            if let Ok(resolved) = resolver.lookup(reference) {
+                let (root, resolver, draft) = resolved.into_inner();
+                graph.visit(root, reference, &resolver, draft);
+            }
+        }
+    }
+    graph.output.push_str("}\n");
+    graph.output
+}
+
+struct Graph {
+    /// Node identifiers keyed by the address of the subschema they represent.
+    ids: AHashMap<*const Value, usize>,
+    output: String,
+}
+
+impl Graph {
+    /// Add a node for `schema` and everything reachable from it, returning its identifier.
+    ///
+    /// Subschemas are only expanded once, so cyclic references become back-edges.
+    fn visit(
+        &mut self,
+        schema: &Value,
+        label: &str,
+        resolver: &Resolver<'_>,
+        draft: Draft,
+    ) -> usize {
+        let address = std::ptr::addr_of!(*schema);
+        if let Some(id) = self.ids.get(&address) {
+            return *id;
+        }
+        let id = self.ids.len();
+        self.ids.insert(address, id);
+        let _ = writeln!(self.output, "  n{id} [label=\"{}\"];", escape(label));
+        let Value::Object(object) = schema else {
+            return id;
+        };
+        let draft = draft.detect(schema).unwrap_or(draft);
+        let Ok(resolver) = resolver.in_subresource(draft.create_resource_ref(schema)) else {
+            return id;
+        };
+        for (keyword, value) in object {
+            match value {
+                Value::String(reference) if keyword == "$ref" => {
+                    if let Ok(resolved) = resolver.lookup(reference) {
+                        let (target, resolver, draft) = resolved.into_inner();
+                        let target = self.visit(target, reference, &resolver, draft);
+                        self.edge(id, target, "$ref", true);
+                    }
+                }
+                Value::Object(_) | Value::Bool(_) if SINGLE.contains(&keyword.as_str()) => {
+                    let child = self.visit(value, &format!("{label}/{keyword}"), &resolver, draft);
+                    self.edge(id, child, keyword, false);
+                }
+                Value::Object(subschemas) if MAP.contains(&keyword.as_str()) => {
+                    for (name, subschema) in subschemas {
+                        if !subschema.is_object() && !subschema.is_boolean() {
+                            continue;
+                        }
+                        let edge = format!("{keyword}/{name}");
+                        let child =
+                            self.visit(subschema, &format!("{label}/{edge}"), &resolver, draft);
+                        self.edge(id, child, &edge, false);
+                    }
+                }
+                Value::Array(subschemas) if ARRAY.contains(&keyword.as_str()) => {
+                    for (idx, subschema) in subschemas.iter().enumerate() {
+                        let edge = format!("{keyword}/{idx}");
+                        let child =
+                            self.visit(subschema, &format!("{label}/{edge}"), &resolver, draft);
+                        self.edge(id, child, &edge, false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str, reference: bool) {
+        let style = if reference { ", style=dashed" } else { "" };
+        let _ = writeln!(
+            self.output,
+            "  n{from} -> n{to} [label=\"{}\"{style}];",
+            escape(label)
+        );
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    #[test]
+    fn reference() {
+        let schema = json!({
+            "properties": {
+                "home": {"$ref": "#/$defs/address"},
+                "name": {"type": "string"}
+            },
+            "$defs": {"address": {"properties": {"city": {"type": "string"}}}}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(
+            validator.to_dot(),
+            r##"digraph schema {
+  n0 [label="#"];
+  n1 [label="#/$defs/address"];
+  n2 [label="#/$defs/address/properties/city"];
+  n1 -> n2 [label="properties/city"];
+  n0 -> n1 [label="$defs/address"];
+  n3 [label="#/properties/home"];
+  n3 -> n1 [label="$ref", style=dashed];
+  n0 -> n3 [label="properties/home"];
+  n4 [label="#/properties/name"];
+  n0 -> n4 [label="properties/name"];
+}
+"##
+        );
+    }
+
+    #[test]
+    fn recursive_reference() {
+        let schema = json!({
+            "items": {"$ref": "#"},
+            "properties": {"say \"hi\"": {"type": "string"}}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(
+            validator.to_dot(),
+            r##"digraph schema {
+  n0 [label="#"];
+  n1 [label="#/items"];
+  n1 -> n0 [label="$ref", style=dashed];
+  n0 -> n1 [label="items"];
+  n2 [label="#/properties/say \"hi\""];
+  n0 -> n2 [label="properties/say \"hi\""];
+}
+"##
+        );
+    }
+
+    #[test]
+    fn combined_validators() {
+        let left = crate::validator_for(&json!({"type": "string"})).expect("Invalid schema");
+        let right = crate::validator_for(&json!({"minLength": 1})).expect("Invalid schema");
+        assert_eq!(left.and(&right).to_dot(), "digraph schema {\n}\n");
+    }
+}
@@ -0,0 +1,122 @@
+//! Detection of duplicate object keys in raw JSON documents.
+use ahash::AHashSet;
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+/// Check whether any object in the raw JSON document contains the same key more than once.
+///
+/// Validation works on [`serde_json::Value`], where an object is a map and duplicate keys are
+/// already collapsed during parsing, with the last value winning. Such documents can't be
+/// rejected by a validator, so run this check on the raw input before parsing it if duplicates
+/// should be treated as an error.
+///
+/// # Errors
+///
+/// Returns an error if `raw` is not valid JSON.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// assert!(!jsonschema::has_duplicate_keys(r#"{"a": 1, "b": {"a": 2}}"#)?);
+/// assert!(jsonschema::has_duplicate_keys(r#"{"a": 1, "a": 2}"#)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn has_duplicate_keys(raw: &str) -> Result<bool, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(raw);
+    let found = Scan.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(found)
+}
+
+/// Walks a document without building it and reports whether duplicate keys were found.
+struct Scan;
+
+impl<'de> DeserializeSeed<'de> for Scan {
+    type Value = bool;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Scan {
+    type Value = bool;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_unit<E>(self) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<bool, A::Error> {
+        let mut found = false;
+        while let Some(nested) = seq.next_element_seed(Scan)? {
+            found |= nested;
+        }
+        Ok(found)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<bool, A::Error> {
+        let mut keys = AHashSet::new();
+        let mut found = false;
+        // Keep going after a duplicate, so the rest of the input is still checked for syntax errors
+        while let Some(key) = map.next_key::<String>()? {
+            found |= map.next_value_seed(Scan)?;
+            found |= !keys.insert(key);
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_duplicate_keys;
+    use test_case::test_case;
+
+    #[test_case(r#"{"a": 1, "a": 2}"#; "top level")]
+    #[test_case(r#"{"a": {"b": 1, "c": 2, "b": 3}}"#; "nested object")]
+    #[test_case(r#"[1, {"a": null, "a": null}]"#; "object in array")]
+    #[test_case(r#"{"a\u0062": 1, "ab": 2}"#; "escaped key")]
+    fn duplicates(raw: &str) {
+        assert!(has_duplicate_keys(raw).expect("Valid JSON"));
+    }
+
+    #[test_case(r#"{"a": 1, "b": 2}"#; "distinct keys")]
+    #[test_case(r#"{"a": {"a": {"a": 1}}}"#; "same key at different levels")]
+    #[test_case(r#"[{"a": 1}, {"a": 2}]"#; "same key in sibling objects")]
+    #[test_case(r#""a""#; "scalar")]
+    #[test_case("[]"; "empty array")]
+    fn no_duplicates(raw: &str) {
+        assert!(!has_duplicate_keys(raw).expect("Valid JSON"));
+    }
+
+    #[test_case(r#"{"a": 1, "a": 2"#; "unterminated object")]
+    #[test_case(r#"{"a": 1} {"b": 2}"#; "trailing characters")]
+    fn invalid_json(raw: &str) {
+        assert!(has_duplicate_keys(raw).is_err());
+    }
+}
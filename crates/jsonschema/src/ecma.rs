@@ -1,6 +1,39 @@
 use std::borrow::Cow;
 
 use regex_syntax::ast::{self, parse::Parser, *};
+use serde_json::Value;
+
+use crate::{paths::Location, ValidationError};
+
+/// Why a `pattern` could not be compiled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RegexError {
+    /// Not a valid ECMA 262 regular expression, or uses unsupported features.
+    Invalid,
+    /// The compiled regular expression exceeds the configured size limit in bytes.
+    TooLarge(usize),
+}
+
+impl RegexError {
+    /// Build a schema error for `pattern` located at `location`.
+    pub(crate) fn into_validation_error(
+        self,
+        location: Location,
+        pattern: &Value,
+    ) -> ValidationError<'_> {
+        match self {
+            RegexError::Invalid => {
+                ValidationError::format(Location::new(), location, pattern, "regex")
+            }
+            RegexError::TooLarge(limit) => ValidationError::custom(
+                Location::new(),
+                location,
+                pattern,
+                format!("Regular expression exceeds the size limit of {limit} bytes"),
+            ),
+        }
+    }
+}
 
 /// Convert ECMA Script 262 regex to Rust regex on the best effort basiso.
 ///
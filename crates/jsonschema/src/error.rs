@@ -39,6 +39,7 @@ use crate::{
     paths::Location,
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
 };
+use serde::ser::SerializeMap;
 use serde_json::{Map, Number, Value};
 use std::{
     borrow::Cow,
@@ -58,6 +59,10 @@ pub struct ValidationError<'a> {
     /// Path to the value that failed validation.
     pub instance_path: Location,
     /// Path to the JSON Schema keyword that failed validation.
+    ///
+    /// The path follows the evaluation, so every reference keyword that was traversed
+    /// (`$ref`, `$dynamicRef`, `$recursiveRef`) appears in it, e.g.
+    /// `/properties/user/$ref/properties/name/type`.
     pub schema_path: Location,
 }
 
@@ -98,7 +103,14 @@ pub enum ValidationErrorKind {
     /// Unexpected properties.
     AdditionalProperties { unexpected: Vec<String> },
     /// The input value is not valid under any of the schemas listed in the 'anyOf' keyword.
-    AnyOf,
+    ///
+    /// `context` holds the first error of each subschema, in order. It is empty unless
+    /// [`ValidationOptions::should_report_branch_errors`] is enabled.
+    ///
+    /// [`ValidationOptions::should_report_branch_errors`]: crate::ValidationOptions::should_report_branch_errors
+    AnyOf {
+        context: Vec<ValidationError<'static>>,
+    },
     /// Results from a [`fancy_regex::RuntimeError::BacktrackLimitExceeded`] variant when matching
     BacktrackLimitExceeded { error: fancy_regex::Error },
     /// The input value doesn't match expected constant.
@@ -123,6 +135,8 @@ pub enum ValidationErrorKind {
     Format { format: String },
     /// May happen in `contentEncoding` validation if `base64` encoded data is invalid.
     FromUtf8 { error: FromUtf8Error },
+    /// Validation followed recursive references into a value nested deeper than allowed.
+    MaxDepth { limit: usize },
     /// Too many items in an array.
     MaxItems { limit: u64 },
     /// Value is too large.
@@ -146,7 +160,14 @@ pub enum ValidationErrorKind {
     /// The given schema is valid under more than one of the schemas listed in the 'oneOf' keyword.
     OneOfMultipleValid,
     /// The given schema is not valid under any of the schemas listed in the 'oneOf' keyword.
-    OneOfNotValid,
+    ///
+    /// `context` holds the first error of each subschema, in order. It is empty unless
+    /// [`ValidationOptions::should_report_branch_errors`] is enabled.
+    ///
+    /// [`ValidationOptions::should_report_branch_errors`]: crate::ValidationOptions::should_report_branch_errors
+    OneOfNotValid {
+        context: Vec<ValidationError<'static>>,
+    },
     /// When the input doesn't match to a pattern.
     Pattern { pattern: String },
     /// Object property names are invalid.
@@ -192,6 +213,37 @@ impl<'a> ValidationError<'a> {
             placeholder: placeholder.into(),
         }
     }
+    /// Returns the part of `instance` located at this error's instance path.
+    ///
+    /// Returns `None` if the location doesn't exist in `instance`, e.g. if it was modified after
+    /// validation, or if the error is within a document decoded for `contentSchema`, see
+    /// [`ValidationOptions::should_validate_content`](crate::ValidationOptions::should_validate_content).
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"items": {"type": "string"}}))
+    ///     .expect("Invalid schema");
+    /// let instance = json!(["a", 42]);
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(error.instance_fragment(&instance), Some(&json!(42)));
+    /// ```
+    #[must_use]
+    pub fn instance_fragment<'i>(&self, instance: &'i Value) -> Option<&'i Value> {
+        instance.pointer(self.instance_path.as_str())
+    }
+    pub(crate) fn with_schema_path_prefix(mut self, prefix: &Location) -> Self {
+        self.schema_path = self.schema_path.prefixed(prefix);
+        if let ValidationErrorKind::AnyOf { context }
+        | ValidationErrorKind::OneOfNotValid { context } = &mut self.kind
+        {
+            *context = std::mem::take(context)
+                .into_iter()
+                .map(|error| error.with_schema_path_prefix(prefix))
+                .collect();
+        }
+        self
+    }
     /// Converts the `ValidationError` into an owned version with `'static` lifetime.
     pub fn to_owned(self) -> ValidationError<'static> {
         ValidationError {
@@ -232,11 +284,12 @@ impl<'a> ValidationError<'a> {
         location: Location,
         instance_path: Location,
         instance: &'a Value,
+        context: Vec<ValidationError<'static>>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::AnyOf,
+            kind: ValidationErrorKind::AnyOf { context },
             schema_path: location,
         }
     }
@@ -437,6 +490,19 @@ impl<'a> ValidationError<'a> {
             schema_path: location,
         }
     }
+    pub(crate) fn max_depth(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        limit: usize,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::MaxDepth { limit },
+            schema_path: location,
+        }
+    }
     pub(crate) fn format(
         location: Location,
         instance_path: Location,
@@ -606,11 +672,12 @@ impl<'a> ValidationError<'a> {
         location: Location,
         instance_path: Location,
         instance: &'a Value,
+        context: Vec<ValidationError<'static>>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::OneOfNotValid,
+            kind: ValidationErrorKind::OneOfNotValid { context },
             schema_path: location,
         }
     }
@@ -761,6 +828,79 @@ impl From<FromUtf8Error> for ValidationError<'_> {
     }
 }
 
+/// An error building a [`Validator`](crate::Validator) from a JSON string.
+#[derive(Debug)]
+pub enum SchemaParseError {
+    /// The string is not valid JSON.
+    Json(serde_json::Error),
+    /// The parsed schema is not valid.
+    Schema(ValidationError<'static>),
+}
+
+impl error::Error for SchemaParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SchemaParseError::Json(error) => Some(error),
+            SchemaParseError::Schema(error) => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for SchemaParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaParseError::Json(error) => error.fmt(f),
+            SchemaParseError::Schema(error) => error.fmt(f),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SchemaParseError {
+    fn from(error: serde_json::Error) -> Self {
+        SchemaParseError::Json(error)
+    }
+}
+
+impl From<ValidationError<'static>> for SchemaParseError {
+    fn from(error: ValidationError<'static>) -> Self {
+        SchemaParseError::Schema(error)
+    }
+}
+
+/// Serializes the error as an output unit of the JSON Schema 2020-12 "basic" output format.
+///
+/// The unit contains `keywordLocation`, `instanceLocation` and `error`, where the latter is the
+/// [`Display`](fmt::Display) message of the error. `absoluteKeywordLocation` is not included,
+/// as validation errors do not track the URI of the failing keyword.
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({"minimum": 5})).expect("Invalid schema");
+/// let instance = json!(3);
+/// let error = validator.validate(&instance).expect_err("Should fail");
+/// assert_eq!(
+///     serde_json::to_value(&error).expect("Serializable"),
+///     json!({
+///         "keywordLocation": "/minimum",
+///         "instanceLocation": "",
+///         "error": "3 is less than the minimum of 5"
+///     })
+/// );
+/// ```
+impl serde::Serialize for ValidationError<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map_ser = serializer.serialize_map(Some(3))?;
+        map_ser.serialize_entry("keywordLocation", self.schema_path.as_str())?;
+        map_ser.serialize_entry("instanceLocation", self.instance_path.as_str())?;
+        map_ser.serialize_entry("error", &self.to_string())?;
+        map_ser.end()
+    }
+}
+
 fn write_quoted_list(f: &mut Formatter<'_>, items: &[impl fmt::Display]) -> fmt::Result {
     let mut iter = items.iter();
     if let Some(item) = iter.next() {
@@ -815,12 +955,12 @@ impl fmt::Display for ValidationError<'_> {
                 write_quoted_list(f, unexpected)?;
                 write_unexpected_suffix(f, unexpected.len())
             }
-            ValidationErrorKind::AnyOf => write!(
+            ValidationErrorKind::AnyOf { .. } => write!(
                 f,
                 "{} is not valid under any of the schemas listed in the 'anyOf' keyword",
                 self.instance
             ),
-            ValidationErrorKind::OneOfNotValid => write!(
+            ValidationErrorKind::OneOfNotValid { .. } => write!(
                 f,
                 "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
                 self.instance
@@ -848,9 +988,10 @@ impl fmt::Display for ValidationError<'_> {
                 )
             }
             ValidationErrorKind::FromUtf8 { error } => error.fmt(f),
-            ValidationErrorKind::Enum { options } => {
-                write!(f, "{} is not one of {}", self.instance, options)
-            }
+            ValidationErrorKind::Enum { options } => match options.as_array().map(Vec::as_slice) {
+                Some([value]) => write!(f, "{} is not equal to {}", self.instance, value),
+                _ => write!(f, "{} is not one of {}", self.instance, options),
+            },
             ValidationErrorKind::ExclusiveMaximum { limit } => write!(
                 f,
                 "{} is greater than or equal to the maximum of {}",
@@ -886,6 +1027,10 @@ impl fmt::Display for ValidationError<'_> {
                 limit,
                 if *limit == 1 { "" } else { "s" }
             ),
+            ValidationErrorKind::MaxDepth { limit } => write!(
+                f,
+                "Instance is nested deeper than the maximum depth of {limit}"
+            ),
             ValidationErrorKind::MaxItems { limit } => write!(
                 f,
                 "{} has more than {} item{}",
@@ -994,12 +1139,12 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
                 write_quoted_list(f, unexpected)?;
                 write_unexpected_suffix(f, unexpected.len())
             }
-            ValidationErrorKind::AnyOf => write!(
+            ValidationErrorKind::AnyOf { .. } => write!(
                 f,
                 "{} is not valid under any of the schemas listed in the 'anyOf' keyword",
                 self.placeholder
             ),
-            ValidationErrorKind::OneOfNotValid => write!(
+            ValidationErrorKind::OneOfNotValid { .. } => write!(
                 f,
                 "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
                 self.placeholder
@@ -1027,9 +1172,10 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
                 )
             }
             ValidationErrorKind::FromUtf8 { error } => error.fmt(f),
-            ValidationErrorKind::Enum { options } => {
-                write!(f, "{} is not one of {}", self.placeholder, options)
-            }
+            ValidationErrorKind::Enum { options } => match options.as_array().map(Vec::as_slice) {
+                Some([value]) => write!(f, "{} is not equal to {}", self.placeholder, value),
+                _ => write!(f, "{} is not one of {}", self.placeholder, options),
+            },
             ValidationErrorKind::ExclusiveMaximum { limit } => write!(
                 f,
                 "{} is greater than or equal to the maximum of {}",
@@ -1069,6 +1215,10 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
                 limit,
                 if *limit == 1 { "" } else { "s" }
             ),
+            ValidationErrorKind::MaxDepth { limit } => write!(
+                f,
+                "Instance is nested deeper than the maximum depth of {limit}"
+            ),
             ValidationErrorKind::MaxItems { limit } => write!(
                 f,
                 "{} has more than {} item{}",
@@ -1166,6 +1316,32 @@ mod tests {
     use serde_json::json;
     use test_case::test_case;
 
+    #[test_case(&json!({"properties": {"a/b": {"properties": {"c~d": {"type": "string"}}}}}), &json!({"a/b": {"c~d": 1}}), &json!(1); "escaped properties")]
+    #[test_case(&json!({"items": {"items": {"minimum": 5}}}), &json!([[5], [6, 7, 1]]), &json!(1); "nested arrays")]
+    #[test_case(&json!({"properties": {"tags": {"items": {"type": "string"}}}}), &json!({"tags": ["a", {"b": null}]}), &json!({"b": null}); "array in object")]
+    #[test_case(&json!({"type": "string"}), &json!(42), &json!(42); "root")]
+    fn instance_fragment(schema: &Value, instance: &Value, expected: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.instance_fragment(instance), Some(expected));
+        assert_eq!(error.instance_fragment(instance), Some(&*error.instance));
+    }
+
+    #[test]
+    fn instance_fragment_missing() {
+        let schema = json!({"properties": {"a": {"items": {"type": "string"}}}});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"a": ["x", 1]});
+        let error = validator
+            .validate(&instance)
+            .expect_err("Should fail")
+            .to_owned();
+        let mut mutated = instance.clone();
+        mutated["a"].as_array_mut().expect("Array").pop();
+        assert_eq!(error.instance_fragment(&mutated), None);
+        assert_eq!(error.instance_fragment(&json!(null)), None);
+    }
+
     #[test]
     fn single_type_error() {
         let instance = json!(42);
@@ -1382,4 +1558,22 @@ mod tests {
         };
         assert_eq!(error.masked_with(placeholder).to_string(), expected);
     }
+
+    #[test]
+    fn serialize_minimum() {
+        let validator = crate::validator_for(&json!({
+            "properties": {"age": {"minimum": 18}}
+        }))
+        .expect("Invalid schema");
+        let instance = json!({"age": 15});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            serde_json::to_value(&error).expect("Serializable"),
+            json!({
+                "keywordLocation": "/properties/age/minimum",
+                "instanceLocation": "/age",
+                "error": "15 is less than the minimum of 18"
+            })
+        );
+    }
 }
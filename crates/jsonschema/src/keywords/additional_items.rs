@@ -5,7 +5,7 @@ use crate::{
     node::SchemaNode,
     paths::{LazyLocation, Location},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
-    validator::Validate,
+    validator::{PartialApplication, TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
@@ -54,6 +54,21 @@ impl Validate for AdditionalItemsObjectValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Array(items) = instance {
+            for (idx, item) in items.iter().enumerate().skip(self.items_count) {
+                is_valid &= self.node.trace(item, &location.push(idx), callback);
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -66,6 +81,25 @@ impl Validate for AdditionalItemsObjectValidator {
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if let Value::Array(items) = instance {
+            let mut results = Vec::with_capacity(items.len().saturating_sub(self.items_count));
+            for (idx, item) in items.iter().enumerate().skip(self.items_count) {
+                let path = location.push(idx);
+                results.push(self.node.apply_rooted(item, &path));
+            }
+            let mut output: PartialApplication = results.into_iter().collect();
+            // Per draft 2019-09 section https://json-schema.org/draft/2019-09/json-schema-core.html#rfc.section.9.3.1.2
+            // `additionalItems` produces `true` if the subschema was applied to any item.
+            if items.len() > self.items_count {
+                output.annotate(Value::Bool(true).into());
+            }
+            output
+        } else {
+            PartialApplication::valid_empty()
+        }
+    }
 }
 
 pub(crate) struct AdditionalItemsBooleanValidator {
@@ -15,11 +15,63 @@ use crate::{
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     properties::*,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, TraceRecord, Validate},
 };
 use referencing::Uri;
 use serde_json::{Map, Value};
 
+/// Annotations of the `properties` and `patternProperties` keywords for validators that handle
+/// them together with `additionalProperties`.
+///
+/// These validators are compiled under the `additionalProperties` keyword, so the locations of
+/// the sibling keywords are stored separately. A property matching both keywords is reported by
+/// each of them.
+struct SiblingAnnotations {
+    properties_path: Location,
+    properties_absolute_location: Option<Uri<String>>,
+    patterns_path: Location,
+    patterns_absolute_location: Option<Uri<String>>,
+}
+
+impl SiblingAnnotations {
+    fn new(ctx: &compiler::Context) -> SiblingAnnotations {
+        SiblingAnnotations {
+            properties_path: ctx.location().join("properties"),
+            properties_absolute_location: ctx.new_at_location("properties").base_uri(),
+            patterns_path: ctx.location().join("patternProperties"),
+            patterns_absolute_location: ctx.new_at_location("patternProperties").base_uri(),
+        }
+    }
+
+    fn output(
+        &self,
+        location: &LazyLocation,
+        properties: Vec<String>,
+        patterns: Vec<String>,
+    ) -> BasicOutput<'static> {
+        let mut output = BasicOutput::default();
+        if !properties.is_empty() {
+            output += OutputUnit::<Annotations<'_>>::annotations(
+                self.properties_path.clone(),
+                location.into(),
+                self.properties_absolute_location.clone(),
+                Value::from(properties).into(),
+            )
+            .into();
+        }
+        if !patterns.is_empty() {
+            output += OutputUnit::<Annotations<'_>>::annotations(
+                self.patterns_path.clone(),
+                location.into(),
+                self.patterns_absolute_location.clone(),
+                Value::from(patterns).into(),
+            )
+            .into();
+        }
+        output
+    }
+}
+
 macro_rules! is_valid {
     ($node:expr, $value:ident) => {{
         $node.is_valid($value)
@@ -62,6 +114,41 @@ macro_rules! iter_errors {
     }};
 }
 
+/// Trace the subschemas applied to each property of `instance`.
+///
+/// A property is checked against its `properties` subschema and every matching pattern. Other
+/// properties are checked against `additional`, where `None` stands for `false`.
+fn trace_properties<M: PropertiesValidatorsMap>(
+    properties: &M,
+    patterns: &[(fancy_regex::Regex, SchemaNode)],
+    additional: Option<&SchemaNode>,
+    instance: &Value,
+    location: &LazyLocation,
+    callback: &mut dyn FnMut(TraceRecord),
+) -> bool {
+    let mut is_valid = true;
+    if let Value::Object(item) = instance {
+        for (property, value) in item {
+            let location = location.push(property.as_str());
+            let mut has_match = false;
+            if let Some(node) = properties.get_validator(property) {
+                has_match = true;
+                is_valid &= node.trace(value, &location, callback);
+            }
+            for (re, node) in patterns {
+                if re.is_match(property).unwrap_or(false) {
+                    has_match = true;
+                    is_valid &= node.trace(value, &location, callback);
+                }
+            }
+            if !has_match {
+                is_valid &= additional.is_some_and(|node| node.trace(value, &location, callback));
+            }
+        }
+    }
+    is_valid
+}
+
 /// # Schema example
 ///
 /// ```json
@@ -111,6 +198,22 @@ impl Validate for AdditionalPropertiesValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(
+            &SmallValidatorsMap::new(),
+            &[],
+            Some(&self.node),
+            instance,
+            location,
+            callback,
+        )
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -273,6 +376,15 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(&self.properties, &[], None, instance, location, callback)
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -414,6 +526,22 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValida
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(
+            &self.properties,
+            &[],
+            Some(&self.node),
+            instance,
+            location,
+            callback,
+        )
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -547,6 +675,22 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
         true
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(
+            &SmallValidatorsMap::new(),
+            &self.patterns,
+            Some(&self.node),
+            instance,
+            location,
+            callback,
+        )
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -694,6 +838,22 @@ impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
         true
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(
+            &SmallValidatorsMap::new(),
+            &self.patterns,
+            None,
+            instance,
+            location,
+            callback,
+        )
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -798,6 +958,7 @@ pub(crate) struct AdditionalPropertiesWithPatternsNotEmptyValidator<M: Propertie
     node: SchemaNode,
     properties: M,
     patterns: PatternedValidators,
+    annotations: SiblingAnnotations,
 }
 impl AdditionalPropertiesWithPatternsNotEmptyValidator<SmallValidatorsMap> {
     #[inline]
@@ -813,6 +974,7 @@ impl AdditionalPropertiesWithPatternsNotEmptyValidator<SmallValidatorsMap> {
                 node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
                 properties: compile_small_map(ctx, map)?,
                 patterns,
+                annotations: SiblingAnnotations::new(ctx),
             },
         ))
     }
@@ -831,6 +993,7 @@ impl AdditionalPropertiesWithPatternsNotEmptyValidator<BigValidatorsMap> {
                 node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
                 properties: compile_big_map(ctx, map)?,
                 patterns,
+                annotations: SiblingAnnotations::new(ctx),
             },
         ))
     }
@@ -906,6 +1069,22 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(
+            &self.properties,
+            &self.patterns,
+            Some(&self.node),
+            instance,
+            location,
+            callback,
+        )
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -942,15 +1121,23 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
         if let Value::Object(item) = instance {
             let mut output = BasicOutput::default();
             let mut additional_matches = Vec::with_capacity(item.len());
+            let mut matched_props = Vec::with_capacity(item.len());
+            let mut pattern_matched_props = Vec::with_capacity(item.len());
             for (property, value) in item {
                 let path = location.push(property.as_str());
                 if let Some((_name, node)) = self.properties.get_key_validator(property) {
+                    matched_props.push(property.clone());
                     output += node.apply_rooted(value, &path);
+                    let mut has_match = false;
                     for (pattern, node) in &self.patterns {
                         if pattern.is_match(property).unwrap_or(false) {
+                            has_match = true;
                             output += node.apply_rooted(value, &path);
                         }
                     }
+                    if has_match {
+                        pattern_matched_props.push(property.clone());
+                    }
                 } else {
                     let mut has_match = false;
                     for (pattern, node) in &self.patterns {
@@ -959,12 +1146,18 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
                             output += node.apply_rooted(value, &path);
                         }
                     }
+                    if has_match {
+                        pattern_matched_props.push(property.clone());
+                    }
                     if !has_match {
                         additional_matches.push(property.clone());
                         output += self.node.apply_rooted(value, &path);
                     }
                 }
             }
+            output += self
+                .annotations
+                .output(location, matched_props, pattern_matched_props);
             let mut result: PartialApplication = output.into();
             result.annotate(Value::from(additional_matches).into());
             result
@@ -1004,6 +1197,7 @@ pub(crate) struct AdditionalPropertiesWithPatternsNotEmptyFalseValidator<M: Prop
     properties: M,
     patterns: PatternedValidators,
     location: Location,
+    annotations: SiblingAnnotations,
 }
 impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<SmallValidatorsMap> {
     #[inline]
@@ -1017,6 +1211,7 @@ impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<SmallValidatorsMap>
                 properties: compile_small_map(ctx, map)?,
                 patterns,
                 location: ctx.location().join("additionalProperties"),
+                annotations: SiblingAnnotations::new(ctx),
             },
         ))
     }
@@ -1033,6 +1228,7 @@ impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<BigValidatorsMap> {
                 properties: compile_big_map(ctx, map)?,
                 patterns,
                 location: ctx.location().join("additionalProperties"),
+                annotations: SiblingAnnotations::new(ctx),
             },
         ))
     }
@@ -1110,6 +1306,22 @@ impl<M: PropertiesValidatorsMap> Validate
         true
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        trace_properties(
+            &self.properties,
+            &self.patterns,
+            None,
+            instance,
+            location,
+            callback,
+        )
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -1151,16 +1363,24 @@ impl<M: PropertiesValidatorsMap> Validate
         if let Value::Object(item) = instance {
             let mut output = BasicOutput::default();
             let mut unexpected = vec![];
+            let mut matched_props = Vec::with_capacity(item.len());
+            let mut pattern_matched_props = Vec::with_capacity(item.len());
             // No properties are allowed, except ones defined in `properties` or `patternProperties`
             for (property, value) in item {
                 let path = location.push(property.as_str());
                 if let Some((_name, node)) = self.properties.get_key_validator(property) {
+                    matched_props.push(property.clone());
                     output += node.apply_rooted(value, &path);
+                    let mut has_match = false;
                     for (pattern, node) in &self.patterns {
                         if pattern.is_match(property).unwrap_or(false) {
+                            has_match = true;
                             output += node.apply_rooted(value, &path);
                         }
                     }
+                    if has_match {
+                        pattern_matched_props.push(property.clone());
+                    }
                 } else {
                     let mut has_match = false;
                     for (pattern, node) in &self.patterns {
@@ -1169,11 +1389,17 @@ impl<M: PropertiesValidatorsMap> Validate
                             output += node.apply_rooted(value, &path);
                         }
                     }
+                    if has_match {
+                        pattern_matched_props.push(property.clone());
+                    }
                     if !has_match {
                         unexpected.push(property.clone());
                     }
                 }
             }
+            output += self
+                .annotations
+                .output(location, matched_props, pattern_matched_props);
             let mut result: PartialApplication = output.into();
             if !unexpected.is_empty() {
                 result.mark_errored(
@@ -1696,4 +1922,25 @@ mod tests {
         tests_util::expect_errors(&schema, instance, expected);
         tests_util::assert_locations(&schema, instance, locations)
     }
+
+    // Every variant reports only the first failing property from `validate`
+    #[test_case(&json!({"additionalProperties": {"type": "string"}}), "/a", "/additionalProperties/type"; "schema")]
+    #[test_case(&json!({"additionalProperties": false}), "", "/additionalProperties"; "false")]
+    #[test_case(&json!({"additionalProperties": false, "properties": {"foo": {}}}), "", "/additionalProperties"; "false with properties")]
+    #[test_case(&json!({"additionalProperties": {"type": "string"}, "properties": {"foo": {}}}), "/a", "/additionalProperties/type"; "schema with properties")]
+    #[test_case(&json!({"additionalProperties": false, "patternProperties": {"^x-": {}}}), "", "/additionalProperties"; "false with patterns")]
+    #[test_case(&json!({"additionalProperties": {"type": "string"}, "patternProperties": {"^x-": {}}}), "/a", "/additionalProperties/type"; "schema with patterns")]
+    #[test_case(&json!({"additionalProperties": false, "properties": {"foo": {}}, "patternProperties": {"^x-": {}}}), "", "/additionalProperties"; "false with properties and patterns")]
+    #[test_case(&json!({"additionalProperties": {"type": "string"}, "properties": {"foo": {}}, "patternProperties": {"^x-": {}}}), "/a", "/additionalProperties/type"; "schema with properties and patterns")]
+    fn validate_stops_at_first_property(schema: &Value, instance_path: &str, schema_path: &str) {
+        let instance = json!({"a": 1, "b": 2, "c": 3});
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), instance_path);
+        assert_eq!(error.schema_path.as_str(), schema_path);
+        if let crate::error::ValidationErrorKind::AdditionalProperties { unexpected } = &error.kind
+        {
+            assert_eq!(unexpected, &["a"]);
+        }
+    }
 }
@@ -5,11 +5,11 @@ use crate::{
     output::BasicOutput,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
-use super::CompilationResult;
+use super::{helpers::compile_flattened, CompilationResult};
 
 pub(crate) struct AllOfValidator {
     schemas: Vec<SchemaNode>,
@@ -21,14 +21,17 @@ impl AllOfValidator {
         ctx: &compiler::Context,
         items: &'a [Value],
     ) -> CompilationResult<'a> {
-        let ctx = ctx.new_at_location("allOf");
-        let mut schemas = Vec::with_capacity(items.len());
-        for (idx, item) in items.iter().enumerate() {
-            let ctx = ctx.new_at_location(idx);
-            let validators = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
-            schemas.push(validators)
+        let mut schemas: Vec<_> = compile_flattened(ctx, "allOf", items)?
+            .schemas
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect();
+        if schemas.len() == 1 {
+            let node = schemas.pop().expect("Vec is not empty");
+            Ok(Box::new(SingleValueAllOfValidator { node }))
+        } else {
+            Ok(Box::new(AllOfValidator { schemas }))
         }
-        Ok(Box::new(AllOfValidator { schemas }))
     }
 }
 
@@ -47,6 +50,19 @@ impl Validate for AllOfValidator {
         self.schemas.iter().all(|n| n.is_valid(instance))
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        for node in &self.schemas {
+            is_valid &= node.trace(instance, location, callback);
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -71,16 +87,6 @@ pub(crate) struct SingleValueAllOfValidator {
     node: SchemaNode,
 }
 
-impl SingleValueAllOfValidator {
-    #[inline]
-    pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
-        let ctx = ctx.new_at_location("allOf");
-        let ctx = ctx.new_at_location(0);
-        let node = compiler::compile(&ctx, ctx.as_resource_ref(schema))?;
-        Ok(Box::new(SingleValueAllOfValidator { node }))
-    }
-}
-
 impl Validate for SingleValueAllOfValidator {
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         self.node.iter_errors(instance, location)
@@ -90,6 +96,15 @@ impl Validate for SingleValueAllOfValidator {
         self.node.is_valid(instance)
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        self.node.trace(instance, location, callback)
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -110,12 +125,7 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     if let Value::Array(items) = schema {
-        if items.len() == 1 {
-            let value = items.iter().next().expect("Vec is not empty");
-            Some(SingleValueAllOfValidator::compile(ctx, value))
-        } else {
-            Some(AllOfValidator::compile(ctx, items))
-        }
+        Some(AllOfValidator::compile(ctx, items))
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
@@ -129,7 +139,7 @@ pub(crate) fn compile<'a>(
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
-    use serde_json::{json, Value};
+    use serde_json::{json, Map, Value};
     use test_case::test_case;
 
     #[test_case(&json!({"allOf": [{"type": "string"}]}), &json!(1), "/allOf/0/type")]
@@ -137,4 +147,44 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"allOf": [{"allOf": [{"type": "integer"}, {"maximum": 5}]}, {"minimum": 0}]}), &json!(6), "/allOf/0/allOf/1/maximum")]
+    #[test_case(&json!({"allOf": [{"allOf": [{"type": "integer"}], "maximum": 5}]}), &json!(6), "/allOf/0/maximum")]
+    fn nested_location(schema: &Value, instance: &Value, expected: &str) {
+        tests_util::assert_schema_location(schema, instance, expected)
+    }
+
+    #[test]
+    fn deeply_nested_chain() {
+        // `serde_json` clones and drops values recursively, so the schema itself needs more
+        // stack than test threads get by default
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(|| {
+                let depth = 1000;
+                // `json!` would serialize `inner` recursively on every step
+                let schema = (0..depth).fold(json!({"type": "integer"}), |inner, _| {
+                    Value::Object(Map::from_iter([(
+                        "allOf".to_string(),
+                        Value::Array(vec![inner]),
+                    )]))
+                });
+                // The meta-schema check would reject such a deep schema before compiling it
+                let validator = crate::options()
+                    .without_schema_validation()
+                    .build(&schema)
+                    .expect("Valid schema");
+                assert!(validator.is_valid(&json!(1)));
+                let instance = json!("a");
+                let error = validator.validate(&instance).expect_err("Should fail");
+                assert_eq!(
+                    error.schema_path.as_str(),
+                    format!("{}/type", "/allOf/0".repeat(depth))
+                );
+                assert_eq!(validator.iter_errors(&instance).count(), 1);
+            })
+            .expect("Failed to spawn a thread")
+            .join()
+            .expect("Thread panicked");
+    }
 }
@@ -3,32 +3,53 @@ use crate::{
     error::{error, no_error, ErrorIterator, ValidationError},
     node::SchemaNode,
     paths::{LazyLocation, Location},
-    primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    validator::{PartialApplication, TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
-use super::CompilationResult;
+use super::{
+    helpers::{compile_flattened, declared_types, matches_type, Branch, Flattened},
+    CompilationResult,
+};
 
 pub(crate) struct AnyOfValidator {
     schemas: Vec<SchemaNode>,
+    /// Types declared by each subschema, used to skip subschemas that can't match.
+    types: Vec<Option<PrimitiveTypesBitMap>>,
+    /// Subschemas as written in the schema, so errors are reported per branch.
+    branches: Vec<Branch>,
     location: Location,
+    report_branch_errors: bool,
 }
 
 impl AnyOfValidator {
     #[inline]
     pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
         if let Value::Array(items) = schema {
-            let ctx = ctx.new_at_location("anyOf");
-            let mut schemas = Vec::with_capacity(items.len());
-            for (idx, item) in items.iter().enumerate() {
-                let ctx = ctx.new_at_location(idx);
-                let node = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
-                schemas.push(node)
+            if items.is_empty() {
+                // Not caught by meta-schema validation if the subschema is reachable only via `$ref`
+                return Err(ValidationError::min_items(
+                    Location::new(),
+                    ctx.location().join("anyOf"),
+                    schema,
+                    1,
+                ));
             }
+            let Flattened {
+                schemas: compiled,
+                branches,
+            } = compile_flattened(ctx, "anyOf", items)?;
+            let (schemas, types) = compiled
+                .into_iter()
+                .map(|(node, schema)| (node, declared_types(ctx, schema)))
+                .unzip();
             Ok(Box::new(AnyOfValidator {
                 schemas,
-                location: ctx.location().clone(),
+                types,
+                branches,
+                location: ctx.location().join("anyOf"),
+                report_branch_errors: ctx.config().reports_branch_errors(),
             }))
         } else {
             Err(ValidationError::single_type_error(
@@ -39,6 +60,52 @@ impl AnyOfValidator {
             ))
         }
     }
+
+    fn error<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ValidationError<'i> {
+        let context = if self.report_branch_errors {
+            branch_errors(&self.branches, &self.schemas, instance, location)
+        } else {
+            Vec::new()
+        };
+        ValidationError::any_of(self.location.clone(), location.into(), instance, context)
+    }
+}
+
+/// Collect the first error of each branch that fails on `instance`.
+///
+/// Inlined `anyOf` subschemas are reported as a single error, the same as if they were not
+/// flattened.
+fn branch_errors(
+    branches: &[Branch],
+    schemas: &[SchemaNode],
+    instance: &Value,
+    location: &LazyLocation,
+) -> Vec<ValidationError<'static>> {
+    branches
+        .iter()
+        .filter_map(|branch| match branch {
+            Branch::Leaf(idx) => schemas[*idx]
+                .validate(instance, location)
+                .err()
+                .map(ValidationError::to_owned),
+            Branch::Nested {
+                location: schema_location,
+                branches,
+            } => {
+                let context = branch_errors(branches, schemas, instance, location);
+                // Every inlined branch failing means the nested `anyOf` failed
+                (context.len() == branches.len()).then(|| {
+                    ValidationError::any_of(
+                        schema_location.clone(),
+                        location.into(),
+                        instance,
+                        context,
+                    )
+                    .to_owned()
+                })
+            }
+        })
+        .collect()
 }
 
 impl Validate for AnyOfValidator {
@@ -46,16 +113,28 @@ impl Validate for AnyOfValidator {
         if self.is_valid(instance) {
             no_error()
         } else {
-            error(ValidationError::any_of(
-                self.location.clone(),
-                location.into(),
-                instance,
-            ))
+            error(self.error(instance, location))
         }
     }
 
     fn is_valid(&self, instance: &Value) -> bool {
-        self.schemas.iter().any(|s| s.is_valid(instance))
+        self.schemas
+            .iter()
+            .zip(&self.types)
+            .any(|(node, types)| matches_type(*types, instance) && node.is_valid(instance))
+    }
+
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = false;
+        for node in &self.schemas {
+            is_valid |= node.trace(instance, location, callback);
+        }
+        is_valid
     }
 
     fn validate<'i>(
@@ -66,11 +145,7 @@ impl Validate for AnyOfValidator {
         if self.is_valid(instance) {
             Ok(())
         } else {
-            Err(ValidationError::any_of(
-                self.location.clone(),
-                location.into(),
-                instance,
-            ))
+            Err(self.error(instance, location))
         }
     }
 
@@ -104,8 +179,8 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
-    use serde_json::{json, Value};
+    use crate::{error::ValidationErrorKind, tests_util};
+    use serde_json::{json, Map, Value};
     use test_case::test_case;
 
     #[test_case(&json!({"anyOf": [{"type": "string"}]}), &json!(1), "/anyOf")]
@@ -113,4 +188,162 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test]
+    fn deeply_nested_chain() {
+        // `serde_json` clones and drops values recursively, so the schema itself needs more
+        // stack than test threads get by default
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(|| {
+                // `json!` would serialize `inner` recursively on every step
+                let schema = (0..1000).fold(json!({"type": "integer"}), |inner, _| {
+                    Value::Object(Map::from_iter([(
+                        "anyOf".to_string(),
+                        Value::Array(vec![json!({"type": "null"}), inner]),
+                    )]))
+                });
+                // The meta-schema check would reject such a deep schema before compiling it
+                let validator = crate::options()
+                    .without_schema_validation()
+                    .build(&schema)
+                    .expect("Valid schema");
+                assert!(validator.is_valid(&json!(1)));
+                assert!(validator.is_valid(&json!(null)));
+                let instance = json!("a");
+                let error = validator.validate(&instance).expect_err("Should fail");
+                assert_eq!(error.schema_path.as_str(), "/anyOf");
+            })
+            .expect("Failed to spawn a thread")
+            .join()
+            .expect("Thread panicked");
+    }
+
+    fn branch_errors(schema: &Value, instance: &Value, report: bool) -> Vec<(String, String)> {
+        let validator = crate::options()
+            .should_report_branch_errors(report)
+            .build(schema)
+            .expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        let ValidationErrorKind::AnyOf { context } = error.kind else {
+            panic!("Unexpected error kind: {:?}", error.kind)
+        };
+        context
+            .iter()
+            .map(|error| (error.schema_path.as_str().to_string(), error.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_first_error_of_each_branch() {
+        let schema = json!({"anyOf": [
+            {"type": "object", "required": ["name"]},
+            {"type": "array", "minItems": 2}
+        ]});
+        assert_eq!(
+            branch_errors(&schema, &json!({"age": 1}), true),
+            [
+                (
+                    "/anyOf/0/required".to_string(),
+                    r#""name" is a required property"#.to_string()
+                ),
+                (
+                    "/anyOf/1/type".to_string(),
+                    r#"{"age":1} is not of type "array""#.to_string()
+                )
+            ]
+        );
+        assert_eq!(
+            branch_errors(&schema, &json!([1]), true),
+            [
+                (
+                    "/anyOf/0/type".to_string(),
+                    r#"[1] is not of type "object""#.to_string()
+                ),
+                (
+                    "/anyOf/1/minItems".to_string(),
+                    "[1] has less than 2 items".to_string()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_inlined_branches_as_one_error() {
+        let schema = json!({"anyOf": [
+            {"anyOf": [{"type": "string"}, {"anyOf": [{"type": "null"}]}]},
+            {"minimum": 10}
+        ]});
+        assert_eq!(
+            branch_errors(&schema, &json!(5), true),
+            [
+                (
+                    "/anyOf/0/anyOf".to_string(),
+                    "5 is not valid under any of the schemas listed in the 'anyOf' keyword"
+                        .to_string()
+                ),
+                (
+                    "/anyOf/1/minimum".to_string(),
+                    "5 is less than the minimum of 10".to_string()
+                )
+            ]
+        );
+        let validator = crate::options()
+            .should_report_branch_errors(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance = json!(5);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let ValidationErrorKind::AnyOf { context } = &error.kind else {
+            panic!("Unexpected error kind: {:?}", error.kind)
+        };
+        let ValidationErrorKind::AnyOf { context } = &context[0].kind else {
+            panic!("Unexpected error kind: {:?}", context[0].kind)
+        };
+        let paths: Vec<_> = context.iter().map(|e| e.schema_path.as_str()).collect();
+        assert_eq!(paths, ["/anyOf/0/anyOf/0/type", "/anyOf/0/anyOf/1/anyOf"]);
+        // A valid inlined branch makes the whole keyword valid
+        assert!(validator.is_valid(&json!(null)));
+    }
+
+    #[test]
+    fn branch_errors_are_disabled_by_default() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"minimum": 10}]});
+        assert!(branch_errors(&schema, &json!(5), false).is_empty());
+    }
+    #[test_case(&json!({"anyOf": [{"type": "integer", "minimum": 5}, {"type": "string"}]}), &json!(7), true; "integer branch")]
+    #[test_case(&json!({"anyOf": [{"type": "integer", "minimum": 5}, {"type": "string"}]}), &json!(7.0), true; "integer valued float")]
+    #[test_case(&json!({"anyOf": [{"type": "integer", "minimum": 5}, {"type": "string"}]}), &json!(3), false; "failing integer branch")]
+    #[test_case(&json!({"anyOf": [{"type": ["null", "number"]}, {"type": "string"}]}), &json!(null), true; "type array")]
+    #[test_case(&json!({"anyOf": [{"type": "object"}, {"minLength": 2}]}), &json!("ab"), true; "branch without type")]
+    #[test_case(&json!({"anyOf": [{"type": "object"}, {"type": "array"}]}), &json!("ab"), false; "no matching branch")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "definitions": {"s": {"type": "string"}}, "anyOf": [{"$ref": "#/definitions/s", "type": "object"}]}), &json!("a"), true; "type next to ref is ignored")]
+    fn type_discriminated_branches(schema: &Value, instance: &Value, expected: bool) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test]
+    fn overridden_type_keyword() {
+        struct AnyType;
+        impl crate::Keyword for AnyType {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &crate::paths::LazyLocation,
+            ) -> Result<(), crate::ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                true
+            }
+        }
+        // The custom `type` keyword accepts anything, so the first subschema must not be skipped
+        let validator = crate::options()
+            .with_keyword("type", |_, _, _| Ok(Box::new(AnyType)))
+            .build(&json!({"anyOf": [{"type": "object"}, {"minLength": 5}]}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("a")));
+    }
 }
@@ -122,7 +122,8 @@ impl Validate for ConstNullValidator {
 struct ConstNumberValidator {
     // This is saved in order to ensure that the error message is not altered by precision loss
     original_value: Number,
-    value: f64,
+    /// `None` beyond the `f64` range, only possible with `arbitrary-precision`
+    value: Option<f64>,
     location: Location,
 }
 
@@ -131,9 +132,7 @@ impl ConstNumberValidator {
     pub(crate) fn compile(original_value: &Number, location: Location) -> CompilationResult {
         Ok(Box::new(ConstNumberValidator {
             original_value: original_value.clone(),
-            value: original_value
-                .as_f64()
-                .expect("A JSON number will always be representable as f64"),
+            value: original_value.as_f64(),
             location,
         }))
     }
@@ -159,7 +158,11 @@ impl Validate for ConstNumberValidator {
 
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
-            (self.value - item.as_f64().expect("Always representable as f64")).abs() < f64::EPSILON
+            match (self.value, item.as_f64()) {
+                (Some(value), Some(item)) => (value - item).abs() < f64::EPSILON,
+                (None, None) => self.original_value == *item,
+                _ => false,
+            }
         } else {
             false
         }
@@ -209,14 +212,16 @@ impl Validate for ConstObjectValidator {
 
 pub(crate) struct ConstStringValidator {
     value: String,
+    ignore_case: bool,
     location: Location,
 }
 
 impl ConstStringValidator {
     #[inline]
-    pub(crate) fn compile(value: &str, location: Location) -> CompilationResult {
+    pub(crate) fn compile(value: &str, ignore_case: bool, location: Location) -> CompilationResult {
         Ok(Box::new(ConstStringValidator {
             value: value.to_string(),
+            ignore_case,
             location,
         }))
     }
@@ -241,7 +246,11 @@ impl Validate for ConstStringValidator {
     }
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            &self.value == item
+            if self.ignore_case {
+                helpers::equal_ignoring_case(&self.value, item)
+            } else {
+                &self.value == item
+            }
         } else {
             false
         }
@@ -261,7 +270,11 @@ pub(crate) fn compile<'a>(
         Value::Null => Some(ConstNullValidator::compile(location)),
         Value::Number(item) => Some(ConstNumberValidator::compile(item, location)),
         Value::Object(map) => Some(ConstObjectValidator::compile(map, location)),
-        Value::String(string) => Some(ConstStringValidator::compile(string, location)),
+        Value::String(string) => Some(ConstStringValidator::compile(
+            string,
+            ctx.config().ignores_string_case(),
+            location,
+        )),
     }
 }
 
@@ -280,4 +293,25 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    // Parsed from text so the key order is kept with `serde_json/preserve_order`
+    #[test_case(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#, true; "object key order")]
+    #[test_case(r#"{"a": {"x": 1, "y": [1, {"p": 1, "q": 2}]}}"#, r#"{"a": {"y": [1.0, {"q": 2, "p": 1}], "x": 1}}"#, true; "nested key order")]
+    #[test_case(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "c": 1}"#, false; "different keys")]
+    #[test_case(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#, false; "extra key")]
+    #[test_case("1", "1.0", true; "integer and float")]
+    #[test_case("1.0", "1", true; "float and integer")]
+    #[test_case("[1, 2]", "[1.0, 2.0]", true; "array of numbers")]
+    #[test_case("[1, 2]", "[2, 1]", false; "array order")]
+    #[test_case(r#"{"a": 1}"#, r#"{"a": 1.0}"#, true; "number in object")]
+    #[test_case(r#"{"a": false}"#, r#"{"a": 0}"#, false; "boolean and number")]
+    fn structural_equality(value: &str, instance: &str, expected: bool) {
+        let value: Value = serde_json::from_str(value).expect("Valid JSON");
+        let instance: Value = serde_json::from_str(instance).expect("Valid JSON");
+        for schema in [json!({"const": value}), json!({"enum": [value]})] {
+            let validator = crate::validator_for(&schema).expect("Invalid schema");
+            assert_eq!(validator.is_valid(&instance), expected);
+            assert_eq!(validator.validate(&instance).is_ok(), expected);
+        }
+    }
 }
@@ -4,7 +4,7 @@ use crate::{
     keywords::CompilationResult,
     node::SchemaNode,
     paths::LazyLocation,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, TraceRecord, Validate},
     Draft,
 };
 use serde_json::{Map, Value};
@@ -34,6 +34,23 @@ impl Validate for ContainsValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        if let Value::Array(items) = instance {
+            let mut any_valid = false;
+            for (idx, item) in items.iter().enumerate() {
+                any_valid |= self.node.trace(item, &location.push(idx), callback);
+            }
+            any_valid
+        } else {
+            true
+        }
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -1,10 +1,11 @@
-//! Validators for `contentMediaType` and `contentEncoding` keywords.
+//! Validators for `contentMediaType`, `contentEncoding` and `contentSchema` keywords.
 use crate::{
     compiler,
     content_encoding::{ContentEncodingCheckType, ContentEncodingConverterType},
     content_media_type::ContentMediaTypeCheckType,
-    error::ValidationError,
+    error::{ErrorIterator, ValidationError},
     keywords::CompilationResult,
+    node::SchemaNode,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
@@ -192,6 +193,78 @@ impl Validate for ContentMediaTypeAndEncodingValidator {
     }
 }
 
+/// Validator for `contentSchema` keyword.
+///
+/// Validates the decoded content of a string instance containing JSON.
+pub(crate) struct ContentSchemaValidator {
+    converter: Option<ContentEncodingConverterType>,
+    node: SchemaNode,
+}
+
+impl ContentSchemaValidator {
+    #[inline]
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        schema: &'a Value,
+        converter: Option<ContentEncodingConverterType>,
+    ) -> CompilationResult<'a> {
+        let ctx = ctx.new_at_location("contentSchema");
+        Ok(Box::new(ContentSchemaValidator {
+            converter,
+            node: compiler::compile(&ctx, ctx.as_resource_ref(schema))?,
+        }))
+    }
+
+    /// Decode the instance into a JSON value.
+    ///
+    /// Content that can't be decoded is left to `contentEncoding` and `contentMediaType`.
+    fn decode(&self, instance: &Value) -> Option<Value> {
+        let Value::String(item) = instance else {
+            return None;
+        };
+        if let Some(converter) = self.converter {
+            let decoded = converter(item).ok()??;
+            serde_json::from_str(&decoded).ok()
+        } else {
+            serde_json::from_str(item).ok()
+        }
+    }
+}
+
+impl Validate for ContentSchemaValidator {
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        if let Some(content) = self.decode(instance) {
+            let errors: Vec<_> = self
+                .node
+                .iter_errors(&content, location)
+                .map(ValidationError::to_owned)
+                .collect();
+            Box::new(errors.into_iter())
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.decode(instance)
+            .map_or(true, |content| self.node.is_valid(&content))
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Some(content) = self.decode(instance) {
+            self.node
+                .validate(&content, location)
+                .map_err(ValidationError::to_owned)
+        } else {
+            Ok(())
+        }
+    }
+}
+
 #[inline]
 pub(crate) fn compile_media_type<'a>(
     ctx: &compiler::Context,
@@ -275,8 +348,29 @@ pub(crate) fn compile_content_encoding<'a>(
     }
 }
 
+#[inline]
+pub(crate) fn compile_content_schema<'a>(
+    ctx: &compiler::Context,
+    schema: &'a Map<String, Value>,
+    subschema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    // `contentSchema` is applied only to JSON content
+    if schema.get("contentMediaType").and_then(Value::as_str) != Some("application/json") {
+        return None;
+    }
+    let converter = match schema.get("contentEncoding") {
+        Some(Value::String(content_encoding)) => {
+            Some(ctx.get_content_encoding_convert(content_encoding)?)
+        }
+        Some(_) => return None,
+        None => None,
+    };
+    Some(ContentSchemaValidator::compile(ctx, subschema, converter))
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::tests_util;
     use referencing::Draft;
     use serde_json::{json, Value};
     use test_case::test_case;
@@ -293,4 +387,80 @@ mod tests {
         let error = validator.validate(instance).expect_err("Should fail");
         assert_eq!(error.schema_path.as_str(), expected);
     }
+
+    fn content_schema() -> Value {
+        json!({
+            "contentMediaType": "application/json",
+            "contentEncoding": "base64",
+            "contentSchema": {
+                "type": "object",
+                "properties": {"age": {"type": "integer"}},
+                "required": ["age"]
+            }
+        })
+    }
+
+    // {"age": 42}
+    #[test_case(&json!("eyJhZ2UiOiA0Mn0="), true)]
+    // {"age": "old"}
+    #[test_case(&json!("eyJhZ2UiOiAib2xkIn0="), false)]
+    // {}
+    #[test_case(&json!("e30="), false)]
+    #[test_case(&json!(42), true)]
+    fn content_schema_assertion(instance: &Value, expected: bool) {
+        let validator = crate::options()
+            .should_validate_content(true)
+            .build(&content_schema())
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+        assert_eq!(validator.iter_errors(instance).next().is_none(), expected);
+    }
+
+    #[test]
+    fn content_schema_location() {
+        let validator = crate::options()
+            .should_validate_content(true)
+            .build(&json!({"properties": {"payload": content_schema()}}))
+            .expect("Invalid schema");
+        let instance = json!({"payload": "eyJhZ2UiOiAib2xkIn0="});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.schema_path.as_str(),
+            "/properties/payload/contentSchema/properties/age/type"
+        );
+        assert_eq!(error.instance_path.as_str(), "/payload/age");
+        assert_eq!(*error.instance, json!("old"));
+        // The decoded document is not a part of the instance
+        assert_eq!(error.instance_fragment(&instance), None);
+    }
+
+    #[test_case(&json!({"contentMediaType": "application/json", "contentSchema": {"type": "array"}}), &json!("{}"))]
+    #[test_case(&json!({"contentMediaType": "text/plain", "contentSchema": {"type": "array"}}), &json!("{}"))]
+    #[test_case(&json!({"contentSchema": {"type": "array"}}), &json!("{}"))]
+    fn content_schema_not_applied(schema: &Value, instance: &Value) {
+        tests_util::is_valid(schema, instance);
+    }
+
+    #[test]
+    fn content_schema_without_encoding() {
+        let schema =
+            json!({"contentMediaType": "application/json", "contentSchema": {"type": "array"}});
+        let validator = crate::options()
+            .should_validate_content(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("[]")));
+        assert!(!validator.is_valid(&json!("{}")));
+        // Not a JSON document, reported by `contentMediaType`
+        let instance = json!("{");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/contentMediaType");
+    }
+
+    #[test]
+    fn content_annotations_by_default() {
+        tests_util::is_valid(&content_schema(), &json!("eyJhZ2UiOiAib2xkIn0="));
+        tests_util::is_valid(&content_schema(), &json!("not base64"));
+    }
 }
@@ -3,6 +3,7 @@ use crate::{
     validator::Validate,
     ValidationError,
 };
+use referencing::Uri;
 use serde_json::{Map, Value};
 
 pub(crate) struct CustomKeyword {
@@ -30,6 +31,64 @@ impl Validate for CustomKeyword {
 }
 
 /// Trait that allows implementing custom validation for keywords.
+///
+/// The location of the keyword within the schema is passed to the keyword factory, while the
+/// location of the current instance is passed to [`Keyword::validate`]. Both are needed to
+/// construct correctly located errors:
+///
+/// ```rust
+/// use jsonschema::{
+///     paths::{LazyLocation, Location},
+///     Keyword, ValidationError,
+/// };
+/// use serde_json::{json, Map, Value};
+///
+/// struct NonEmpty {
+///     location: Location,
+/// }
+///
+/// impl Keyword for NonEmpty {
+///     fn validate<'i>(
+///         &self,
+///         instance: &'i Value,
+///         location: &LazyLocation,
+///     ) -> Result<(), ValidationError<'i>> {
+///         if self.is_valid(instance) {
+///             Ok(())
+///         } else {
+///             Err(ValidationError::custom(
+///                 self.location.clone(),
+///                 location.into(),
+///                 instance,
+///                 "Value must not be empty",
+///             ))
+///         }
+///     }
+///
+///     fn is_valid(&self, instance: &Value) -> bool {
+///         instance.as_str().map_or(true, |value| !value.is_empty())
+///     }
+/// }
+///
+/// fn non_empty_factory<'a>(
+///     _: &'a Map<String, Value>,
+///     _: &'a Value,
+///     location: Location,
+/// ) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+///     Ok(Box::new(NonEmpty { location }))
+/// }
+///
+/// let schema = json!({"properties": {"name": {"non-empty": true}}});
+/// let validator = jsonschema::options()
+///     .with_keyword("non-empty", non_empty_factory)
+///     .build(&schema)
+///     .expect("Invalid schema");
+///
+/// let instance = json!({"name": ""});
+/// let error = validator.validate(&instance).expect_err("Should fail");
+/// assert_eq!(error.instance_path.as_str(), "/name");
+/// assert_eq!(error.schema_path.as_str(), "/properties/name/non-empty");
+/// ```
 pub trait Keyword: Send + Sync {
     /// Validate instance according to a custom specification.
     ///
@@ -37,6 +96,8 @@ pub trait Keyword: Send + Sync {
     /// easily or efficiently expressed in JSON schema.
     ///
     /// The custom validation is applied in addition to the JSON schema validation.
+    /// `location` points to the instance being validated and can be converted into
+    /// a [`Location`] for error reporting.
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -48,12 +109,80 @@ pub trait Keyword: Send + Sync {
     fn is_valid(&self, instance: &Value) -> bool;
 }
 
+/// Where a custom keyword appears in the schema.
+///
+/// Passed to factories registered with [`crate::ValidationOptions::with_contextual_keyword`].
+///
+/// ```rust
+/// use jsonschema::{paths::LazyLocation, Keyword, KeywordContext, ValidationError};
+/// use serde_json::{json, Map, Value};
+///
+/// struct Always;
+///
+/// impl Keyword for Always {
+///     fn validate<'i>(&self, _: &'i Value, _: &LazyLocation) -> Result<(), ValidationError<'i>> {
+///         Ok(())
+///     }
+///
+///     fn is_valid(&self, _: &Value) -> bool {
+///         true
+///     }
+/// }
+///
+/// fn factory<'a>(
+///     _: &'a Map<String, Value>,
+///     _: &'a Value,
+///     context: KeywordContext,
+/// ) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+///     assert_eq!(context.location().as_str(), "/properties/name/always");
+///     assert_eq!(
+///         context.base_uri().map(|uri| uri.as_str()),
+///         Some("https://example.com/name.json")
+///     );
+///     Ok(Box::new(Always))
+/// }
+///
+/// let schema = json!({
+///     "$id": "https://example.com/root.json",
+///     "properties": {"name": {"$id": "name.json", "always": true}}
+/// });
+/// let validator = jsonschema::options()
+///     .with_contextual_keyword("always", factory)
+///     .build(&schema)
+///     .expect("Invalid schema");
+/// assert!(validator.is_valid(&json!({"name": 1})));
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeywordContext {
+    location: Location,
+    base_uri: Option<Uri<String>>,
+}
+
+impl KeywordContext {
+    pub(crate) fn new(location: Location, base_uri: Option<Uri<String>>) -> Self {
+        Self { location, base_uri }
+    }
+    /// Location of the keyword within the schema.
+    #[must_use]
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+    /// Base URI of the schema resource containing the keyword, resolved against any `$id` on the
+    /// way to it.
+    ///
+    /// `None` if the schema has no base URI.
+    #[must_use]
+    pub fn base_uri(&self) -> Option<&Uri<String>> {
+        self.base_uri.as_ref()
+    }
+}
+
 pub(crate) trait KeywordFactory: Send + Sync {
     fn init<'a>(
         &self,
         parent: &'a Map<String, Value>,
         schema: &'a Value,
-        path: Location,
+        context: KeywordContext,
     ) -> Result<Box<dyn Keyword>, ValidationError<'a>>;
 }
 
@@ -71,8 +200,31 @@ where
         &self,
         parent: &'a Map<String, Value>,
         schema: &'a Value,
-        path: Location,
+        context: KeywordContext,
+    ) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+        self(parent, schema, context.location)
+    }
+}
+
+/// A factory that receives the full [`KeywordContext`] instead of the keyword location only.
+pub(crate) struct ContextualKeywordFactory<F>(pub(crate) F);
+
+impl<F> KeywordFactory for ContextualKeywordFactory<F>
+where
+    F: for<'a> Fn(
+            &'a Map<String, Value>,
+            &'a Value,
+            KeywordContext,
+        ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+        + Send
+        + Sync,
+{
+    fn init<'a>(
+        &self,
+        parent: &'a Map<String, Value>,
+        schema: &'a Value,
+        context: KeywordContext,
     ) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
-        self(parent, schema, path)
+        (self.0)(parent, schema, context)
     }
 }
@@ -5,7 +5,7 @@ use crate::{
     node::SchemaNode,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::Validate,
+    validator::{TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
@@ -26,10 +26,10 @@ impl DependenciesValidator {
                         Value::Array(_) => {
                             let validators = vec![required::compile_with_path(
                                 subschema,
-                                kctx.location().clone(),
+                                ctx.location().clone(),
                             )
                             .expect("The required validator compilation does not return None")?];
-                            SchemaNode::from_array(&kctx, validators)
+                            SchemaNode::from_array(&ctx, validators)
                         }
                         _ => compiler::compile(&ctx, ctx.as_resource_ref(subschema))?,
                     };
@@ -59,6 +59,23 @@ impl Validate for DependenciesValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Object(item) = instance {
+            for (property, dependency) in &self.dependencies {
+                if item.contains_key(property) {
+                    is_valid &= dependency.trace(instance, location, callback);
+                }
+            }
+        }
+        is_valid
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
@@ -113,12 +130,12 @@ impl DependentRequiredValidator {
                     }
                     let validators =
                         vec![
-                            required::compile_with_path(subschema, kctx.location().clone())
+                            required::compile_with_path(subschema, ictx.location().clone())
                                 .expect(
                                     "The required validator compilation does not return None",
                                 )?,
                         ];
-                    dependencies.push((key.clone(), SchemaNode::from_array(&kctx, validators)));
+                    dependencies.push((key.clone(), SchemaNode::from_array(&ictx, validators)));
                 } else {
                     return Err(ValidationError::single_type_error(
                         Location::new(),
@@ -232,6 +249,23 @@ impl Validate for DependentSchemasValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Object(item) = instance {
+            for (property, dependency) in &self.dependencies {
+                if item.contains_key(property) {
+                    is_valid &= dependency.trace(instance, location, callback);
+                }
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -276,13 +310,67 @@ pub(crate) fn compile_dependent_schemas<'a>(
 }
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{tests_util, Draft};
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    #[test_case(&json!({"dependencies": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependencies")]
+    #[test_case(&json!({"dependencies": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependencies/bar")]
     #[test_case(&json!({"dependencies": {"bar": {"type": "string"}}}), &json!({"bar": 1}), "/dependencies/bar/type")]
+    #[test_case(&json!({"dependentRequired": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependentRequired/bar")]
+    #[test_case(&json!({"dependentSchemas": {"bar": {"type": "string"}}}), &json!({"bar": 1}), "/dependentSchemas/bar/type")]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(Draft::Draft4)]
+    #[test_case(Draft::Draft6)]
+    #[test_case(Draft::Draft7)]
+    fn mixed_forms(draft: Draft) {
+        let schema = json!({
+            "dependencies": {
+                "credit_card": ["billing_address"],
+                "name": {"required": ["age"]}
+            }
+        });
+        let validator = crate::options()
+            .with_draft(draft)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({})));
+        assert!(validator.is_valid(&json!({"credit_card": 1, "billing_address": "a"})));
+        assert!(validator.is_valid(&json!({"name": "a", "age": 1})));
+        let instance = json!({"credit_card": 1, "name": "a"});
+        let errors = validator
+            .iter_errors(&instance)
+            .map(|error| (error.schema_path.to_string(), error.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (
+                    "/dependencies/credit_card".to_string(),
+                    r#""billing_address" is a required property"#.to_string()
+                ),
+                (
+                    "/dependencies/name/required".to_string(),
+                    r#""age" is a required property"#.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test_case(&json!({"foo": 1, "bar": 2}), true)]
+    #[test_case(&json!({"bar": 2}), false)]
+    fn dependent_schemas_evaluate_properties(instance: &Value, expected: bool) {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "properties": {"foo": true},
+            "dependentSchemas": {
+                "foo": {"properties": {"bar": true}}
+            },
+            "unevaluatedProperties": false
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+    }
 }
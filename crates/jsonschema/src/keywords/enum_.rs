@@ -6,32 +6,98 @@ use crate::{
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
 };
+use ahash::AHashSet;
 use serde_json::{Map, Value};
+use std::sync::Arc;
 
+/// Variants of an `enum` keyword, shared by all keywords with the same value.
 #[derive(Debug)]
-pub(crate) struct EnumValidator {
+pub(crate) struct EnumVariants {
     options: Value,
-    // Types that occur in items
+    // Types that occur in non-string items
     types: PrimitiveTypesBitMap,
+    // Strings are only equal to strings, so they are looked up directly
+    strings: AHashSet<String>,
+    // Whether `strings` holds strings mapped by `helpers::fold_case`
+    ignore_case: bool,
+    // Items that need `helpers::equal`, e.g. `1` is equal to `1.0`
     items: Vec<Value>,
+}
+
+impl EnumVariants {
+    pub(crate) fn new(schema: &Value, items: &[Value], ignore_case: bool) -> EnumVariants {
+        let mut types = PrimitiveTypesBitMap::new();
+        let mut strings = AHashSet::new();
+        let mut others = Vec::new();
+        for item in items {
+            if let Value::String(string) = item {
+                if ignore_case {
+                    strings.insert(helpers::fold_case(string).into_owned());
+                } else {
+                    strings.insert(string.clone());
+                }
+            } else {
+                types |= PrimitiveType::from(item);
+                others.push(item.clone());
+            }
+        }
+        EnumVariants {
+            options: schema.clone(),
+            types,
+            strings,
+            ignore_case,
+            items: others,
+        }
+    }
+
+    fn contains(&self, instance: &Value) -> bool {
+        if let Value::String(string) = instance {
+            if self.ignore_case {
+                return self.contains_ignoring_case(string);
+            }
+            return self.strings.contains(string);
+        }
+        // If the input value type is not in the types present among the enum options, then there
+        // is no reason to compare it against all items - we know that
+        // there are no items with such type at all
+        if self.types.contains_type(PrimitiveType::from(instance)) {
+            self.items.iter().any(|item| helpers::equal(instance, item))
+        } else {
+            false
+        }
+    }
+
+    fn contains_ignoring_case(&self, string: &str) -> bool {
+        // Short ASCII strings are lowercased on the stack to avoid allocating for every instance
+        const BUFFER_SIZE: usize = 64;
+        if string.len() <= BUFFER_SIZE && string.is_ascii() {
+            let mut buffer = [0; BUFFER_SIZE];
+            let folded = &mut buffer[..string.len()];
+            folded.copy_from_slice(string.as_bytes());
+            folded.make_ascii_lowercase();
+            let folded = std::str::from_utf8(folded).expect("ASCII is valid UTF-8");
+            return self.strings.contains(folded);
+        }
+        self.strings.contains(helpers::fold_case(string).as_ref())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct EnumValidator {
+    variants: Arc<EnumVariants>,
     location: Location,
 }
 
 impl EnumValidator {
     #[inline]
     pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
         schema: &'a Value,
         items: &'a [Value],
         location: Location,
     ) -> CompilationResult<'a> {
-        let mut types = PrimitiveTypesBitMap::new();
-        for item in items {
-            types |= PrimitiveType::from(item);
-        }
         Ok(Box::new(EnumValidator {
-            options: schema.clone(),
-            items: items.to_vec(),
-            types,
+            variants: ctx.enum_variants(schema, items),
             location,
         }))
     }
@@ -50,20 +116,13 @@ impl Validate for EnumValidator {
                 self.location.clone(),
                 location.into(),
                 instance,
-                &self.options,
+                &self.variants.options,
             ))
         }
     }
 
     fn is_valid(&self, instance: &Value) -> bool {
-        // If the input value type is not in the types present among the enum options, then there
-        // is no reason to compare it against all items - we know that
-        // there are no items with such type at all
-        if self.types.contains_type(PrimitiveType::from(instance)) {
-            self.items.iter().any(|item| helpers::equal(instance, item))
-        } else {
-            false
-        }
+        self.variants.contains(instance)
     }
 }
 
@@ -71,6 +130,7 @@ impl Validate for EnumValidator {
 pub(crate) struct SingleValueEnumValidator {
     value: Value,
     options: Value,
+    ignore_case: bool,
     location: Location,
 }
 
@@ -79,11 +139,13 @@ impl SingleValueEnumValidator {
     pub(crate) fn compile<'a>(
         schema: &'a Value,
         value: &'a Value,
+        ignore_case: bool,
         location: Location,
     ) -> CompilationResult<'a> {
         Ok(Box::new(SingleValueEnumValidator {
             options: schema.clone(),
             value: value.clone(),
+            ignore_case,
             location,
         }))
     }
@@ -108,7 +170,12 @@ impl Validate for SingleValueEnumValidator {
     }
 
     fn is_valid(&self, instance: &Value) -> bool {
-        helpers::equal(&self.value, instance)
+        match (&self.value, instance) {
+            (Value::String(value), Value::String(instance)) if self.ignore_case => {
+                helpers::equal_ignoring_case(value, instance)
+            }
+            _ => helpers::equal(&self.value, instance),
+        }
     }
 }
 
@@ -122,9 +189,14 @@ pub(crate) fn compile<'a>(
         let location = ctx.location().join("enum");
         if items.len() == 1 {
             let value = items.iter().next().expect("Vec is not empty");
-            Some(SingleValueEnumValidator::compile(schema, value, location))
+            Some(SingleValueEnumValidator::compile(
+                schema,
+                value,
+                ctx.config().ignores_string_case(),
+                location,
+            ))
         } else {
-            Some(EnumValidator::compile(schema, items, location))
+            Some(EnumValidator::compile(ctx, schema, items, location))
         }
     } else {
         Some(Err(ValidationError::single_type_error(
@@ -138,8 +210,10 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{compiler, paths::Location, tests_util};
+    use referencing::Draft;
     use serde_json::{json, Value};
+    use std::{rc::Rc, sync::Arc};
     use test_case::test_case;
 
     #[test_case(&json!({"enum": [1]}), &json!(2), "/enum")]
@@ -147,4 +221,140 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!("b"))]
+    #[test_case(&json!(1))]
+    #[test_case(&json!(1.0))]
+    #[test_case(&json!(null))]
+    #[test_case(&json!([2.0]))]
+    #[test_case(&json!({"a": 3}))]
+    fn mixed_valid(instance: &Value) {
+        tests_util::is_valid(
+            &json!({"enum": ["a", "b", 1, null, [2], {"a": 3.0}]}),
+            instance,
+        )
+    }
+
+    #[test_case(&json!("c"))]
+    #[test_case(&json!("1"))]
+    #[test_case(&json!(2))]
+    #[test_case(&json!(false))]
+    #[test_case(&json!(["a"]))]
+    #[test_case(&json!({"a": "b"}))]
+    fn mixed_invalid(instance: &Value) {
+        tests_util::is_not_valid(
+            &json!({"enum": ["a", "b", 1, null, [2], {"a": 3.0}]}),
+            instance,
+        )
+    }
+
+    #[test]
+    fn many_strings() {
+        let variants: Vec<Value> = (0..300).map(|idx| json!(format!("V{idx:03}"))).collect();
+        let schema = json!({"enum": variants});
+        tests_util::is_valid(&schema, &json!("V000"));
+        tests_util::is_valid(&schema, &json!("V299"));
+        tests_util::is_not_valid(&schema, &json!("V300"));
+        tests_util::is_not_valid(&schema, &json!(0));
+    }
+
+    #[test_case(&json!(1))]
+    #[test_case(&json!(1.0))]
+    fn single_value_valid(instance: &Value) {
+        tests_util::is_valid(&json!({"enum": [1]}), instance);
+        tests_util::is_valid(&json!({"const": 1}), instance);
+    }
+
+    #[test_case(&json!(2))]
+    #[test_case(&json!("1"))]
+    #[test_case(&json!([1]))]
+    #[test_case(&json!(null))]
+    fn single_value_invalid(instance: &Value) {
+        tests_util::is_not_valid(&json!({"enum": [1]}), instance);
+        tests_util::is_not_valid(&json!({"const": 1}), instance);
+    }
+
+    #[test_case(&json!("circle"), &json!("square"), r#""square" is not equal to "circle""#)]
+    #[test_case(&json!([1]), &json!([2]), "[2] is not equal to [1]")]
+    fn single_value_error_message(value: &Value, instance: &Value, expected: &str) {
+        tests_util::expect_errors(&json!({"enum": [value]}), instance, &[expected]);
+    }
+
+    #[test]
+    fn error_message() {
+        tests_util::expect_errors(
+            &json!({"enum": ["a", "b", 1]}),
+            &json!("c"),
+            &[r#""c" is not one of ["a","b",1]"#],
+        );
+    }
+
+    #[test]
+    fn repeated_variants_are_stored_once() {
+        let variants: Vec<Value> = (0..100).map(|idx| json!(idx)).collect();
+        let schema = json!({
+            "properties": {
+                "a": {"enum": variants},
+                "b": {"items": {"enum": variants}}
+            }
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let resolver = validator
+            .registry
+            .try_resolver(&validator.base_uri)
+            .expect("Invalid base URI");
+        let ctx = compiler::Context::new(
+            validator.config(),
+            Arc::clone(&validator.registry),
+            Rc::new(resolver),
+            validator
+                .registry
+                .find_vocabularies(Draft::Draft202012, &schema),
+            Draft::Draft202012,
+            Location::new(),
+        );
+        let _node = compiler::compile(&ctx, ctx.as_resource_ref(&schema)).expect("Invalid schema");
+        let pooled = ctx.enum_variants(&json!(variants), &variants);
+        // The pool, both keywords and `pooled` itself
+        assert_eq!(Arc::strong_count(&pooled), 4);
+        tests_util::is_valid(&schema, &json!({"a": 99, "b": [0, 1.0]}));
+        tests_util::is_not_valid(&schema, &json!({"b": [100]}));
+    }
+
+    #[test_case(&json!({"enum": ["get", "post"]}), &json!("GET"), true; "enum")]
+    #[test_case(&json!({"enum": ["get", "post", 1]}), &json!("Post"), true; "enum with other types")]
+    #[test_case(&json!({"enum": ["get", "post"]}), &json!("PUT"), false; "enum mismatch")]
+    #[test_case(&json!({"enum": ["GET"]}), &json!("get"), true; "single value enum")]
+    #[test_case(&json!({"const": "get"}), &json!("gEt"), true; "const")]
+    #[test_case(&json!({"const": "straße"}), &json!("STRASSE"), false; "lowercase mapping only")]
+    #[test_case(&json!({"const": "ÄB"}), &json!("äb"), true; "non-ASCII")]
+    #[test_case(&json!({"const": "ΣΑΣ"}), &json!("σασ"), true; "const without final sigma rule")]
+    #[test_case(&json!({"enum": ["ΣΑΣ"]}), &json!("σασ"), true; "single value enum without final sigma rule")]
+    #[test_case(&json!({"enum": ["ΣΑΣ", "x"]}), &json!("σασ"), true; "enum without final sigma rule")]
+    #[test_case(&json!({"enum": ["σας", "x"]}), &json!("ΣΑΣ"), false; "enum final sigma")]
+    #[test_case(&json!({"enum": ["get", "x"]}), &json!("GET".repeat(30)), false; "enum long ascii")]
+    #[test_case(&json!({"enum": ["get".repeat(30), "x"]}), &json!("GET".repeat(30)), true; "enum long ascii match")]
+    #[test_case(&json!({"enum": [["get"]]}), &json!(["GET"]), false; "nested strings")]
+    #[test_case(&json!({"enum": ["1"]}), &json!(1), false; "not a string")]
+    fn ignore_string_case(schema: &Value, instance: &Value, expected: bool) {
+        let validator = crate::options()
+            .should_ignore_string_case(true)
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+        // Case is significant by default
+        tests_util::is_not_valid(schema, instance);
+    }
+
+    #[test]
+    fn ignore_string_case_error_message() {
+        let validator = crate::options()
+            .should_ignore_string_case(true)
+            .build(&json!({"enum": ["Get", "Post"]}))
+            .expect("Invalid schema");
+        let instance = json!("put");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.to_string(), r#""put" is not one of ["Get","Post"]"#);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers::saturating_f64, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
@@ -52,8 +52,7 @@ macro_rules! validate {
                     } else if let Some(item) = item.as_i64() {
                         NumCmp::num_lt(item, self.limit)
                     } else {
-                        let item = item.as_f64().expect("Always valid");
-                        NumCmp::num_lt(item, self.limit)
+                        NumCmp::num_lt(saturating_f64(item), self.limit)
                     }
                 } else {
                     true
@@ -74,8 +73,7 @@ impl Validate for ExclusiveMaximumF64Validator {
             } else if let Some(item) = item.as_i64() {
                 NumCmp::num_lt(item, self.limit)
             } else {
-                let item = item.as_f64().expect("Always valid");
-                NumCmp::num_lt(item, self.limit)
+                NumCmp::num_lt(saturating_f64(item), self.limit)
             }
         } else {
             true
@@ -121,7 +119,7 @@ pub(crate) fn compile<'a>(
                 location,
             })))
         } else {
-            let limit = limit.as_f64().expect("Always valid");
+            let limit = saturating_f64(limit);
             Some(Ok(Box::new(ExclusiveMaximumF64Validator {
                 limit,
                 limit_val: (*schema).clone(),
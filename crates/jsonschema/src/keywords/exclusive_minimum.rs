@@ -1,7 +1,7 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers::saturating_f64, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
@@ -52,8 +52,7 @@ macro_rules! validate {
                     } else if let Some(item) = item.as_i64() {
                         NumCmp::num_gt(item, self.limit)
                     } else {
-                        let item = item.as_f64().expect("Always valid");
-                        NumCmp::num_gt(item, self.limit)
+                        NumCmp::num_gt(saturating_f64(item), self.limit)
                     };
                 }
                 true
@@ -73,8 +72,7 @@ impl Validate for ExclusiveMinimumF64Validator {
             } else if let Some(item) = item.as_i64() {
                 NumCmp::num_gt(item, self.limit)
             } else {
-                let item = item.as_f64().expect("Always valid");
-                NumCmp::num_gt(item, self.limit)
+                NumCmp::num_gt(saturating_f64(item), self.limit)
             };
         }
         true
@@ -119,7 +117,7 @@ pub(crate) fn compile<'a>(
                 location,
             })))
         } else {
-            let limit = limit.as_f64().expect("Always valid");
+            let limit = saturating_f64(limit);
             Some(Ok(Box::new(ExclusiveMinimumF64Validator {
                 limit,
                 limit_val: schema.clone(),
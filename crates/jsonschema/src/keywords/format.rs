@@ -758,10 +758,20 @@ pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
-    if !ctx.validates_formats_by_default() {
-        return None;
+    if ctx.validates_formats_by_default() {
+        compile_format(ctx, schema)
+    } else if ctx.are_formats_strict() {
+        // Formats are annotations only, but unknown ones are still rejected
+        match compile_format(ctx, schema) {
+            Some(Err(error)) => Some(Err(error)),
+            _ => None,
+        }
+    } else {
+        None
     }
+}
 
+fn compile_format<'a>(ctx: &compiler::Context, schema: &'a Value) -> Option<CompilationResult<'a>> {
     if let Value::String(format) = schema {
         if let Some((name, func)) = ctx.get_format(format) {
             return Some(CustomFormatValidator::compile(
@@ -794,7 +804,7 @@ pub(crate) fn compile<'a>(
             "uri-template" if draft >= Draft::Draft6 => Some(UriTemplateValidator::compile(ctx)),
             "uuid" if draft >= Draft::Draft201909 => Some(UuidValidator::compile(ctx)),
             name => {
-                if ctx.are_unknown_formats_ignored() {
+                if ctx.are_unknown_formats_ignored() && !ctx.are_formats_strict() {
                     None
                 } else {
                     Some(Err(ValidationError::custom(
@@ -903,6 +913,32 @@ mod tests {
         assert!(!is_valid_duration(input));
     }
 
+    #[test_case("P4Y", true; "years")]
+    #[test_case("P1M", true; "months")]
+    #[test_case("P0D", true; "zero days")]
+    #[test_case("P2W", true; "weeks")]
+    #[test_case("PT36H", true; "hours")]
+    #[test_case("PT1M", true; "minutes")]
+    #[test_case("PT0S", true; "zero seconds")]
+    #[test_case("P1DT2H", true; "days and hours")]
+    #[test_case("P1Y2M3DT4H5M6S", true; "all units")]
+    #[test_case("P", false; "no elements")]
+    #[test_case("PT", false; "no time elements")]
+    #[test_case("P1YT", false; "time separator without elements")]
+    #[test_case("P1", false; "element without unit")]
+    #[test_case("1D", false; "missing designator")]
+    #[test_case("P2D1Y", false; "elements out of order")]
+    #[test_case("P1D2H", false; "missing time separator")]
+    #[test_case("P2S", false; "time element in the date part")]
+    #[test_case("PT1D", false; "date element in the time part")]
+    #[test_case("P1Y2W", false; "weeks combined with other units")]
+    #[test_case("P1.5D", false; "fractional value")]
+    #[test_case("P২Y", false; "non-ASCII digit")]
+    #[test_case("P1D ", false; "trailing whitespace")]
+    fn test_is_valid_duration(input: &str, expected: bool) {
+        assert_eq!(is_valid_duration(input), expected);
+    }
+
     #[test]
     fn unknown_formats_should_not_be_ignored() {
         let schema = json!({ "format": "custom", "type": "string"});
@@ -918,6 +954,59 @@ mod tests {
         );
     }
 
+    #[test_case(json!({"properties": {"created": {"format": "date-tme"}}}), None; "annotation only")]
+    #[test_case(json!({"properties": {"created": {"format": "date-tme"}}}), Some(true); "asserted")]
+    #[test_case(json!({"$schema": "http://json-schema.org/draft-07/schema#", "properties": {"created": {"format": "date-tme"}}}), Some(false); "draft 7")]
+    fn strict_misspelled_format(schema: Value, validate_formats: Option<bool>) {
+        let mut options = crate::options();
+        if let Some(yes) = validate_formats {
+            options.should_validate_formats(yes);
+        }
+        let error = options
+            .should_use_strict_formats(true)
+            .build(&schema)
+            .expect_err("Unknown format");
+        assert_eq!(
+            error.to_string(),
+            "Unknown format: 'date-tme'. Adjust configuration to ignore unrecognized formats"
+        );
+        assert_eq!(error.schema_path.as_str(), "/format");
+        assert_eq!(error.instance_path.as_str(), "/properties/created");
+        assert_eq!(error.instance.as_ref(), &json!("date-tme"));
+    }
+
+    #[test_case(None; "annotation only")]
+    #[test_case(Some(true); "asserted")]
+    fn strict_registered_custom_format(validate_formats: Option<bool>) {
+        let mut options = crate::options();
+        if let Some(yes) = validate_formats {
+            options.should_validate_formats(yes);
+        }
+        let validator = options
+            .should_use_strict_formats(true)
+            .with_format("currency", |value: &str| value.len() == 3)
+            .build(&json!({"format": "currency"}))
+            .expect("Known format");
+        assert_eq!(
+            validator.is_valid(&json!("USDT")),
+            validate_formats.is_none()
+        );
+    }
+
+    #[test]
+    fn unknown_format_ignored_by_default() {
+        assert!(crate::validator_for(&json!({"format": "date-tme"})).is_ok());
+    }
+
+    #[test]
+    fn unknown_format_not_asserted() {
+        // Without strict formats, unknown formats are only reported when formats are asserted
+        assert!(crate::options()
+            .should_ignore_unknown_formats(false)
+            .build(&json!({"format": "date-tme"}))
+            .is_ok());
+    }
+
     #[test_case("2023-01-01", true; "valid regular date")]
     #[test_case("2020-02-29", true; "valid leap year date")]
     #[test_case("2021-02-28", true; "valid non-leap year date")]
@@ -974,6 +1063,10 @@ mod tests {
     #[test_case("23:59:60+00:30", false; "invalid leap second with non-zero minute offset")]
     #[test_case("23:59:60Z", true; "valid leap second at the end of day")]
     #[test_case("23:59:60+00:00", true; "valid leap second with zero offset")]
+    #[test_case("15:59:60-08:00", true; "valid leap second with negative offset")]
+    #[test_case("00:29:60+00:30", true; "valid leap second with positive offset")]
+    #[test_case("22:59:60Z", false; "invalid leap second before the end of day")]
+    #[test_case("23:59:59", false; "invalid time without offset")]
     #[test_case("ab:59:59Z", false; "invalid time with letters in hour")]
     #[test_case("23:ab:59Z", false; "invalid time with letters in minute")]
     #[test_case("23:59:abZ", false; "invalid time with letters in second")]
@@ -1094,4 +1187,39 @@ mod tests {
     fn test_invalid_relative_json_pointer(pointer: &str) {
         assert!(!is_valid_relative_json_pointer(pointer));
     }
+
+    #[test_case("^[a-z]+$"; "anchored class")]
+    #[test_case("(?<year>\\d{4})-\\d{2}"; "named group")]
+    #[test_case("\\p{L}+"; "unicode property")]
+    #[test_case(""; "empty")]
+    fn test_valid_regex(regex: &str) {
+        assert!(is_valid_regex(regex));
+    }
+
+    #[test_case("[a-z"; "unclosed class")]
+    #[test_case("(abc"; "unclosed group")]
+    #[test_case("a{2,1}"; "invalid repetition range")]
+    #[test_case("\\a"; "invalid escape")]
+    fn test_invalid_regex(regex: &str) {
+        assert!(!is_valid_regex(regex));
+    }
+
+    #[test_case("regex", "^a+$", "[a-z")]
+    #[test_case("json-pointer", "/foo/~0bar", "foo/bar")]
+    #[test_case("relative-json-pointer", "1/foo", "/foo")]
+    #[test_case("duration", "P1DT2H", "P")]
+    #[test_case("time", "23:59:60Z", "23:59:60+01:00")]
+    #[test_case("time", "08:30:06.283185Z", "08:30:06")]
+    fn format_assertion(format: &str, valid: &str, invalid: &str) {
+        let schema = json!({"format": format});
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(valid)));
+        assert!(!validator.is_valid(&json!(invalid)));
+        // Formats are annotations by default in Draft 2020-12
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!(invalid)));
+    }
 }
@@ -1,7 +1,17 @@
 use num_cmp::NumCmp;
-use serde_json::{Map, Value};
+use serde_json::{Map, Number, Value};
+use std::borrow::Cow;
 
-use crate::{compiler, paths::Location, primitive_type::PrimitiveType, ValidationError};
+use crate::{
+    compiler,
+    ecma::RegexError,
+    keywords,
+    node::SchemaNode,
+    paths::{LazyLocation, Location},
+    primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    validator::Validate,
+    ValidationError,
+};
 
 macro_rules! num_cmp {
     ($left:expr, $right:expr) => {
@@ -9,8 +19,10 @@ macro_rules! num_cmp {
             NumCmp::num_eq($left, b)
         } else if let Some(b) = $right.as_i64() {
             NumCmp::num_eq($left, b)
+        } else if let Some(b) = $right.as_f64() {
+            NumCmp::num_eq($left, b)
         } else {
-            NumCmp::num_eq($left, $right.as_f64().expect("Always valid"))
+            false
         }
     };
 }
@@ -26,9 +38,11 @@ pub(crate) fn equal(left: &Value, right: &Value) -> bool {
                 num_cmp!(a, right)
             } else if let Some(a) = left.as_i64() {
                 num_cmp!(a, right)
-            } else {
-                let a = left.as_f64().expect("Always valid");
+            } else if let Some(a) = left.as_f64() {
                 num_cmp!(a, right)
+            } else {
+                // Beyond the `f64` range, only possible with `arbitrary-precision`
+                left == right
             }
         }
         (Value::Array(left), Value::Array(right)) => equal_arrays(left, right),
@@ -37,6 +51,39 @@ pub(crate) fn equal(left: &Value, right: &Value) -> bool {
     }
 }
 
+/// Compare strings by their Unicode lowercase mapping, see [`fold_case`].
+#[inline]
+pub(crate) fn equal_ignoring_case(left: &str, right: &str) -> bool {
+    if left.is_ascii() && right.is_ascii() {
+        return left.eq_ignore_ascii_case(right);
+    }
+    left.chars()
+        .flat_map(char::to_lowercase)
+        .eq(right.chars().flat_map(char::to_lowercase))
+}
+
+/// Map every character of `value` to its Unicode lowercase form.
+///
+/// Characters are mapped one by one, unlike `str::to_lowercase`, which also applies
+/// context-dependent rules such as the final sigma. Returns `value` as is if it has no uppercase
+/// characters.
+pub(crate) fn fold_case(value: &str) -> Cow<'_, str> {
+    if value.is_ascii() {
+        if value.bytes().any(|byte| byte.is_ascii_uppercase()) {
+            Cow::Owned(value.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(value)
+        }
+    } else if value.chars().all(|c| {
+        let mut lower = c.to_lowercase();
+        lower.next() == Some(c) && lower.next().is_none()
+    }) {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(value.chars().flat_map(char::to_lowercase).collect())
+    }
+}
+
 #[inline]
 pub(crate) fn equal_arrays(left: &[Value], right: &[Value]) -> bool {
     left.len() == right.len() && {
@@ -53,11 +100,12 @@ pub(crate) fn equal_arrays(left: &[Value], right: &[Value]) -> bool {
 
 #[inline]
 pub(crate) fn equal_objects(left: &Map<String, Value>, right: &Map<String, Value>) -> bool {
+    // Look up keys instead of zipping entries, as they are in insertion order with
+    // `serde_json/preserve_order`
     left.len() == right.len()
         && left
             .iter()
-            .zip(right)
-            .all(|((ka, va), (kb, vb))| ka == kb && equal(va, vb))
+            .all(|(key, value)| right.get(key).is_some_and(|other| equal(value, other)))
 }
 
 #[inline]
@@ -93,6 +141,17 @@ pub(crate) fn map_get_u64<'a>(
     }
 }
 
+/// Report a `patternProperties` key that is not a valid regular expression.
+pub(crate) fn invalid_pattern_property(
+    location: Location,
+    pattern: &str,
+    error: RegexError,
+) -> ValidationError<'static> {
+    error
+        .into_validation_error(location, &Value::String(pattern.to_string()))
+        .to_owned()
+}
+
 /// Fail if the input value is not `u64`.
 pub(crate) fn fail_on_non_positive_integer(
     value: &Value,
@@ -110,6 +169,80 @@ pub(crate) fn fail_on_non_positive_integer(
     }
 }
 
+/// The value of `number` as `f64` for comparing instances with bounds.
+///
+/// Numbers beyond the `f64` range, only possible with `arbitrary-precision`, become an infinity of
+/// the same sign, so they are still ordered correctly against any finite number. The same applies
+/// to bounds beyond the range, e.g. `{"minimum": 1e400}` rejects every finite number.
+pub(crate) fn saturating_f64(number: &Number) -> f64 {
+    if let Some(value) = number.as_f64() {
+        return value;
+    }
+    #[cfg(feature = "arbitrary-precision")]
+    if number.as_str().starts_with('-') {
+        return f64::NEG_INFINITY;
+    }
+    f64::INFINITY
+}
+
+/// Whether `number` has no fractional part, e.g. `5` or `5.0`.
+pub(crate) fn is_integer(number: &Number) -> bool {
+    if number.is_u64() || number.is_i64() {
+        return true;
+    }
+    #[cfg(feature = "arbitrary-precision")]
+    {
+        has_integer_value(number.as_str())
+    }
+    #[cfg(not(feature = "arbitrary-precision"))]
+    {
+        number.as_f64().is_some_and(|number| number.fract() == 0.)
+    }
+}
+
+/// Whether `number` is written without a fractional part or an exponent, as Draft 4 requires
+/// for integers.
+pub(crate) fn is_integer_literal(number: &Number) -> bool {
+    #[cfg(feature = "arbitrary-precision")]
+    {
+        let digits = number.as_str();
+        let digits = digits.strip_prefix('-').unwrap_or(digits);
+        !digits.is_empty() && digits.bytes().all(|digit| digit.is_ascii_digit())
+    }
+    #[cfg(not(feature = "arbitrary-precision"))]
+    {
+        number.is_u64() || number.is_i64()
+    }
+}
+
+/// Whether the decimal number in `text` has no fractional part, e.g. `1.50e1`.
+///
+/// Works on the textual representation, so numbers beyond the range of `f64` are classified
+/// exactly.
+#[cfg(feature = "arbitrary-precision")]
+fn has_integer_value(text: &str) -> bool {
+    let text = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = text.split_once(['e', 'E']).unwrap_or((text, "0"));
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let significant = integer.trim_end_matches('0');
+    let Ok(exponent) = exponent.parse::<i64>() else {
+        // Such exponents move every digit far away from the decimal point
+        return !exponent.starts_with('-')
+            || (fraction.is_empty() && integer.bytes().all(|digit| digit == b'0'));
+    };
+    if !fraction.is_empty() {
+        // Every fractional digit has to move before the decimal point
+        usize::try_from(exponent).is_ok_and(|shift| shift >= fraction.len())
+    } else if significant.is_empty() || exponent >= 0 {
+        true
+    } else {
+        // Only trailing zeros of the integer part may move after the decimal point
+        usize::try_from(exponent.unsigned_abs())
+            .is_ok_and(|shift| shift <= integer.len() - significant.len())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::equal;
@@ -131,4 +264,148 @@ mod tests {
     fn are_not_equal(left: &Value, right: &Value) {
         assert!(!equal(left, right))
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case("123456789012345678901234567890", true)]
+    #[test_case("-123456789012345678901234567890", true)]
+    #[test_case("123456789012345678901234567890.000", true)]
+    #[test_case("123456789012345678901234567890.5", false)]
+    #[test_case("1.5e1", true)]
+    #[test_case("1.25e1", false)]
+    #[test_case("1E400", true)]
+    #[test_case("1200e-2", true)]
+    #[test_case("1210e-2", false)]
+    #[test_case("0.0e-5", true)]
+    #[test_case("1e-99999999999999999999", false)]
+    #[test_case("1e99999999999999999999", true)]
+    fn integer_value(text: &str, expected: bool) {
+        assert_eq!(super::has_integer_value(text), expected);
+    }
+}
+
+/// Collect the first error of each subschema that fails on `instance`.
+pub(crate) fn first_errors(
+    schemas: &[SchemaNode],
+    instance: &Value,
+    location: &LazyLocation,
+) -> Vec<ValidationError<'static>> {
+    schemas
+        .iter()
+        .filter_map(|node| node.validate(instance, location).err())
+        .map(ValidationError::to_owned)
+        .collect()
+}
+
+/// A subschema of `allOf` or `anyOf` as written in the schema.
+pub(crate) enum Branch {
+    /// The subschema compiled at this index of the flattened list.
+    Leaf(usize),
+    /// A subschema consisting of the same keyword only, whose subschemas were inlined.
+    Nested {
+        location: Location,
+        branches: Vec<Branch>,
+    },
+}
+
+/// Compiled subschemas of a flattened `allOf` or `anyOf`.
+pub(crate) struct Flattened<'a> {
+    /// Compiled subschemas together with their source.
+    pub(crate) schemas: Vec<(SchemaNode, &'a Value)>,
+    /// The original nesting of `schemas`.
+    pub(crate) branches: Vec<Branch>,
+}
+
+/// Compile the subschemas of `keyword` (`allOf` or `anyOf`), inlining nested subschemas that
+/// consist of the same keyword only.
+///
+/// Both keywords are associative, so such chains are equivalent to a single flat list. Walking
+/// them with an explicit stack keeps compilation and evaluation of deeply nested chains from
+/// growing the call stack.
+pub(crate) fn compile_flattened<'a>(
+    ctx: &compiler::Context,
+    keyword: &str,
+    items: &'a [Value],
+) -> Result<Flattened<'a>, ValidationError<'a>> {
+    let mut schemas = Vec::with_capacity(items.len());
+    let mut root = Vec::new();
+    let mut stack = vec![(
+        ctx.location().join(keyword),
+        items.iter().enumerate(),
+        Vec::with_capacity(items.len()),
+    )];
+    while let Some((parent, iter, branches)) = stack.last_mut() {
+        let Some((idx, item)) = iter.next() else {
+            let (location, _, branches) = stack.pop().expect("Stack is not empty");
+            match stack.last_mut() {
+                Some((_, _, outer)) => outer.push(Branch::Nested { location, branches }),
+                None => root = branches,
+            }
+            continue;
+        };
+        let location = parent.join(idx);
+        match item
+            .as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.get(keyword))
+        {
+            Some(Value::Array(nested)) if !nested.is_empty() => {
+                stack.push((
+                    location.join(keyword),
+                    nested.iter().enumerate(),
+                    Vec::with_capacity(nested.len()),
+                ));
+            }
+            _ => {
+                branches.push(Branch::Leaf(schemas.len()));
+                let ctx = ctx.at_location(location);
+                schemas.push((compiler::compile(&ctx, ctx.as_resource_ref(item))?, item));
+            }
+        }
+    }
+    Ok(Flattened {
+        schemas,
+        branches: root,
+    })
+}
+
+/// Instance types that may be valid against `schema` according to its `type` keyword, or `None`
+/// if the keyword does not rule out any type.
+pub(crate) fn declared_types(
+    ctx: &compiler::Context,
+    schema: &Value,
+) -> Option<PrimitiveTypesBitMap> {
+    let schema = schema.as_object()?;
+    // `type` may be ignored next to `$ref`, evaluated under another draft, or overridden
+    if schema.contains_key("$ref")
+        || schema.contains_key("$schema")
+        || ctx.get_keyword_factory("type").is_some()
+        || keywords::get_for_draft(ctx, "type").is_none()
+    {
+        return None;
+    }
+    let mut types = PrimitiveTypesBitMap::new();
+    match schema.get("type")? {
+        Value::String(name) => types |= PrimitiveType::try_from(name.as_str()).ok()?,
+        Value::Array(names) => {
+            for name in names {
+                types |= PrimitiveType::try_from(name.as_str()?).ok()?;
+            }
+        }
+        _ => return None,
+    }
+    Some(types)
+}
+
+/// Whether `instance` may have one of `types`. `None` matches any instance.
+///
+/// Numbers match both `number` and `integer`, the exact check is left to the `type` keyword.
+#[inline]
+pub(crate) fn matches_type(types: Option<PrimitiveTypesBitMap>, instance: &Value) -> bool {
+    types.map_or(true, |types| match instance {
+        Value::Number(_) => {
+            types.contains_type(PrimitiveType::Number)
+                || types.contains_type(PrimitiveType::Integer)
+        }
+        _ => types.contains_type(PrimitiveType::from(instance)),
+    })
 }
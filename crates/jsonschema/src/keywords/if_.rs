@@ -4,7 +4,7 @@ use crate::{
     keywords::CompilationResult,
     node::SchemaNode,
     paths::LazyLocation,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, TraceRecord, Validate},
     ValidationError,
 };
 use serde_json::{Map, Value};
@@ -53,6 +53,19 @@ impl Validate for IfThenValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        if self.schema.trace(instance, location, callback) {
+            self.then_schema.trace(instance, location, callback)
+        } else {
+            true
+        }
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -121,6 +134,19 @@ impl Validate for IfElseValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        if self.schema.trace(instance, location, callback) {
+            true
+        } else {
+            self.else_schema.trace(instance, location, callback)
+        }
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -194,6 +220,19 @@ impl Validate for IfThenElseValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        if self.schema.trace(instance, location, callback) {
+            self.then_schema.trace(instance, location, callback)
+        } else {
+            self.else_schema.trace(instance, location, callback)
+        }
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
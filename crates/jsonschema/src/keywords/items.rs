@@ -4,7 +4,7 @@ use crate::{
     keywords::CompilationResult,
     node::SchemaNode,
     paths::LazyLocation,
-    validator::{PartialApplication, Validate},
+    validator::{Descent, PartialApplication, TraceRecord, Validate},
     ValidationError,
 };
 use serde_json::{Map, Value};
@@ -55,6 +55,21 @@ impl Validate for ItemsArrayValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Array(items) = instance {
+            for (idx, (item, node)) in items.iter().zip(self.items.iter()).enumerate() {
+                is_valid &= node.trace(item, &location.push(idx), callback);
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -67,6 +82,40 @@ impl Validate for ItemsArrayValidator {
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if let Value::Array(items) = instance {
+            if !items.is_empty() {
+                let mut results = Vec::with_capacity(self.items.len());
+                let mut max_index_applied = 0;
+                for (idx, (item, node)) in items.iter().zip(self.items.iter()).enumerate() {
+                    let path = location.push(idx);
+                    results.push(node.apply_rooted(item, &path));
+                    max_index_applied = idx;
+                }
+                // Per draft 2019-09 section https://json-schema.org/draft/2019-09/json-schema-core.html#rfc.section.9.3.1.1
+                // the array form of `items` produces an annotation with the largest index to
+                // which a subschema was applied, or `true` if it was applied to every index.
+                let schema_was_applied: Value = if results.len() == items.len() {
+                    true.into()
+                } else {
+                    max_index_applied.into()
+                };
+                let mut output: PartialApplication = results.into_iter().collect();
+                output.annotate(schema_was_applied.into());
+                return output;
+            }
+        }
+        PartialApplication::valid_empty()
+    }
+
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        let node = match (instance, segment.parse::<usize>()) {
+            (Value::Array(_), Ok(idx)) => self.items.get(idx),
+            _ => None,
+        };
+        Some(Descent::Child(node))
+    }
 }
 
 pub(crate) struct ItemsObjectValidator {
@@ -104,6 +153,21 @@ impl Validate for ItemsObjectValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Array(items) = instance {
+            for (idx, item) in items.iter().enumerate() {
+                is_valid &= self.node.trace(item, &location.push(idx), callback);
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -137,6 +201,11 @@ impl Validate for ItemsObjectValidator {
             PartialApplication::valid_empty()
         }
     }
+
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        let applies = instance.is_array() && segment.parse::<usize>().is_ok();
+        Some(Descent::Child(applies.then_some(&self.node)))
+    }
 }
 
 pub(crate) struct ItemsObjectSkipPrefixValidator {
@@ -190,6 +259,21 @@ impl Validate for ItemsObjectSkipPrefixValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Array(items) = instance {
+            for (idx, item) in items.iter().enumerate().skip(self.skip_prefix) {
+                is_valid &= self.node.trace(item, &location.push(idx), callback);
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -222,6 +306,14 @@ impl Validate for ItemsObjectSkipPrefixValidator {
             PartialApplication::valid_empty()
         }
     }
+
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        let applies = instance.is_array()
+            && segment
+                .parse::<usize>()
+                .is_ok_and(|idx| idx >= self.skip_prefix);
+        Some(Descent::Child(applies.then_some(&self.node)))
+    }
 }
 
 #[inline]
@@ -1,12 +1,12 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::{type_, CompilationResult},
+    keywords::{helpers::is_integer_literal, type_, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
 };
-use serde_json::{json, Map, Number, Value};
+use serde_json::{json, Map, Value};
 use std::convert::TryFrom;
 
 pub(crate) struct MultipleTypesValidator {
@@ -56,7 +56,7 @@ impl Validate for MultipleTypesValidator {
             Value::Null => self.types.contains_type(PrimitiveType::Null),
             Value::Number(num) => {
                 self.types.contains_type(PrimitiveType::Number)
-                    || (self.types.contains_type(PrimitiveType::Integer) && is_integer(num))
+                    || (self.types.contains_type(PrimitiveType::Integer) && is_integer_literal(num))
             }
             Value::Object(_) => self.types.contains_type(PrimitiveType::Object),
             Value::String(_) => self.types.contains_type(PrimitiveType::String),
@@ -94,7 +94,7 @@ impl IntegerTypeValidator {
 impl Validate for IntegerTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(num) = instance {
-            is_integer(num)
+            is_integer_literal(num)
         } else {
             false
         }
@@ -117,10 +117,6 @@ impl Validate for IntegerTypeValidator {
     }
 }
 
-fn is_integer(num: &Number) -> bool {
-    num.is_u64() || num.is_i64()
-}
-
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
@@ -36,12 +36,26 @@ impl MaxLengthValidator {
         }
         Err(fail_on_non_positive_integer(schema, location))
     }
+
+    /// A char is 1 to 4 bytes long in UTF-8, so the byte length bounds the char count
+    /// from both sides and the full count is only needed in between.
+    #[inline]
+    fn is_too_long(&self, item: &str) -> bool {
+        let bytes = item.len() as u64;
+        if bytes <= self.limit {
+            false
+        } else if bytes > self.limit.saturating_mul(4) {
+            true
+        } else {
+            (bytecount::num_chars(item.as_bytes()) as u64) > self.limit
+        }
+    }
 }
 
 impl Validate for MaxLengthValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            if (bytecount::num_chars(item.as_bytes()) as u64) > self.limit {
+            if self.is_too_long(item) {
                 return false;
             }
         }
@@ -54,7 +68,7 @@ impl Validate for MaxLengthValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
-            if (bytecount::num_chars(item.as_bytes()) as u64) > self.limit {
+            if self.is_too_long(item) {
                 return Err(ValidationError::max_length(
                     self.location.clone(),
                     location.into(),
@@ -81,9 +95,26 @@ pub(crate) fn compile<'a>(
 mod tests {
     use crate::tests_util;
     use serde_json::json;
+    use test_case::test_case;
 
     #[test]
     fn location() {
         tests_util::assert_schema_location(&json!({"maxLength": 1}), &json!("ab"), "/maxLength")
     }
+
+    #[test_case(2, "ab", true)]
+    #[test_case(2, "abc", false)]
+    #[test_case(2, "éé", true)]
+    #[test_case(2, "ééé", false)]
+    #[test_case(2, "😀😀", true)]
+    #[test_case(2, "😀😀😀", false)]
+    #[test_case(1, "日", true)]
+    #[test_case(0, "", true)]
+    #[test_case(0, "a", false)]
+    fn byte_length_bounds(limit: u64, instance: &str, expected: bool) {
+        let validator = crate::validator_for(&json!({"maxLength": limit})).expect("Invalid schema");
+        let instance = json!(instance);
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.validate(&instance).is_ok(), expected);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers::saturating_f64, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
@@ -52,8 +52,7 @@ macro_rules! validate {
                     } else if let Some(item) = item.as_i64() {
                         !NumCmp::num_gt(item, self.limit)
                     } else {
-                        let item = item.as_f64().expect("Always valid");
-                        !NumCmp::num_gt(item, self.limit)
+                        !NumCmp::num_gt(saturating_f64(item), self.limit)
                     };
                 }
                 true
@@ -73,8 +72,7 @@ impl Validate for MaximumF64Validator {
             } else if let Some(item) = item.as_i64() {
                 !NumCmp::num_gt(item, self.limit)
             } else {
-                let item = item.as_f64().expect("Always valid");
-                !NumCmp::num_gt(item, self.limit)
+                !NumCmp::num_gt(saturating_f64(item), self.limit)
             };
         }
         true
@@ -119,7 +117,7 @@ pub(crate) fn compile<'a>(
                 location,
             })))
         } else {
-            let limit = limit.as_f64().expect("Always valid");
+            let limit = saturating_f64(limit);
             Some(Ok(Box::new(MaximumF64Validator {
                 limit,
                 limit_val: schema.clone(),
@@ -144,6 +142,9 @@ mod tests {
 
     #[test_case(&json!({"maximum": 1_u64 << 54}), &json!((1_u64 << 54) + 1))]
     #[test_case(&json!({"maximum": 1_i64 << 54}), &json!((1_i64 << 54) + 1))]
+    #[test_case(&json!({"maximum": 9_007_199_254_740_992_u64}), &json!(9_007_199_254_740_993_u64))]
+    #[test_case(&json!({"maximum": -9_007_199_254_740_993_i64}), &json!(-9_007_199_254_740_992_i64))]
+    #[test_case(&json!({"maximum": 9_007_199_254_740_992.0}), &json!(9_007_199_254_740_993_u64))]
     fn is_not_valid(schema: &Value, instance: &Value) {
         tests_util::is_not_valid(schema, instance)
     }
@@ -36,12 +36,26 @@ impl MinLengthValidator {
         }
         Err(fail_on_non_positive_integer(schema, location))
     }
+
+    /// A char is 1 to 4 bytes long in UTF-8, so the byte length bounds the char count
+    /// from both sides and the full count is only needed in between.
+    #[inline]
+    fn is_too_short(&self, item: &str) -> bool {
+        let bytes = item.len() as u64;
+        if bytes < self.limit {
+            true
+        } else if bytes >= self.limit.saturating_mul(4) {
+            false
+        } else {
+            (bytecount::num_chars(item.as_bytes()) as u64) < self.limit
+        }
+    }
 }
 
 impl Validate for MinLengthValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            if (bytecount::num_chars(item.as_bytes()) as u64) < self.limit {
+            if self.is_too_short(item) {
                 return false;
             }
         }
@@ -54,7 +68,7 @@ impl Validate for MinLengthValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
-            if (bytecount::num_chars(item.as_bytes()) as u64) < self.limit {
+            if self.is_too_short(item) {
                 return Err(ValidationError::min_length(
                     self.location.clone(),
                     location.into(),
@@ -81,9 +95,25 @@ pub(crate) fn compile<'a>(
 mod tests {
     use crate::tests_util;
     use serde_json::json;
+    use test_case::test_case;
 
     #[test]
     fn location() {
         tests_util::assert_schema_location(&json!({"minLength": 1}), &json!(""), "/minLength")
     }
+
+    #[test_case(2, "ab", true)]
+    #[test_case(2, "a", false)]
+    #[test_case(2, "éé", true)]
+    #[test_case(2, "é", false)]
+    #[test_case(2, "😀", false)]
+    #[test_case(2, "😀😀", true)]
+    #[test_case(3, "日本", false)]
+    #[test_case(0, "", true)]
+    fn byte_length_bounds(limit: u64, instance: &str, expected: bool) {
+        let validator = crate::validator_for(&json!({"minLength": limit})).expect("Invalid schema");
+        let instance = json!(instance);
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.validate(&instance).is_ok(), expected);
+    }
 }
@@ -92,6 +92,7 @@ pub(crate) enum BuiltinKeyword {
     PropertyNames,
     ContentMediaType,
     ContentEncoding,
+    ContentSchema,
     If,
     RecursiveRef,
     DependentRequired,
@@ -138,6 +139,7 @@ impl BuiltinKeyword {
             Self::PropertyNames => "propertyNames",
             Self::ContentMediaType => "contentMediaType",
             Self::ContentEncoding => "contentEncoding",
+            Self::ContentSchema => "contentSchema",
             Self::If => "if",
             Self::RecursiveRef => "$recursiveRef",
             Self::DependentRequired => "dependentRequired",
@@ -150,7 +152,194 @@ impl BuiltinKeyword {
     }
 }
 
+/// How a keyword holds its subschemas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Subschemas {
+    /// A single subschema or an array of subschemas.
+    Inline,
+    /// An object with subschemas as its values.
+    Map,
+}
+
+impl BuiltinKeyword {
+    pub(crate) const ALL: &'static [BuiltinKeyword] = &[
+        Self::Ref,
+        Self::AdditionalItems,
+        Self::AdditionalProperties,
+        Self::AllOf,
+        Self::AnyOf,
+        Self::Dependencies,
+        Self::Enum,
+        Self::Format,
+        Self::Items,
+        Self::MaxItems,
+        Self::MaxLength,
+        Self::MaxProperties,
+        Self::MinItems,
+        Self::MinLength,
+        Self::MinProperties,
+        Self::MultipleOf,
+        Self::Not,
+        Self::OneOf,
+        Self::Pattern,
+        Self::PatternProperties,
+        Self::Properties,
+        Self::Required,
+        Self::UniqueItems,
+        Self::Maximum,
+        Self::Minimum,
+        Self::Type,
+        Self::Const,
+        Self::Contains,
+        Self::ExclusiveMaximum,
+        Self::ExclusiveMinimum,
+        Self::PropertyNames,
+        Self::ContentMediaType,
+        Self::ContentEncoding,
+        Self::ContentSchema,
+        Self::If,
+        Self::RecursiveRef,
+        Self::DependentRequired,
+        Self::DependentSchemas,
+        Self::PrefixItems,
+        Self::UnevaluatedItems,
+        Self::UnevaluatedProperties,
+        Self::DynamicRef,
+    ];
+
+    pub(crate) fn by_name(name: &str) -> Option<&'static BuiltinKeyword> {
+        Self::ALL.iter().find(|keyword| keyword.as_str() == name)
+    }
+
+    /// How this keyword holds its subschemas, if it has any.
+    pub(crate) fn subschemas(&self) -> Option<Subschemas> {
+        match self {
+            Self::AdditionalItems
+            | Self::AdditionalProperties
+            | Self::AllOf
+            | Self::AnyOf
+            | Self::Contains
+            | Self::ContentSchema
+            | Self::If
+            | Self::Items
+            | Self::Not
+            | Self::OneOf
+            | Self::PrefixItems
+            | Self::PropertyNames
+            | Self::UnevaluatedItems
+            | Self::UnevaluatedProperties => Some(Subschemas::Inline),
+            Self::Dependencies
+            | Self::DependentSchemas
+            | Self::PatternProperties
+            | Self::Properties => Some(Subschemas::Map),
+            Self::Ref
+            | Self::Enum
+            | Self::Format
+            | Self::MaxItems
+            | Self::MaxLength
+            | Self::MaxProperties
+            | Self::MinItems
+            | Self::MinLength
+            | Self::MinProperties
+            | Self::MultipleOf
+            | Self::Pattern
+            | Self::Required
+            | Self::UniqueItems
+            | Self::Maximum
+            | Self::Minimum
+            | Self::Type
+            | Self::Const
+            | Self::ExclusiveMaximum
+            | Self::ExclusiveMinimum
+            | Self::ContentMediaType
+            | Self::ContentEncoding
+            | Self::RecursiveRef
+            | Self::DependentRequired
+            | Self::DynamicRef => None,
+        }
+    }
+}
+
+/// JSON types that determine which keywords may affect validation of an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InstanceKind {
+    Array,
+    Number,
+    Object,
+    String,
+    /// `null` and booleans
+    Other,
+}
+
+impl InstanceKind {
+    pub(crate) const ALL: [InstanceKind; 5] = [
+        InstanceKind::Array,
+        InstanceKind::Number,
+        InstanceKind::Object,
+        InstanceKind::String,
+        InstanceKind::Other,
+    ];
+
+    #[inline]
+    pub(crate) fn of(instance: &Value) -> InstanceKind {
+        match instance {
+            Value::Array(_) => InstanceKind::Array,
+            Value::Number(_) => InstanceKind::Number,
+            Value::Object(_) => InstanceKind::Object,
+            Value::String(_) => InstanceKind::String,
+            Value::Null | Value::Bool(_) => InstanceKind::Other,
+        }
+    }
+}
+
+impl BuiltinKeyword {
+    /// The only kind of instances this keyword can fail on, if it is limited to one.
+    pub(crate) fn applies_only_to(&self) -> Option<InstanceKind> {
+        match self {
+            Self::AdditionalItems
+            | Self::Contains
+            | Self::Items
+            | Self::MaxItems
+            | Self::MinItems
+            | Self::PrefixItems
+            | Self::UnevaluatedItems
+            | Self::UniqueItems => Some(InstanceKind::Array),
+            Self::ExclusiveMaximum
+            | Self::ExclusiveMinimum
+            | Self::Maximum
+            | Self::Minimum
+            | Self::MultipleOf => Some(InstanceKind::Number),
+            Self::AdditionalProperties
+            | Self::Dependencies
+            | Self::DependentRequired
+            | Self::DependentSchemas
+            | Self::MaxProperties
+            | Self::MinProperties
+            | Self::PatternProperties
+            | Self::Properties
+            | Self::PropertyNames
+            | Self::Required
+            | Self::UnevaluatedProperties => Some(InstanceKind::Object),
+            Self::ContentEncoding
+            | Self::ContentMediaType
+            | Self::ContentSchema
+            | Self::MaxLength
+            | Self::MinLength
+            | Self::Pattern => Some(InstanceKind::String),
+            _ => None,
+        }
+    }
+}
+
 impl Keyword {
+    pub(crate) fn applies_to(&self, kind: InstanceKind) -> bool {
+        match self {
+            Self::Buildin(keyword) => keyword
+                .applies_only_to()
+                .map_or(true, |expected| expected == kind),
+            Self::Custom(_) => true,
+        }
+    }
     pub(crate) fn custom(name: impl Into<String>) -> Self {
         Keyword::Custom(name.into().into_boxed_str())
     }
@@ -323,6 +512,30 @@ pub(crate) fn get_for_draft<'a>(
             BuiltinKeyword::ContentEncoding.into(),
             content::compile_content_encoding,
         )),
+        (Draft::Draft201909 | Draft::Draft202012, "contentMediaType")
+            if ctx.validates_content() && ctx.has_vocabulary(&Vocabulary::Content) =>
+        {
+            Some((
+                BuiltinKeyword::ContentMediaType.into(),
+                content::compile_media_type,
+            ))
+        }
+        (Draft::Draft201909 | Draft::Draft202012, "contentEncoding")
+            if ctx.validates_content() && ctx.has_vocabulary(&Vocabulary::Content) =>
+        {
+            Some((
+                BuiltinKeyword::ContentEncoding.into(),
+                content::compile_content_encoding,
+            ))
+        }
+        (Draft::Draft201909 | Draft::Draft202012, "contentSchema")
+            if ctx.validates_content() && ctx.has_vocabulary(&Vocabulary::Content) =>
+        {
+            Some((
+                BuiltinKeyword::ContentSchema.into(),
+                content::compile_content_schema,
+            ))
+        }
         (Draft::Draft7 | Draft::Draft201909 | Draft::Draft202012, "if")
             if ctx.has_vocabulary(&Vocabulary::Applicator) =>
         {
@@ -475,6 +688,36 @@ mod tests {
             .expect("Invalid schema")
             .is_valid(&instance));
     }
+    // Subschemas behind `$ref` to a non-schema location are not checked against the meta-schema
+    #[test_case(&json!({"$ref": "#/x", "x": {"oneOf": []}}), "[] has less than 1 item")]
+    #[test_case(&json!({"$ref": "#/x", "x": {"anyOf": []}}), "[] has less than 1 item")]
+    #[test_case(&json!({"$ref": "#/x", "x": {"patternProperties": []}}), r#"[] is not of type "object""#)]
+    #[test_case(&json!({"$ref": "#/x", "x": {"properties": {"a": 1}}}), r#"1 is not of types "boolean", "object""#)]
+    #[test_case(&json!({"$ref": "#/x", "x": {"items": [1]}}), r#"1 is not of types "boolean", "object""#)]
+    #[test_case(&json!({"$ref": "#/x", "x": {"pattern": "("}}), r#""(" is not a "regex""#)]
+    fn malformed_subschema(schema: &Value, expected: &str) {
+        let error = crate::validator_for(schema).expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn malformed_subschema_location() {
+        let schema = json!({"$ref": "#/x", "x": {"oneOf": []}});
+        let error = crate::validator_for(&schema).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/$ref/oneOf");
+    }
+
+    #[test_case("anyOf")]
+    #[test_case("oneOf")]
+    fn empty_applicator_without_schema_validation(keyword: &str) {
+        let schema = json!({keyword: []});
+        let error = crate::options()
+            .without_schema_validation()
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(error.to_string(), "[] has less than 1 item");
+    }
+
     #[test_case(&json!({"additionalProperties": false}), &json!({}))]
     #[test_case(&json!({"additionalItems": false, "items": true}), &json!([]))]
     fn is_valid(schema: &Value, instance: &Value) {
@@ -507,6 +750,12 @@ mod tests {
     #[test_case(&json!({"const": [{"b": 1.0}]}), &json!([{"b": 1}]))]
     // const:: Array in Object
     #[test_case(&json!({"const": {"c": [1.0]}}), &json!({"c": [1]}))]
+    // Exponent notation
+    #[test_case(&json!({"const": 1e0}), &json!(1))]
+    #[test_case(&json!({"enum": [1]}), &json!(1e0))]
+    // Negative integers
+    #[test_case(&json!({"const": -1.0}), &json!(-1))]
+    #[test_case(&json!({"enum": [-1]}), &json!(-1e0))]
     fn numeric_equivalence(schema: &Value, instance: &Value) {
         // See: GH-149
         assert!(crate::is_valid(schema, instance));
@@ -523,4 +772,143 @@ mod tests {
         assert_eq!(errors[0].to_string(), r#""foo" is a required property"#);
         assert_eq!(errors[1].to_string(), r#""bar" is a required property"#);
     }
+
+    #[test_case(&json!("abc"), true)]
+    #[test_case(&json!("ABC"), false)]
+    #[test_case(&json!(42), true)]
+    #[test_case(&json!(101), false)]
+    #[test_case(&json!({"a": 1}), true)]
+    #[test_case(&json!({"b": 1}), false)]
+    #[test_case(&json!([1, 2]), true)]
+    #[test_case(&json!([1, 1]), false)]
+    #[test_case(&json!(null), true)]
+    #[test_case(&json!(true), false)]
+    fn mixed_type_keywords(instance: &Value, expected: bool) {
+        let schema = json!({
+            "maxLength": 10,
+            "pattern": "^[a-z]+$",
+            "maximum": 100,
+            "multipleOf": 2,
+            "required": ["a"],
+            "uniqueItems": true,
+            "not": {"const": true}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test_case(f64::NAN)]
+    #[test_case(f64::INFINITY)]
+    #[test_case(f64::NEG_INFINITY)]
+    fn non_finite_float(value: f64) {
+        // Not a JSON number, `serde_json` stores it as `null`
+        let instance = Value::from(value);
+        assert_eq!(instance, Value::Null);
+        for schema in [
+            json!({"type": "number"}),
+            json!({"type": "integer"}),
+            json!({"type": "number", "minimum": 5}),
+            json!({"type": "number", "maximum": 5}),
+        ] {
+            let validator = crate::validator_for(&schema).expect("Invalid schema");
+            assert!(!validator.is_valid(&instance));
+            assert!(validator.validate(&instance).is_err());
+        }
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(&json!({"minimum": 0}), "1e400", true)]
+    #[test_case(&json!({"minimum": 0}), "-1e400", false)]
+    #[test_case(&json!({"minimum": -5}), "-1e400", false)]
+    #[test_case(&json!({"minimum": 5.5}), "1e400", true)]
+    #[test_case(&json!({"maximum": 5}), "1e400", false)]
+    #[test_case(&json!({"maximum": 5.5}), "-1e400", true)]
+    #[test_case(&json!({"exclusiveMinimum": 5}), "1e400", true)]
+    #[test_case(&json!({"exclusiveMinimum": 5.5}), "-1e400", false)]
+    #[test_case(&json!({"exclusiveMaximum": 5}), "-1e400", true)]
+    #[test_case(&json!({"exclusiveMaximum": 5.5}), "1e400", false)]
+    #[test_case(&json!({"multipleOf": 2}), "1e400", true)]
+    #[test_case(&json!({"multipleOf": 3}), "-1e400", false)]
+    #[test_case(&json!({"multipleOf": 7}), "7e400", true)]
+    #[test_case(&json!({"multipleOf": 0.5}), "1.5e400", true)]
+    #[test_case(&json!({"multipleOf": 0.3}), "1e400", false)]
+    #[test_case(&json!({"const": 5.5}), "1e400", false)]
+    #[test_case(&json!({"enum": [1, 5.5]}), "1e400", false)]
+    #[test_case(&json!({"type": "number", "minimum": 5}), "1e400", true; "scalar schema")]
+    #[test_case(&json!({"type": "number", "minimum": 5}), "-1e400", false; "scalar schema negative")]
+    fn number_beyond_f64_range(schema: &Value, instance: &str, expected: bool) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let instance: Value = serde_json::from_str(instance).expect("Valid JSON");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.validate(&instance).is_ok(), expected);
+        // Also as a property, outside of the inlined scalar check
+        let instance = json!({"a": instance});
+        let validator =
+            crate::validator_for(&json!({"properties": {"a": schema}})).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(r#"{"minimum": 1e400}"#, "5", false)]
+    #[test_case(r#"{"minimum": 1e400}"#, "1e500", true)]
+    #[test_case(r#"{"minimum": -1e400}"#, "-5", true)]
+    #[test_case(r#"{"maximum": 1e400}"#, "5", true)]
+    #[test_case(r#"{"maximum": -1e400}"#, "-5", false)]
+    #[test_case(r#"{"exclusiveMinimum": -1e400}"#, "0", true)]
+    #[test_case(r#"{"exclusiveMaximum": 1e400}"#, "0", true)]
+    #[test_case(r#"{"exclusiveMaximum": -1e400}"#, "0", false)]
+    #[test_case(r#"{"type": "number", "maximum": 1e400}"#, "5", true; "scalar schema")]
+    #[test_case(r#"{"type": "number", "minimum": 1e400}"#, "5", false; "scalar schema minimum")]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "0", true)]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "1e400", true)]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "-3e401", true)]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "5", false)]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "1.5e400", false)]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "1e399", false)]
+    #[test_case(r#"{"multipleOf": 2.5e400}"#, "7.5e400", true)]
+    #[test_case(r#"{"multipleOf": 2.5e400}"#, "1e401", true)]
+    #[test_case(r#"{"multipleOf": 2.5e400}"#, "1.25e400", false)]
+    #[test_case(r#"{"multipleOf": 3e400}"#, "9e400", true)]
+    #[test_case(r#"{"multipleOf": 3e400}"#, "1e401", false)]
+    #[test_case(r#"{"multipleOf": 1e400}"#, "1e99999999999999999999", true; "huge exponent")]
+    fn limit_beyond_f64_range(schema: &str, instance: &str, expected: bool) {
+        let schema: Value = serde_json::from_str(schema).expect("Valid JSON");
+        let instance: Value = serde_json::from_str(instance).expect("Valid JSON");
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.validate(&instance).is_ok(), expected);
+        let instance = json!({"a": instance});
+        let validator =
+            crate::validator_for(&json!({"properties": {"a": schema}})).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case("0", "e-10", true; "trailing zeros")]
+    #[test_case("5", "e-1", false; "fractional digit")]
+    #[test_case("6", "e-1", false; "fractional digit that is a multiple")]
+    #[test_case("60", "e-1", true; "integer after shift")]
+    fn multiple_of_long_mantissa(last: &str, exponent: &str, expected: bool) {
+        // Beyond the `f64` range with digits that move after the decimal point
+        let instance = format!("1{}{last}{exponent}", "0".repeat(400));
+        let instance: Value = serde_json::from_str(&instance).expect("Valid JSON");
+        let validator = crate::validator_for(&json!({"multipleOf": 2})).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test]
+    fn number_beyond_f64_range_without_numeric_keywords() {
+        let instance: Value = serde_json::from_str("1e400").expect("Valid JSON");
+        for schema in [
+            json!({"type": "number"}),
+            json!({"const": instance.clone()}),
+            json!({"enum": [instance.clone()]}),
+            json!({"uniqueItems": true}),
+        ] {
+            let validator = crate::validator_for(&schema).expect("Invalid schema");
+            assert!(validator.is_valid(&instance));
+        }
+    }
 }
@@ -7,10 +7,273 @@ use crate::{
     validator::Validate,
 };
 use fraction::{BigFraction, BigUint};
-use serde_json::{Map, Value};
+use serde_json::{Map, Number, Value};
+use std::fmt::Write;
+
+/// The largest number of fractional digits of a divisor that is checked via decimal arithmetic.
+const MAX_DECIMAL_SCALE: u32 = 18;
+
+/// A non-negative decimal number represented as `mantissa / 10^scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Decimal {
+    mantissa: u128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Take the shortest decimal representation of `value`, i.e. the one written in JSON.
+    fn from_f64(value: f64) -> Option<Decimal> {
+        let mut buffer = DecimalBuffer::default();
+        write!(buffer, "{}", value.abs()).ok()?;
+        let text = buffer.as_str();
+        let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let mut mantissa = 0_u128;
+        for digit in integer.bytes().chain(fraction.bytes()) {
+            mantissa = mantissa
+                .checked_mul(10)?
+                .checked_add(u128::from(digit.checked_sub(b'0')?))?;
+        }
+        Some(Decimal {
+            mantissa,
+            scale: u32::try_from(fraction.len()).ok()?,
+        })
+    }
+
+    fn from_number(number: &Number) -> Option<Decimal> {
+        if let Some(value) = number.as_u64() {
+            Some(Decimal {
+                mantissa: u128::from(value),
+                scale: 0,
+            })
+        } else if let Some(value) = number.as_i64() {
+            Some(Decimal {
+                mantissa: u128::from(value.unsigned_abs()),
+                scale: 0,
+            })
+        } else {
+            Decimal::from_f64(number.as_f64()?)
+        }
+    }
+
+    /// Whether `self` is an integer multiple of `divisor`, if it can be decided without overflow.
+    fn is_multiple_of(self, divisor: Decimal) -> Option<bool> {
+        if self.scale > divisor.scale {
+            // Has more fractional digits than any multiple of `divisor` could have
+            return Some(false);
+        }
+        let scaled = self
+            .mantissa
+            .checked_mul(10_u128.checked_pow(divisor.scale - self.scale)?)?;
+        Some(scaled % divisor.mantissa == 0)
+    }
+}
+
+/// Whether `number`, which is beyond the `f64` range, is an integer multiple of `divisor`.
+///
+/// Such numbers only exist with `arbitrary-precision` and are checked on their textual
+/// representation. Divisors beyond `u64` after scaling are not supported and never match.
+fn is_large_multiple_of(number: &Number, divisor: Option<Decimal>) -> bool {
+    #[cfg(feature = "arbitrary-precision")]
+    {
+        divisor
+            .and_then(|divisor| is_text_multiple_of(number.as_str(), divisor))
+            .unwrap_or(false)
+    }
+    #[cfg(not(feature = "arbitrary-precision"))]
+    {
+        let _ = (number, divisor);
+        false
+    }
+}
+
+/// Whether the decimal number in `text`, e.g. `1.5e400`, is an integer multiple of `divisor`.
+#[cfg(feature = "arbitrary-precision")]
+fn is_text_multiple_of(text: &str, divisor: Decimal) -> Option<bool> {
+    let modulus = u128::from(u64::try_from(divisor.mantissa).ok()?);
+    let text = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = text.split_once(['e', 'E']).unwrap_or((text, "0"));
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    // `text * 10^scale` equals the digits of the mantissa times `10^shift`
+    let shift = i128::from(exponent.parse::<i64>().ok()?) + i128::from(divisor.scale)
+        - i128::try_from(fraction.len()).ok()?;
+    let digits = integer.bytes().chain(fraction.bytes());
+    let mut remainder = 0_u128;
+    if let Ok(mut shift) = u128::try_from(shift) {
+        for digit in digits {
+            remainder = (remainder * 10 + u128::from(digit - b'0')) % modulus;
+        }
+        // Multiply by `10^shift` modulo `modulus`
+        let mut base = 10 % modulus;
+        while shift > 0 && remainder != 0 {
+            if shift & 1 == 1 {
+                remainder = remainder * base % modulus;
+            }
+            base = base * base % modulus;
+            shift >>= 1;
+        }
+    } else {
+        // Digits that move after the decimal point have to be zeros
+        let kept = (integer.len() + fraction.len())
+            .saturating_sub(usize::try_from(shift.unsigned_abs()).ok()?);
+        for (idx, digit) in digits.enumerate() {
+            if idx < kept {
+                remainder = (remainder * 10 + u128::from(digit - b'0')) % modulus;
+            } else if digit != b'0' {
+                return Some(false);
+            }
+        }
+    }
+    Some(remainder == 0)
+}
+
+/// Split the decimal number in `text` into its digits without trailing zeros and the power of ten
+/// they are scaled by, e.g. `-1.50e3` into `15` and `2`. The sign is ignored.
+#[cfg(feature = "arbitrary-precision")]
+fn parse_text_decimal(text: &str) -> Option<(BigUint, i128)> {
+    let text = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = text.split_once(['e', 'E']).unwrap_or((text, "0"));
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{integer}{fraction}");
+    let significant = digits.trim_end_matches('0');
+    // Exponents beyond `i64` saturate, the result is not exact for them anyway
+    let exponent = exponent
+        .parse::<i64>()
+        .unwrap_or(if exponent.starts_with('-') {
+            i64::MIN
+        } else {
+            i64::MAX
+        });
+    let exponent = i128::from(exponent) - i128::try_from(fraction.len()).ok()?
+        + i128::try_from(digits.len() - significant.len()).ok()?;
+    if significant.is_empty() {
+        return Some((BigUint::from(0_u8), 0));
+    }
+    Some((BigUint::parse_bytes(significant.as_bytes(), 10)?, exponent))
+}
+
+/// Whether `digits * 10^shift` is divisible by `base^count`, where `base` is 2 or 5.
+///
+/// `digits` has no trailing zeros, so a negative `shift` has to be covered by its own factors.
+#[cfg(feature = "arbitrary-precision")]
+fn has_power_of(digits: &BigUint, base: u32, count: u64, shift: i128) -> bool {
+    let needed = i128::from(count) - shift;
+    if needed <= 0 {
+        return true;
+    }
+    match u32::try_from(needed) {
+        // `base^needed` is larger than `digits` otherwise
+        Ok(needed) if u64::from(needed) <= digits.bits() => {
+            (digits % BigUint::from(base).pow(needed)) == BigUint::from(0_u8)
+        }
+        _ => false,
+    }
+}
+
+/// `multipleOf` with a divisor beyond the `f64` range, e.g. `1e400`.
+///
+/// The divisor is kept as `coprime * 2^twos * 5^fives * 10^exponent`, where `coprime` has no
+/// factors of 2 or 5. An instance `digits * 10^p` is a multiple of it if `coprime` divides
+/// `digits` and the powers of 2 and 5 are covered by `digits * 10^(p - exponent)`.
+#[cfg(feature = "arbitrary-precision")]
+pub(crate) struct MultipleOfTextValidator {
+    coprime: BigUint,
+    twos: u64,
+    fives: u64,
+    exponent: i128,
+    location: Location,
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl MultipleOfTextValidator {
+    #[inline]
+    pub(crate) fn compile<'a>(multiple_of: &Number, location: Location) -> CompilationResult<'a> {
+        // Not zero, as it is beyond the `f64` range
+        let (mut coprime, exponent) =
+            parse_text_decimal(multiple_of.as_str()).expect("JSON numbers are decimal");
+        let twos = coprime.trailing_zeros().unwrap_or(0);
+        coprime >>= twos;
+        let mut fives = 0;
+        let five = BigUint::from(5_u8);
+        while (&coprime % &five) == BigUint::from(0_u8) {
+            coprime /= &five;
+            fives += 1;
+        }
+        Ok(Box::new(MultipleOfTextValidator {
+            coprime,
+            twos,
+            fives,
+            exponent,
+            location,
+        }))
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Validate for MultipleOfTextValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            let Some((digits, exponent)) = parse_text_decimal(item.as_str()) else {
+                return false;
+            };
+            if digits == BigUint::from(0_u8) {
+                return true;
+            }
+            let shift = exponent - self.exponent;
+            (&digits % &self.coprime) == BigUint::from(0_u8)
+                && has_power_of(&digits, 2, self.twos, shift)
+                && has_power_of(&digits, 5, self.fives, shift)
+        } else {
+            true
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if !self.is_valid(instance) {
+            return Err(ValidationError::multiple_of(
+                self.location.clone(),
+                location.into(),
+                instance,
+                f64::INFINITY,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A stack buffer for formatting floats without allocating.
+#[derive(Default)]
+struct DecimalBuffer {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl DecimalBuffer {
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len]).expect("Formatted numbers are ASCII")
+    }
+}
+
+impl Write for DecimalBuffer {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let end = self.len + s.len();
+        if end > self.bytes.len() {
+            return Err(std::fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
 
 pub(crate) struct MultipleOfFloatValidator {
     multiple_of: f64,
+    /// The divisor as a decimal with few fractional digits, e.g. `0.1`. Floating point division
+    /// is inexact for such divisors (`0.3 / 0.1` is not `3`), so they are checked via integers.
+    decimal: Option<Decimal>,
     location: Location,
 }
 
@@ -19,6 +282,8 @@ impl MultipleOfFloatValidator {
     pub(crate) fn compile<'a>(multiple_of: f64, location: Location) -> CompilationResult<'a> {
         Ok(Box::new(MultipleOfFloatValidator {
             multiple_of,
+            decimal: Decimal::from_f64(multiple_of)
+                .filter(|decimal| decimal.mantissa != 0 && decimal.scale <= MAX_DECIMAL_SCALE),
             location,
         }))
     }
@@ -27,7 +292,14 @@ impl MultipleOfFloatValidator {
 impl Validate for MultipleOfFloatValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
-            let item = item.as_f64().expect("Always valid");
+            if let Some(is_multiple) = self.decimal.and_then(|divisor| {
+                Decimal::from_number(item).and_then(|item| item.is_multiple_of(divisor))
+            }) {
+                return is_multiple;
+            }
+            let Some(item) = item.as_f64() else {
+                return is_large_multiple_of(item, self.decimal);
+            };
             let remainder = (item / self.multiple_of) % 1.;
             if remainder.is_nan() {
                 // Involves heap allocations via the underlying `BigUint` type
@@ -64,14 +336,22 @@ impl Validate for MultipleOfFloatValidator {
 
 pub(crate) struct MultipleOfIntegerValidator {
     multiple_of: f64,
+    /// The divisor as an integer, if it is one. Used for exact checks of integer instances that
+    /// can't be represented as `f64` without losing precision.
+    exact: Option<u64>,
     location: Location,
 }
 
 impl MultipleOfIntegerValidator {
     #[inline]
-    pub(crate) fn compile<'a>(multiple_of: f64, location: Location) -> CompilationResult<'a> {
+    pub(crate) fn compile<'a>(
+        multiple_of: f64,
+        exact: Option<u64>,
+        location: Location,
+    ) -> CompilationResult<'a> {
         Ok(Box::new(MultipleOfIntegerValidator {
             multiple_of,
+            exact: exact.filter(|exact| *exact != 0),
             location,
         }))
     }
@@ -80,7 +360,20 @@ impl MultipleOfIntegerValidator {
 impl Validate for MultipleOfIntegerValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
-            let item = item.as_f64().expect("Always valid");
+            if let Some(exact) = self.exact {
+                if let Some(item) = item.as_u64() {
+                    return item % exact == 0;
+                } else if let Some(item) = item.as_i64() {
+                    return i128::from(item) % i128::from(exact) == 0;
+                }
+            }
+            let Some(item) = item.as_f64() else {
+                let divisor = self.exact.map(|exact| Decimal {
+                    mantissa: u128::from(exact),
+                    scale: 0,
+                });
+                return is_large_multiple_of(item, divisor);
+            };
             // As the divisor has its fractional part as zero, then any value with a non-zero
             // fractional part can't be a multiple of this divisor, therefore it is short-circuited
             item.fract() == 0. && (item % self.multiple_of) == 0.
@@ -113,10 +406,19 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     if let Value::Number(multiple_of) = schema {
-        let multiple_of = multiple_of.as_f64().expect("Always valid");
+        let exact = multiple_of.as_u64();
         let location = ctx.location().join("multipleOf");
+        #[cfg(feature = "arbitrary-precision")]
+        if multiple_of.as_f64().is_none() {
+            return Some(MultipleOfTextValidator::compile(multiple_of, location));
+        }
+        let multiple_of = multiple_of.as_f64().expect("Always valid");
         if multiple_of.fract() == 0. {
-            Some(MultipleOfIntegerValidator::compile(multiple_of, location))
+            Some(MultipleOfIntegerValidator::compile(
+                multiple_of,
+                exact,
+                location,
+            ))
         } else {
             Some(MultipleOfFloatValidator::compile(multiple_of, location))
         }
@@ -140,15 +442,46 @@ mod tests {
     #[test_case(&json!({"multipleOf": 1.0}), &json!(4.0))]
     #[test_case(&json!({"multipleOf": 1.5}), &json!(3.0))]
     #[test_case(&json!({"multipleOf": 1.5}), &json!(4.5))]
+    #[test_case(&json!({"multipleOf": 3}), &json!(9_007_199_254_740_993_u64))]
+    #[test_case(&json!({"multipleOf": 3}), &json!(-9_007_199_254_740_993_i64))]
+    #[test_case(&json!({"multipleOf": 2}), &json!(u64::MAX - 1))]
+    #[test_case(&json!({"multipleOf": 2}), &json!(i64::MIN))]
     fn multiple_of_is_valid(schema: &Value, instance: &Value) {
         tests_util::is_valid(schema, instance)
     }
 
     #[test_case(&json!({"multipleOf": 1.0}), &json!(4.5))]
+    #[test_case(&json!({"multipleOf": 2}), &json!(9_007_199_254_740_993_u64))]
+    #[test_case(&json!({"multipleOf": 2}), &json!(-9_007_199_254_740_993_i64))]
+    #[test_case(&json!({"multipleOf": 2}), &json!(u64::MAX))]
+    #[test_case(&json!({"multipleOf": 2}), &json!(i64::MIN + 1))]
     fn multiple_of_is_not_valid(schema: &Value, instance: &Value) {
         tests_util::is_not_valid(schema, instance)
     }
 
+    #[test_case(0.1, &json!(0.3))]
+    #[test_case(0.1, &json!(-0.3))]
+    #[test_case(0.1, &json!(3))]
+    #[test_case(0.01, &json!(0.07))]
+    #[test_case(0.01, &json!(19.99))]
+    #[test_case(0.0001, &json!(0.0075))]
+    #[test_case(2.5, &json!(7.5))]
+    #[test_case(0.1, &json!(u64::MAX))]
+    #[test_case(0.1, &json!(1e300))]
+    fn decimal_multiple(multiple_of: f64, instance: &Value) {
+        tests_util::is_valid(&json!({"multipleOf": multiple_of}), instance)
+    }
+
+    #[test_case(0.1, &json!(0.35))]
+    #[test_case(0.1, &json!(0.30000000000000004))]
+    #[test_case(0.01, &json!(0.075))]
+    #[test_case(0.01, &json!(19.999))]
+    #[test_case(2.5, &json!(8))]
+    #[test_case(0.3, &json!(0.5))]
+    fn decimal_not_multiple(multiple_of: f64, instance: &Value) {
+        tests_util::is_not_valid(&json!({"multipleOf": multiple_of}), instance)
+    }
+
     #[test_case(&json!({"multipleOf": 2}), &json!(3), "/multipleOf")]
     #[test_case(&json!({"multipleOf": 1.5}), &json!(5), "/multipleOf")]
     fn location(schema: &Value, instance: &Value, expected: &str) {
@@ -1,6 +1,10 @@
 use crate::{
-    compiler, error::ValidationError, keywords::CompilationResult, node::SchemaNode,
-    paths::LazyLocation, validator::Validate,
+    compiler,
+    error::ValidationError,
+    keywords::CompilationResult,
+    node::SchemaNode,
+    paths::LazyLocation,
+    validator::{TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
@@ -26,6 +30,15 @@ impl Validate for NotValidator {
         !self.node.is_valid(instance)
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        !self.node.trace(instance, location, callback)
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -56,7 +69,8 @@ pub(crate) fn compile<'a>(
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
-    use serde_json::json;
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn location() {
@@ -66,4 +80,17 @@ mod tests {
             "/not",
         )
     }
+
+    #[test_case(&json!({"not": {"type": "string"}}), &json!(5), true)]
+    #[test_case(&json!({"not": {"type": "string"}}), &json!("foo"), false)]
+    #[test_case(&json!({"allOf": [{"not": {"maximum": 5}}]}), &json!(6), true)]
+    #[test_case(&json!({"allOf": [{"not": {"maximum": 5}}]}), &json!(5), false)]
+    #[test_case(&json!({"allOf": [{"not": {"maximum": 5}}, {"type": "integer"}]}), &json!(6.5), false)]
+    #[test_case(&json!({"not": {"not": {"type": "string"}}}), &json!("foo"), true)]
+    fn is_valid(schema: &Value, instance: &Value, expected: bool) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+        assert_eq!(validator.apply(instance).basic().is_valid(), expected);
+    }
 }
@@ -1,18 +1,25 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{
+        helpers::{declared_types, first_errors, matches_type},
+        CompilationResult,
+    },
     node::SchemaNode,
     output::BasicOutput,
     paths::{LazyLocation, Location},
-    primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    validator::{PartialApplication, TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
+use std::cmp::Reverse;
 
 pub(crate) struct OneOfValidator {
     schemas: Vec<SchemaNode>,
+    /// Types declared by each subschema, used to skip subschemas that can't match.
+    types: Vec<Option<PrimitiveTypesBitMap>>,
     location: Location,
+    report_branch_errors: bool,
 }
 
 impl OneOfValidator {
@@ -20,15 +27,28 @@ impl OneOfValidator {
     pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
         if let Value::Array(items) = schema {
             let ctx = ctx.new_at_location("oneOf");
+            if items.is_empty() {
+                // Not caught by meta-schema validation if the subschema is reachable only via `$ref`
+                return Err(ValidationError::min_items(
+                    Location::new(),
+                    ctx.location().clone(),
+                    schema,
+                    1,
+                ));
+            }
             let mut schemas = Vec::with_capacity(items.len());
+            let mut types = Vec::with_capacity(items.len());
             for (idx, item) in items.iter().enumerate() {
                 let ctx = ctx.new_at_location(idx);
                 let node = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
-                schemas.push(node)
+                schemas.push(node);
+                types.push(declared_types(&ctx, item));
             }
             Ok(Box::new(OneOfValidator {
                 schemas,
+                types,
                 location: ctx.location().clone(),
+                report_branch_errors: ctx.config().reports_branch_errors(),
             }))
         } else {
             Err(ValidationError::single_type_error(
@@ -42,8 +62,8 @@ impl OneOfValidator {
 
     fn get_first_valid(&self, instance: &Value) -> Option<usize> {
         let mut first_valid_idx = None;
-        for (idx, node) in self.schemas.iter().enumerate() {
-            if node.is_valid(instance) {
+        for (idx, (node, types)) in self.schemas.iter().zip(&self.types).enumerate() {
+            if matches_type(*types, instance) && node.is_valid(instance) {
                 first_valid_idx = Some(idx);
                 break;
             }
@@ -58,8 +78,9 @@ impl OneOfValidator {
         // in `get_first_valid` we enumerate from `0`, and on the last index will be `usize::MAX - 1`
         self.schemas
             .iter()
+            .zip(&self.types)
             .skip(idx + 1)
-            .any(|n| n.is_valid(instance))
+            .any(|(node, types)| matches_type(*types, instance) && node.is_valid(instance))
     }
 }
 
@@ -68,6 +89,19 @@ impl Validate for OneOfValidator {
         let first_valid_idx = self.get_first_valid(instance);
         first_valid_idx.map_or(false, |idx| !self.are_others_valid(instance, idx))
     }
+
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        self.schemas
+            .iter()
+            .filter(|node| node.trace(instance, location, callback))
+            .count()
+            == 1
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -84,10 +118,16 @@ impl Validate for OneOfValidator {
             }
             Ok(())
         } else {
+            let context = if self.report_branch_errors {
+                first_errors(&self.schemas, instance, location)
+            } else {
+                Vec::new()
+            };
             Err(ValidationError::one_of_not_valid(
                 self.location.clone(),
                 location.into(),
                 instance,
+                context,
             ))
         }
     }
@@ -106,9 +146,29 @@ impl Validate for OneOfValidator {
         } else if successes.len() > 1 {
             PartialApplication::invalid_empty(vec!["more than one subschema succeeded".into()])
         } else if !failures.is_empty() {
+            // The closest branch goes first, so its errors lead the output
+            failures.sort_by_cached_key(distance);
             failures.into_iter().sum::<BasicOutput<'_>>().into()
         } else {
-            unreachable!("compilation should fail for oneOf with no subschemas")
+            unreachable!("Compilation fails for `oneOf` with no subschemas")
+        }
+    }
+}
+
+/// How far a failed subschema is from matching the instance.
+///
+/// Fewer errors are closer. On ties, errors deeper in the instance are closer, as they come from
+/// a branch whose outer keywords, e.g. `type` or a discriminating `const`, already matched.
+fn distance(output: &BasicOutput<'_>) -> (usize, Reverse<usize>) {
+    match output {
+        BasicOutput::Valid(_) => (0, Reverse(0)),
+        BasicOutput::Invalid(errors) => {
+            let depth = errors
+                .iter()
+                .map(|error| error.instance_location().as_str().matches('/').count())
+                .max()
+                .unwrap_or(0);
+            (errors.len(), Reverse(depth))
         }
     }
 }
@@ -124,7 +184,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -133,4 +193,151 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    fn branch_errors(schema: &Value, instance: &Value, report: bool) -> Vec<(String, String)> {
+        let validator = crate::options()
+            .should_report_branch_errors(report)
+            .build(schema)
+            .expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        let ValidationErrorKind::OneOfNotValid { context } = error.kind else {
+            panic!("Unexpected error kind: {:?}", error.kind)
+        };
+        context
+            .iter()
+            .map(|error| (error.schema_path.as_str().to_string(), error.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_first_error_of_each_branch() {
+        let schema = json!({"oneOf": [
+            {"type": "object", "required": ["name"]},
+            {"type": "array", "minItems": 2}
+        ]});
+        assert_eq!(
+            branch_errors(&schema, &json!({"age": 1}), true),
+            [
+                (
+                    "/oneOf/0/required".to_string(),
+                    r#""name" is a required property"#.to_string()
+                ),
+                (
+                    "/oneOf/1/type".to_string(),
+                    r#"{"age":1} is not of type "array""#.to_string()
+                )
+            ]
+        );
+        assert_eq!(
+            branch_errors(&schema, &json!([1]), true),
+            [
+                (
+                    "/oneOf/0/type".to_string(),
+                    r#"[1] is not of type "object""#.to_string()
+                ),
+                (
+                    "/oneOf/1/minItems".to_string(),
+                    "[1] has less than 2 items".to_string()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_errors_are_disabled_by_default() {
+        let schema = json!({"oneOf": [{"type": "string"}, {"minimum": 10}]});
+        assert!(branch_errors(&schema, &json!(5), false).is_empty());
+    }
+    #[test_case(&json!({"oneOf": [{"type": "integer", "minimum": 5}, {"type": "string"}]}), &json!(7), true; "integer branch")]
+    #[test_case(&json!({"oneOf": [{"type": "integer", "minimum": 5}, {"type": "string"}]}), &json!(7.0), true; "integer valued float")]
+    #[test_case(&json!({"oneOf": [{"type": "integer", "minimum": 5}, {"type": "string"}]}), &json!(3), false; "failing integer branch")]
+    #[test_case(&json!({"oneOf": [{"type": ["null", "number"]}, {"type": "string"}]}), &json!(null), true; "type array")]
+    #[test_case(&json!({"oneOf": [{"type": "object"}, {"minLength": 2}]}), &json!("ab"), true; "branch without type")]
+    #[test_case(&json!({"oneOf": [{"type": "object"}, {"type": "array"}]}), &json!("ab"), false; "no matching branch")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "definitions": {"s": {"type": "string"}}, "oneOf": [{"$ref": "#/definitions/s", "type": "object"}]}), &json!("a"), true; "type next to ref is ignored")]
+    fn type_discriminated_branches(schema: &Value, instance: &Value, expected: bool) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+    }
+
+    #[test]
+    fn overridden_type_keyword() {
+        struct AnyType;
+        impl crate::Keyword for AnyType {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &crate::paths::LazyLocation,
+            ) -> Result<(), crate::ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                true
+            }
+        }
+        // The custom `type` keyword accepts anything, so the first subschema must not be skipped
+        let validator = crate::options()
+            .with_keyword("type", |_, _, _| Ok(Box::new(AnyType)))
+            .build(&json!({"oneOf": [{"type": "object"}, {"minLength": 5}]}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("a")));
+    }
+
+    fn output_errors(schema: &Value, instance: &Value) -> Vec<String> {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let crate::BasicOutput::Invalid(errors) = validator.apply(instance).basic() else {
+            panic!("Should fail")
+        };
+        errors
+            .iter()
+            .map(|error| error.keyword_location().as_str().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn closest_branch_comes_first() {
+        let schema = json!({"oneOf": [
+            {
+                "properties": {"kind": {"const": "cat"}, "lives": {"type": "integer"}},
+                "required": ["kind", "lives"]
+            },
+            {
+                "properties": {"kind": {"const": "dog"}, "name": {"type": "string"}},
+                "required": ["kind", "name"]
+            },
+            {"type": "array"}
+        ]});
+        // A typo in the value of `name`
+        assert_eq!(
+            output_errors(&schema, &json!({"kind": "dog", "name": 5})),
+            [
+                "/oneOf/1/properties/name/type",
+                "/oneOf/2/type",
+                "/oneOf/0/properties/kind/const",
+                "/oneOf/0/required"
+            ]
+        );
+        // A typo in the name of `lives`
+        assert_eq!(
+            output_errors(&schema, &json!({"kind": "cat", "livs": 9})),
+            [
+                "/oneOf/0/required",
+                "/oneOf/2/type",
+                "/oneOf/1/properties/kind/const",
+                "/oneOf/1/required"
+            ]
+        );
+    }
+
+    #[test]
+    fn equally_close_branches_keep_their_order() {
+        assert_eq!(
+            output_errors(
+                &json!({"oneOf": [{"type": "object"}, {"maxLength": 4}]}),
+                &json!("some string")
+            ),
+            ["/oneOf/0/type", "/oneOf/1/maxLength"]
+        );
+    }
 }
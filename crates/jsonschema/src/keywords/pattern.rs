@@ -1,5 +1,5 @@
 use crate::{
-    compiler, ecma,
+    compiler,
     error::ValidationError,
     keywords::CompilationResult,
     paths::{LazyLocation, Location},
@@ -71,24 +71,28 @@ impl PatternValidator {
     ) -> CompilationResult<'a> {
         match pattern {
             Value::String(item) => {
+                let source = if ctx.config().anchors_patterns() {
+                    format!("^(?:{item})$")
+                } else {
+                    item.clone()
+                };
+                // Patterns compiled with size limits must not be shared with other validators
+                let cacheable = !ctx.has_regex_limits();
                 let mut cache = REGEX_CACHE.lock().expect("Lock is poisoned");
-                let pattern = if let Some(regex) = cache.get(item) {
-                    regex.clone()
+                let cached = if cacheable {
+                    cache.get(&source).cloned()
+                } else {
+                    None
+                };
+                let pattern = if let Some(regex) = cached {
+                    regex
                 } else {
-                    let regex = match ecma::to_rust_regex(item)
-                        .map(|pattern| fancy_regex::Regex::new(&pattern))
-                    {
-                        Ok(Ok(r)) => r,
-                        _ => {
-                            return Err(ValidationError::format(
-                                Location::new(),
-                                ctx.location().clone(),
-                                pattern,
-                                "regex",
-                            ))
-                        }
-                    };
-                    cache.insert(item.clone(), regex.clone());
+                    let regex = ctx.compile_regex(&source).map_err(|error| {
+                        error.into_validation_error(ctx.location().join("pattern"), pattern)
+                    })?;
+                    if cacheable {
+                        cache.insert(source, regex.clone());
+                    }
                     regex
                 };
                 Ok(Box::new(PatternValidator {
@@ -158,7 +162,7 @@ pub(crate) fn compile<'a>(
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
-    use serde_json::json;
+    use serde_json::{json, Value};
     use test_case::test_case;
 
     #[test_case("^(?!eo:)", "eo:bands", false)]
@@ -170,8 +174,89 @@ mod tests {
         assert_eq!(validator.is_valid(&text), is_matching)
     }
 
+    #[test_case("a", "bab", true; "unanchored")]
+    #[test_case("^a", "bab", false; "explicit anchor")]
+    #[test_case("a|b", "cbc", true; "alternation")]
+    fn match_anywhere(pattern: &str, text: &str, expected: bool) {
+        let validator = crate::validator_for(&json!({"pattern": pattern})).unwrap();
+        assert_eq!(validator.is_valid(&json!(text)), expected);
+    }
+
+    #[test_case("a", "a", true; "whole string")]
+    #[test_case("a", "bab", false; "substring")]
+    #[test_case("a|b", "b", true; "alternation")]
+    #[test_case("a|b", "ab", false; "alternation substring")]
+    #[test_case("^a+$", "aaa", true; "already anchored")]
+    #[test_case("a", "a\n", false; "trailing newline")]
+    fn anchored(pattern: &str, text: &str, expected: bool) {
+        let validator = crate::options()
+            .should_anchor_patterns(true)
+            .build(&json!({"pattern": pattern}))
+            .unwrap();
+        assert_eq!(validator.is_valid(&json!(text)), expected);
+    }
+
+    #[test]
+    fn anchored_error_reports_original_pattern() {
+        let validator = crate::options()
+            .should_anchor_patterns(true)
+            .build(&json!({"pattern": "a"}))
+            .unwrap();
+        let instance = json!("bab");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.to_string(), r#""bab" does not match "a""#);
+    }
+
     #[test]
     fn location() {
         tests_util::assert_schema_location(&json!({"pattern": "^f"}), &json!("b"), "/pattern")
     }
+
+    #[test_case(&json!({"pattern": "a{1000}b{1000}"}), "/pattern")]
+    #[test_case(&json!({"properties": {"a": {"pattern": "(x|y){5000}"}}}), "/properties/a/pattern")]
+    #[test_case(&json!({"patternProperties": {"(x|y){5000}": {}}}), "/patternProperties/(x|y){5000}")]
+    #[test_case(&json!({"properties": {"a": {}}, "patternProperties": {"(x|y){5000}": {}}, "additionalProperties": false}), "/patternProperties/(x|y){5000}"; "with additionalProperties")]
+    #[test_case(&json!({"patternProperties": {"(x|y){5000}": {}}, "unevaluatedProperties": false}), "/patternProperties/(x|y){5000}"; "with unevaluatedProperties")]
+    fn size_limit_exceeded(schema: &Value, expected: &str) {
+        let error = crate::options()
+            .with_regex_size_limit(1000)
+            .build(schema)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Regular expression exceeds the size limit of 1000 bytes"
+        );
+        assert_eq!(error.instance_path.as_str(), expected);
+    }
+
+    #[test]
+    fn size_limit_not_exceeded() {
+        let validator = crate::options()
+            .with_regex_size_limit(100_000)
+            .with_regex_dfa_size_limit(100_000)
+            .build(&json!({"pattern": "^a{3}$"}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("aaa")));
+        assert!(!validator.is_valid(&json!("aa")));
+    }
+
+    #[test]
+    fn size_limit_bypasses_cache() {
+        // Compiled without a limit first, so the cached regex must not be reused
+        let schema = json!({"pattern": "c{1000}d{1000}"});
+        crate::validator_for(&schema).expect("Invalid schema");
+        crate::options()
+            .with_regex_size_limit(1000)
+            .build(&schema)
+            .expect_err("Should fail");
+    }
+
+    #[test_case(&json!({"pattern": "("}), "/pattern")]
+    #[test_case(&json!({"properties": {"a": {"pattern": "[a-"}}}), "/properties/a/pattern")]
+    #[test_case(&json!({"$defs": {"a": {"properties": {"b": {"$ref": "#/$defs/a"}}, "pattern": "("}}, "$ref": "#/$defs/a"}), "/$ref/pattern")]
+    fn invalid_pattern(schema: &Value, expected: &str) {
+        let error = crate::validator_for(schema).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), expected);
+        assert!(error.to_string().ends_with(r#" is not a "regex""#));
+    }
 }
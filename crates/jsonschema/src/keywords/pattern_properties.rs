@@ -1,12 +1,12 @@
 use crate::{
-    compiler, ecma,
+    compiler,
     error::{no_error, ErrorIterator, ValidationError},
-    keywords::CompilationResult,
+    keywords::{helpers::invalid_pattern_property, CompilationResult},
     node::SchemaNode,
     output::BasicOutput,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, TraceRecord, Validate},
 };
 use fancy_regex::Regex;
 use serde_json::{Map, Value};
@@ -26,14 +26,13 @@ impl PatternPropertiesValidator {
         for (pattern, subschema) in map {
             let pctx = ctx.new_at_location(pattern.as_str());
             patterns.push((
-                match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
-                    Ok(Ok(r)) => r,
-                    _ => {
-                        return Err(ValidationError::format(
-                            Location::new(),
-                            ctx.location().clone(),
-                            subschema,
-                            "regex",
+                match ctx.compile_regex(pattern) {
+                    Ok(r) => r,
+                    Err(error) => {
+                        return Err(invalid_pattern_property(
+                            pctx.location().clone(),
+                            pattern,
+                            error,
                         ))
                     }
                 },
@@ -48,13 +47,13 @@ impl Validate for PatternPropertiesValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
-            let errors: Vec<_> = self
-                .patterns
+            let errors: Vec<_> = item
                 .iter()
-                .flat_map(move |(re, node)| {
-                    item.iter()
-                        .filter(move |(key, _)| re.is_match(key).unwrap_or(false))
-                        .flat_map(move |(key, value)| {
+                .flat_map(move |(key, value)| {
+                    self.patterns
+                        .iter()
+                        .filter(move |(re, _)| re.is_match(key).unwrap_or(false))
+                        .flat_map(move |(_, node)| {
                             let location = location.push(key.as_str());
                             node.iter_errors(value, &location)
                         })
@@ -78,14 +77,33 @@ impl Validate for PatternPropertiesValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Object(item) = instance {
+            for (key, value) in item {
+                for (re, node) in &self.patterns {
+                    if re.is_match(key).unwrap_or(false) {
+                        is_valid &= node.trace(value, &location.push(key.as_str()), callback);
+                    }
+                }
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for (re, node) in self.patterns.iter() {
-                for (key, value) in item.iter() {
+            for (key, value) in item.iter() {
+                for (re, node) in self.patterns.iter() {
                     if re.is_match(key).unwrap_or(false) {
                         node.validate(value, &location.push(key))?;
                     }
@@ -133,14 +151,13 @@ impl SingleValuePatternPropertiesValidator {
         let pctx = kctx.new_at_location(pattern);
         Ok(Box::new(SingleValuePatternPropertiesValidator {
             pattern: {
-                match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
-                    Ok(Ok(r)) => r,
-                    _ => {
-                        return Err(ValidationError::format(
-                            Location::new(),
-                            kctx.location().clone(),
-                            schema,
-                            "regex",
+                match ctx.compile_regex(pattern) {
+                    Ok(r) => r,
+                    Err(error) => {
+                        return Err(invalid_pattern_property(
+                            pctx.location().clone(),
+                            pattern,
+                            error,
                         ))
                     }
                 }
@@ -178,6 +195,25 @@ impl Validate for SingleValuePatternPropertiesValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Object(item) = instance {
+            for (key, value) in item {
+                if self.pattern.is_match(key).unwrap_or(false) {
+                    is_valid &= self
+                        .node
+                        .trace(value, &location.push(key.as_str()), callback);
+                }
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -255,4 +291,15 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"patternProperties": {"(": {}}}))]
+    #[test_case(&json!({"patternProperties": {"^a": {}, "(": {}}}))]
+    #[test_case(&json!({"patternProperties": {"(": {}}, "additionalProperties": false}))]
+    #[test_case(&json!({"patternProperties": {"(": {}}, "properties": {"a": {}}, "additionalProperties": false}))]
+    #[test_case(&json!({"patternProperties": {"(": {}}, "unevaluatedProperties": false}))]
+    fn invalid_pattern(schema: &Value) {
+        let error = crate::validator_for(schema).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/patternProperties/(");
+        assert_eq!(error.to_string(), r#""(" is not a "regex""#);
+    }
 }
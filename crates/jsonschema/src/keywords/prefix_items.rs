@@ -4,7 +4,7 @@ use crate::{
     node::SchemaNode,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{Descent, PartialApplication, TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
@@ -59,6 +59,21 @@ impl Validate for PrefixItemsValidator {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Array(items) = instance {
+            for (idx, (node, item)) in self.schemas.iter().zip(items.iter()).enumerate() {
+                is_valid &= node.trace(item, &location.push(idx), callback);
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -100,6 +115,14 @@ impl Validate for PrefixItemsValidator {
         }
         PartialApplication::valid_empty()
     }
+
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        let node = match (instance, segment.parse::<usize>()) {
+            (Value::Array(_), Ok(idx)) => self.schemas.get(idx),
+            _ => None,
+        };
+        Some(Descent::Child(node))
+    }
 }
 
 #[inline]
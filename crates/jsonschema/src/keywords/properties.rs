@@ -2,57 +2,61 @@ use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
-    node::SchemaNode,
     output::BasicOutput,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    properties::{
+        are_properties_valid, compile_big_map, compile_dynamic_prop_map_validator,
+        compile_small_map, BigValidatorsMap, PropertiesValidatorsMap, SmallValidatorsMap,
+    },
+    validator::{Descent, PartialApplication, TraceRecord, Validate},
 };
 use serde_json::{Map, Value};
 
-pub(crate) struct PropertiesValidator {
-    pub(crate) properties: Vec<(String, SchemaNode)>,
+pub(crate) struct PropertiesValidator<M: PropertiesValidatorsMap> {
+    properties: M,
 }
 
-impl PropertiesValidator {
+impl PropertiesValidator<SmallValidatorsMap> {
     #[inline]
-    pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
-        match schema {
-            Value::Object(map) => {
-                let ctx = ctx.new_at_location("properties");
-                let mut properties = Vec::with_capacity(map.len());
-                for (key, subschema) in map {
-                    let ctx = ctx.new_at_location(key.as_str());
-                    properties.push((
-                        key.clone(),
-                        compiler::compile(&ctx, ctx.as_resource_ref(subschema))?,
-                    ));
-                }
-                Ok(Box::new(PropertiesValidator { properties }))
-            }
-            _ => Err(ValidationError::single_type_error(
-                Location::new(),
-                ctx.location().clone(),
-                schema,
-                PrimitiveType::Object,
-            )),
-        }
+    pub(crate) fn compile<'a>(
+        map: &'a Map<String, Value>,
+        ctx: &compiler::Context,
+    ) -> CompilationResult<'a> {
+        Ok(Box::new(PropertiesValidator {
+            properties: compile_small_map(ctx, map)?,
+        }))
     }
 }
 
-impl Validate for PropertiesValidator {
+impl PropertiesValidator<BigValidatorsMap> {
+    #[inline]
+    pub(crate) fn compile<'a>(
+        map: &'a Map<String, Value>,
+        ctx: &compiler::Context,
+    ) -> CompilationResult<'a> {
+        Ok(Box::new(PropertiesValidator {
+            properties: compile_big_map(ctx, map)?,
+        }))
+    }
+}
+
+// Properties are evaluated in the instance order, like in the validators combined with
+// `additionalProperties`
+impl<M: PropertiesValidatorsMap> Validate for PropertiesValidator<M> {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
-            let errors: Vec<_> = self
-                .properties
+            let errors: Vec<_> = item
                 .iter()
-                .flat_map(move |(name, node)| {
-                    let option = item.get(name);
-                    option.into_iter().flat_map(move |item| {
-                        let instance_path = location.push(name.as_str());
-                        node.iter_errors(item, &instance_path)
-                    })
+                .filter_map(|(name, value)| {
+                    self.properties
+                        .get_validator(name)
+                        .map(|node| (name, value, node))
+                })
+                .flat_map(move |(name, value, node)| {
+                    let instance_path = location.push(name.as_str());
+                    node.iter_errors(value, &instance_path)
                 })
                 .collect();
             Box::new(errors.into_iter())
@@ -63,24 +67,38 @@ impl Validate for PropertiesValidator {
 
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
-            self.properties.iter().all(move |(name, node)| {
-                let option = item.get(name);
-                option.into_iter().all(move |item| node.is_valid(item))
-            })
+            are_properties_valid(&self.properties, item, |_| true)
         } else {
             true
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let mut is_valid = true;
+        if let Value::Object(item) = instance {
+            for (name, value) in item {
+                if let Some(node) = self.properties.get_validator(name) {
+                    is_valid &= node.trace(value, &location.push(name.as_str()), callback);
+                }
+            }
+        }
+        is_valid
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for (name, node) in self.properties.iter() {
-                if let Some(item) = item.get(name) {
-                    node.validate(item, &location.push(name))?;
+            for (name, value) in item {
+                if let Some(node) = self.properties.get_validator(name) {
+                    node.validate(value, &location.push(name.as_str()))?;
                 }
             }
         }
@@ -91,8 +109,8 @@ impl Validate for PropertiesValidator {
         if let Value::Object(props) = instance {
             let mut result = BasicOutput::default();
             let mut matched_props = Vec::with_capacity(props.len());
-            for (prop_name, node) in &self.properties {
-                if let Some(prop) = props.get(prop_name) {
+            for (prop_name, prop) in props {
+                if let Some(node) = self.properties.get_validator(prop_name) {
                     let path = location.push(prop_name.as_str());
                     matched_props.push(prop_name.clone());
                     result += node.apply_rooted(prop, &path);
@@ -105,6 +123,13 @@ impl Validate for PropertiesValidator {
             PartialApplication::valid_empty()
         }
     }
+
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        let node = instance
+            .is_object()
+            .then(|| self.properties.get_validator(segment));
+        Some(Descent::Child(node.flatten()))
+    }
 }
 
 #[inline]
@@ -116,14 +141,25 @@ pub(crate) fn compile<'a>(
     match parent.get("additionalProperties") {
         // This type of `additionalProperties` validator handles `properties` logic
         Some(Value::Bool(false)) | Some(Value::Object(_)) => None,
-        _ => Some(PropertiesValidator::compile(ctx, schema)),
+        _ => match schema {
+            Value::Object(_) => {
+                compile_dynamic_prop_map_validator!(PropertiesValidator, schema, ctx)
+            }
+            _ => Some(Err(ValidationError::single_type_error(
+                Location::new(),
+                ctx.location().clone(),
+                schema,
+                PrimitiveType::Object,
+            ))),
+        },
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
-    use serde_json::json;
+    use serde_json::{json, Map, Value};
+    use test_case::test_case;
 
     #[test]
     fn location() {
@@ -133,4 +169,29 @@ mod tests {
             "/properties/foo/properties/bar/required",
         )
     }
+
+    #[test_case(2; "small map")]
+    #[test_case(50; "big map")]
+    fn validate_reports_first_error(size: usize) {
+        let properties: Map<String, Value> = (0..size)
+            .map(|idx| (format!("p{idx}"), json!({"type": "string"})))
+            .collect();
+        let schema = json!({"properties": properties});
+        // With `preserve_order`, the instance order differs from the schema order
+        let instance: Map<String, Value> = (0..size)
+            .rev()
+            .map(|idx| (format!("p{idx}"), json!(idx)))
+            .collect();
+        let instance = Value::Object(instance);
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let first = validator
+            .iter_errors(&instance)
+            .next()
+            .expect("Instance is invalid");
+        let error = validator
+            .validate(&instance)
+            .expect_err("Instance is invalid");
+        assert_eq!(error.instance_path, first.instance_path);
+        assert_eq!(validator.iter_errors(&instance).count(), size);
+    }
 }
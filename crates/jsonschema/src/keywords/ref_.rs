@@ -1,13 +1,13 @@
-use std::{rc::Rc, sync::Arc};
+use std::{cell::Cell, rc::Rc, sync::Arc};
 
 use crate::{
     compiler,
-    error::ErrorIterator,
+    error::{error, ErrorIterator},
     keywords::CompilationResult,
     node::SchemaNode,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{Descent, PartialApplication, TraceRecord, Validate},
     ValidationError, ValidationOptions,
 };
 use once_cell::sync::OnceCell;
@@ -15,7 +15,14 @@ use referencing::{Draft, List, Registry, Resource, Uri, VocabularySet};
 use serde_json::{Map, Value};
 
 pub(crate) enum RefValidator {
-    Default { inner: SchemaNode },
+    Default {
+        inner: SchemaNode,
+    },
+    /// A reference whose target contains lazily compiled references, which may lead back to it.
+    Recursive {
+        inner: SchemaNode,
+        limit: RecursionLimit,
+    },
     Lazy(LazyRefValidator),
 }
 
@@ -43,12 +50,14 @@ impl RefValidator {
                         }
                     }
                 }
+                ctx.mark_lazy_reference();
                 Ok(Box::new(RefValidator::Lazy(LazyRefValidator {
                     resource,
                     config: Arc::clone(ctx.config()),
                     registry: Arc::clone(&ctx.registry),
                     base_uri,
                     scopes,
+                    limit: RecursionLimit::new(ctx.config(), location.clone()),
                     location,
                     vocabularies: ctx.vocabularies().clone(),
                     draft: ctx.draft(),
@@ -59,13 +68,17 @@ impl RefValidator {
                     Ok(resolved) => resolved.into_inner(),
                     Err(error) => return Some(Err(error.into())),
                 };
+                if !matches!(contents, Value::Object(_) | Value::Bool(_)) {
+                    return Some(Err(invalid_target(ctx, reference)));
+                }
                 let vocabularies = ctx.registry.find_vocabularies(draft, contents);
                 let resource_ref = draft.create_resource_ref(contents);
+                let lazy_references = ctx.lazy_references();
                 let ctx = ctx.with_resolver_and_draft(
                     resolver,
                     resource_ref.draft(),
                     vocabularies,
-                    location,
+                    location.clone(),
                 );
                 let inner = match compiler::compile_with(&ctx, resource_ref)
                     .map_err(|err| err.to_owned())
@@ -73,12 +86,121 @@ impl RefValidator {
                     Ok(inner) => inner,
                     Err(error) => return Some(Err(error)),
                 };
-                Ok(Box::new(RefValidator::Default { inner }))
+                let limit = RecursionLimit::new(ctx.config(), location);
+                if ctx.lazy_references() == lazy_references || limit.is_unlimited() {
+                    Ok(Box::new(RefValidator::Default { inner }))
+                } else {
+                    Ok(Box::new(RefValidator::Recursive { inner, limit }))
+                }
             },
         )
     }
 }
 
+thread_local! {
+    /// Number of guarded references being evaluated on this thread.
+    static RECURSION_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Marks a guarded reference as being evaluated until dropped.
+struct RecursionGuard {
+    is_tracked: bool,
+}
+
+impl RecursionGuard {
+    /// Enter a guarded reference, unless `limit` of them are already being evaluated.
+    fn enter(limit: usize) -> Option<RecursionGuard> {
+        if limit == usize::MAX {
+            return Some(RecursionGuard { is_tracked: false });
+        }
+        RECURSION_DEPTH.with(|depth| {
+            let current = depth.get();
+            (current < limit).then(|| {
+                depth.set(current + 1);
+                RecursionGuard { is_tracked: true }
+            })
+        })
+    }
+}
+
+impl Drop for RecursionGuard {
+    fn drop(&mut self) {
+        if self.is_tracked {
+            RECURSION_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+}
+
+/// Limits how many references that may recurse are evaluated within each other.
+///
+/// Validation can only recurse without bound through lazily compiled references, so guarding
+/// them and the references containing them protects against stack overflows on deeply nested
+/// instances, see `ValidationOptions::with_max_instance_depth`.
+pub(crate) struct RecursionLimit {
+    location: Location,
+    limit: usize,
+}
+
+impl RecursionLimit {
+    fn new(config: &ValidationOptions, location: Location) -> RecursionLimit {
+        RecursionLimit {
+            location,
+            limit: config.max_instance_depth(),
+        }
+    }
+    fn enter(&self) -> Option<RecursionGuard> {
+        RecursionGuard::enter(self.limit)
+    }
+    fn is_unlimited(&self) -> bool {
+        self.limit == usize::MAX
+    }
+    fn too_deep<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ValidationError<'i> {
+        ValidationError::max_depth(self.location.clone(), location.into(), instance, self.limit)
+    }
+    fn is_valid(&self, node: &SchemaNode, instance: &Value) -> bool {
+        let Some(_guard) = self.enter() else {
+            return false;
+        };
+        node.is_valid(instance)
+    }
+    fn iter_errors<'i>(
+        &self,
+        node: &SchemaNode,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        let Some(_guard) = self.enter() else {
+            return error(self.too_deep(instance, location));
+        };
+        node.iter_errors(instance, location)
+    }
+    fn apply<'a>(
+        &self,
+        node: &'a SchemaNode,
+        instance: &Value,
+        location: &LazyLocation,
+    ) -> PartialApplication<'a> {
+        let Some(_guard) = self.enter() else {
+            return PartialApplication::invalid_empty(vec![self
+                .too_deep(instance, location)
+                .into()]);
+        };
+        node.apply(instance, location)
+    }
+    fn trace(
+        &self,
+        node: &SchemaNode,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let Some(_guard) = self.enter() else {
+            return false;
+        };
+        node.trace(instance, location, callback)
+    }
+}
+
 /// Lazily evaluated validator used for recursive references.
 ///
 /// The validator tree nodes can't be arbitrary looked up in the current
@@ -95,6 +217,7 @@ pub(crate) struct LazyRefValidator {
     vocabularies: VocabularySet,
     location: Location,
     draft: Draft,
+    limit: RecursionLimit,
     inner: OnceCell<SchemaNode>,
 }
 
@@ -109,6 +232,8 @@ impl LazyRefValidator {
         if let Some(id) = resource.id() {
             base_uri = resolver.resolve_against(&base_uri.borrow(), id)?;
         };
+        let location = ctx.location().join("$recursiveRef");
+        ctx.mark_lazy_reference();
         Ok(Box::new(LazyRefValidator {
             resource,
             config: Arc::clone(ctx.config()),
@@ -116,7 +241,8 @@ impl LazyRefValidator {
             base_uri,
             scopes,
             vocabularies: ctx.vocabularies().clone(),
-            location: ctx.location().join("$recursiveRef"),
+            limit: RecursionLimit::new(ctx.config(), location.clone()),
+            location,
             draft: ctx.draft(),
             inner: OnceCell::default(),
         }))
@@ -144,20 +270,37 @@ impl LazyRefValidator {
 
 impl Validate for LazyRefValidator {
     fn is_valid(&self, instance: &Value) -> bool {
-        self.lazy_compile().is_valid(instance)
+        self.limit.is_valid(self.lazy_compile(), instance)
     }
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
+        let Some(_guard) = self.limit.enter() else {
+            return Err(self.limit.too_deep(instance, location));
+        };
         self.lazy_compile().validate(instance, location)
     }
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        self.lazy_compile().iter_errors(instance, location)
+        self.limit
+            .iter_errors(self.lazy_compile(), instance, location)
     }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
-        self.lazy_compile().apply(instance, location)
+        self.limit.apply(self.lazy_compile(), instance, location)
+    }
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        self.limit
+            .trace(self.lazy_compile(), instance, location, callback)
+    }
+
+    fn descend(&self, _: &Value, _: &str) -> Option<Descent<'_>> {
+        Some(Descent::Here(self.lazy_compile()))
     }
 }
 
@@ -165,6 +308,7 @@ impl Validate for RefValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         match self {
             RefValidator::Default { inner } => inner.is_valid(instance),
+            RefValidator::Recursive { inner, limit } => limit.is_valid(inner, instance),
             RefValidator::Lazy(lazy) => lazy.is_valid(instance),
         }
     }
@@ -175,21 +319,54 @@ impl Validate for RefValidator {
     ) -> Result<(), ValidationError<'i>> {
         match self {
             RefValidator::Default { inner } => inner.validate(instance, location),
+            RefValidator::Recursive { inner, limit } => {
+                let Some(_guard) = limit.enter() else {
+                    return Err(limit.too_deep(instance, location));
+                };
+                inner.validate(instance, location)
+            }
             RefValidator::Lazy(lazy) => lazy.validate(instance, location),
         }
     }
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         match self {
             RefValidator::Default { inner } => inner.iter_errors(instance, location),
+            RefValidator::Recursive { inner, limit } => {
+                limit.iter_errors(inner, instance, location)
+            }
             RefValidator::Lazy(lazy) => lazy.iter_errors(instance, location),
         }
     }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         match self {
             RefValidator::Default { inner } => inner.apply(instance, location),
+            RefValidator::Recursive { inner, limit } => limit.apply(inner, instance, location),
             RefValidator::Lazy(lazy) => lazy.apply(instance, location),
         }
     }
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        match self {
+            RefValidator::Default { inner } => inner.trace(instance, location, callback),
+            RefValidator::Recursive { inner, limit } => {
+                limit.trace(inner, instance, location, callback)
+            }
+            RefValidator::Lazy(lazy) => lazy.trace(instance, location, callback),
+        }
+    }
+
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        match self {
+            RefValidator::Default { inner } | RefValidator::Recursive { inner, .. } => {
+                Some(Descent::Here(inner))
+            }
+            RefValidator::Lazy(lazy) => lazy.descend(instance, segment),
+        }
+    }
 }
 
 fn invalid_reference<'a>(ctx: &compiler::Context, schema: &'a Value) -> ValidationError<'a> {
@@ -201,6 +378,18 @@ fn invalid_reference<'a>(ctx: &compiler::Context, schema: &'a Value) -> Validati
     )
 }
 
+/// The error for a reference that does not point to a schema.
+fn invalid_target(ctx: &compiler::Context, reference: &str) -> ValidationError<'static> {
+    match ctx.resolve_reference(reference) {
+        Ok(uri) => referencing::Error::InvalidReferenceTarget {
+            reference: reference.to_string(),
+            uri: uri.as_str().to_string(),
+        }
+        .into(),
+        Err(error) => error.into(),
+    }
+}
+
 #[inline]
 pub(crate) fn compile_impl<'a>(
     ctx: &compiler::Context,
@@ -253,9 +442,9 @@ pub(crate) fn compile_recursive_ref<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util};
     use ahash::HashMap;
-    use referencing::{Retrieve, Uri};
+    use referencing::{Draft, Resource, Retrieve, Uri};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -271,6 +460,63 @@ mod tests {
         &json!({"foo": 42}),
         "/properties/foo/$ref/type"
     )]
+    #[test_case(
+        &json!({
+            "properties": {"user": {"$ref": "#/$defs/user"}},
+            "$defs": {
+                "user": {"properties": {"name": {"$ref": "#/$defs/name"}}},
+                "name": {"type": "string"}
+            }
+        }),
+        &json!({"user": {"name": 42}}),
+        "/properties/user/$ref/properties/name/$ref/type"
+        ; "chain of references"
+    )]
+    #[test_case(
+        &json!({
+            "$id": "https://example.com/root",
+            "properties": {"name": {"$ref": "https://example.com/name"}},
+            "$defs": {"name": {"$id": "https://example.com/name", "type": "string"}}
+        }),
+        &json!({"name": 42}),
+        "/properties/name/$ref/type"
+        ; "reference to an embedded resource"
+    )]
+    #[test_case(
+        &json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {"a": {"$ref": "#/definitions/a"}},
+            "definitions": {
+                "a": {"allOf": [{"$ref": "#/definitions/b"}]},
+                "b": {"minimum": 3}
+            }
+        }),
+        &json!({"a": 1}),
+        "/properties/a/$ref/allOf/0/$ref/minimum"
+        ; "reference through an applicator"
+    )]
+    #[test_case(
+        &json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$dynamicAnchor": "node",
+            "type": "object",
+            "properties": {"child": {"$dynamicRef": "#node"}}
+        }),
+        &json!({"child": 1}),
+        "/properties/child/$dynamicRef/type"
+        ; "dynamic reference"
+    )]
+    #[test_case(
+        &json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "$recursiveAnchor": true,
+            "type": "object",
+            "properties": {"child": {"$recursiveRef": "#"}}
+        }),
+        &json!({"child": 1}),
+        "/properties/child/$recursiveRef/type"
+        ; "recursive reference"
+    )]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
@@ -322,6 +568,33 @@ mod tests {
         );
     }
 
+    #[test_case(Draft::Draft4, &json!({
+        "allOf": [{"$ref": "#foo"}],
+        "definitions": {"A": {"id": "#foo", "type": "integer"}}
+    }); "draft 4 fragment")]
+    #[test_case(Draft::Draft4, &json!({
+        "allOf": [{"$ref": "http://localhost:1234/bar#foo"}],
+        "definitions": {"A": {"id": "http://localhost:1234/bar#foo", "type": "integer"}}
+    }); "draft 4 absolute uri with fragment")]
+    #[test_case(Draft::Draft4, &json!({
+        "id": "http://localhost:1234/root",
+        "allOf": [{"$ref": "http://localhost:1234/nested.json#foo"}],
+        "definitions": {
+            "A": {
+                "id": "nested.json",
+                "definitions": {"B": {"id": "#foo", "type": "integer"}}
+            }
+        }
+    }); "draft 4 base uri change")]
+    #[test_case(Draft::Draft7, &json!({
+        "allOf": [{"$ref": "http://localhost:1234/bar#foo"}],
+        "definitions": {"A": {"$id": "http://localhost:1234/bar#foo", "type": "integer"}}
+    }); "draft 7 absolute uri with fragment")]
+    fn location_independent_identifier(draft: Draft, schema: &Value) {
+        tests_util::is_valid_with_draft(draft, schema, &json!(1));
+        tests_util::is_not_valid_with_draft(draft, schema, &json!("a"));
+    }
+
     #[test]
     fn test_relative_base_uri() {
         let schema = json!({
@@ -607,4 +880,108 @@ mod tests {
         let validator = crate::validator_for(&json!({"$ref": "#"})).expect("Invalid schema");
         assert!(validator.is_valid(&json!(42)));
     }
+
+    #[test]
+    fn test_bundle_with_embedded_resources() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/bundle",
+            "$ref": "customer",
+            "$defs": {
+                "customer": {
+                    "$id": "https://example.com/customer",
+                    "properties": {"address": {"$ref": "address"}},
+                    "required": ["address"]
+                },
+                "address": {
+                    "$id": "https://example.com/address",
+                    "properties": {
+                        "city": {"type": "string"},
+                        "resident": {"$ref": "customer"}
+                    }
+                }
+            }
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"address": {"city": "Prague"}})));
+        assert!(validator.is_valid(&json!({"address": {"resident": {"address": {}}}})));
+        assert!(!validator.is_valid(&json!({"address": {"city": 42}})));
+        assert!(!validator.is_valid(&json!({"address": {"resident": {}}})));
+    }
+
+    #[test_case(&json!({"$id": "https://例え.test/schemas/ルート", "properties": {"a": {"$ref": "名前"}}, "$defs": {"名前": {"$id": "名前", "type": "string"}}}); "non-ASCII identifiers")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/名前"}}, "$defs": {"名前": {"type": "string"}}}); "non-ASCII pointer")]
+    fn test_iri_references(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": "x"})));
+        assert!(!validator.is_valid(&json!({"a": 1})));
+    }
+
+    #[test_case(&json!({"$ref": "#/examples/0", "examples": ["x"]}), "#/examples/0", "json-schema:///#/examples/0"; "string")]
+    #[test_case(&json!({"$ref": "#/examples", "examples": [{}]}), "#/examples", "json-schema:///#/examples"; "array")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "properties": {"a": {"$ref": "#/values/n"}}, "values": {"n": 5}}), "#/values/n", "json-schema:///#/values/n"; "draft 7")]
+    #[test_case(&json!({"$id": "https://example.com/root", "$ref": "#/values/n", "values": {"n": null}}), "#/values/n", "https://example.com/root#/values/n"; "absolute base")]
+    fn test_invalid_reference_target(schema: &Value, reference: &str, uri: &str) {
+        let error = crate::validator_for(schema).expect_err("Should fail");
+        let ValidationErrorKind::Referencing(referencing::Error::InvalidReferenceTarget {
+            reference: actual_reference,
+            uri: actual_uri,
+        }) = &error.kind
+        else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(actual_reference, reference);
+        assert_eq!(actual_uri, uri);
+        assert_eq!(
+            error.to_string(),
+            format!("Reference '{reference}' points to '{uri}', which is not an object or a boolean and can't be used as a schema")
+        );
+    }
+
+    #[test_case("https://example.com/doc", true; "allowed")]
+    #[test_case("HTTPS://example.com/doc", true; "case insensitive")]
+    #[test_case("file:///doc", false; "not allowed")]
+    fn test_allowed_schemes(reference: &str, is_allowed: bool) {
+        let schema = json!({"$ref": reference});
+        let mut storage = HashMap::default();
+        storage.insert("/doc".to_string(), json!({"type": "integer"}));
+        let retriever = TestRetrieve { storage };
+        let result = crate::options()
+            .with_retriever(retriever)
+            .with_allowed_schemes(["https"])
+            .build(&schema);
+        if is_allowed {
+            let validator = result.expect("Invalid schema");
+            assert!(validator.is_valid(&json!(1)));
+        } else {
+            let error = result.expect_err("Should fail");
+            assert!(matches!(
+                &error.kind,
+                ValidationErrorKind::Referencing(referencing::Error::SchemeNotAllowed { scheme })
+                    if scheme == "file"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rewrite_specification() {
+        let schema = json!({"$ref": "https://json-schema.org/draft/2020-12/schema"});
+        let validator = crate::options()
+            .with_rewrite(
+                "https://json-schema.org/draft/2020-12/",
+                "https://mirror.example.com/2020-12/",
+            )
+            .with_resource(
+                "https://mirror.example.com/2020-12/schema",
+                Resource::from_contents(json!({"type": "string"})).expect("Invalid resource"),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        // The mirror is used instead of the bundled meta-schema
+        assert!(validator.is_valid(&json!("a")));
+        assert!(!validator.is_valid(&json!({})));
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert!(!validator.is_valid(&json!("a")));
+        assert!(validator.is_valid(&json!({})));
+    }
 }
@@ -1,12 +1,12 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers::is_integer, CompilationResult},
     paths::Location,
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
 };
-use serde_json::{json, Map, Number, Value};
+use serde_json::{json, Map, Value};
 use std::convert::TryFrom;
 
 use crate::paths::LazyLocation;
@@ -319,10 +319,6 @@ impl Validate for IntegerTypeValidator {
     }
 }
 
-fn is_integer(num: &Number) -> bool {
-    num.is_u64() || num.is_i64() || num.as_f64().expect("Always valid").fract() == 0.
-}
-
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
@@ -399,4 +395,28 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(crate::Draft::Draft202012, "123456789012345678901234567890", true)]
+    #[test_case(crate::Draft::Draft202012, "-123456789012345678901234567890", true)]
+    #[test_case(crate::Draft::Draft202012, "123456789012345678901234567890.0", true)]
+    #[test_case(crate::Draft::Draft202012, "123456789012345678901234567890.5", false)]
+    #[test_case(crate::Draft::Draft202012, "1e400", true)]
+    #[test_case(crate::Draft::Draft7, "1.0", true)]
+    #[test_case(crate::Draft::Draft4, "123456789012345678901234567890", true)]
+    #[test_case(crate::Draft::Draft4, "123456789012345678901234567890.0", false)]
+    #[test_case(crate::Draft::Draft4, "1e400", false)]
+    fn arbitrary_precision_integer(draft: crate::Draft, instance: &str, expected: bool) {
+        let instance: Value = serde_json::from_str(instance).expect("Valid JSON");
+        for schema in [
+            json!({"type": "integer"}),
+            json!({"type": ["integer", "null"]}),
+        ] {
+            let validator = crate::options()
+                .with_draft(draft)
+                .build(&schema)
+                .expect("Invalid schema");
+            assert_eq!(validator.is_valid(&instance), expected);
+        }
+    }
 }
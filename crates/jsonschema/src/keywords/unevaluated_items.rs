@@ -241,24 +241,21 @@ impl ItemsFilter for Draft2019ItemsFilter {
         }
 
         if let Some(combinator) = &self.any_of {
-            if combinator
-                .subschemas
-                .iter()
-                .all(|(v, _)| v.is_valid(instance))
-            {
-                combinator.mark_evaluated_indexes(instance, indexes);
+            // Every valid branch contributes its annotations
+            for (node, filter) in &combinator.subschemas {
+                if node.is_valid(instance) {
+                    filter.mark_evaluated_indexes(instance, indexes);
+                }
             }
         }
 
         if let Some(combinator) = &self.one_of {
-            if combinator
+            let mut valid = combinator
                 .subschemas
                 .iter()
-                .filter(|(v, _)| v.is_valid(instance))
-                .count()
-                == 1
-            {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                .filter(|(node, _)| node.is_valid(instance));
+            if let (Some((_, filter)), None) = (valid.next(), valid.next()) {
+                filter.mark_evaluated_indexes(instance, indexes);
             }
         }
     }
@@ -419,24 +416,21 @@ impl ItemsFilter for DefaultItemsFilter {
         }
 
         if let Some(combinator) = &self.any_of {
-            if combinator
-                .subschemas
-                .iter()
-                .all(|(v, _)| v.is_valid(instance))
-            {
-                combinator.mark_evaluated_indexes(instance, indexes);
+            // Every valid branch contributes its annotations
+            for (node, filter) in &combinator.subschemas {
+                if node.is_valid(instance) {
+                    filter.mark_evaluated_indexes(instance, indexes);
+                }
             }
         }
 
         if let Some(combinator) = &self.one_of {
-            if combinator
+            let mut valid = combinator
                 .subschemas
                 .iter()
-                .filter(|(v, _)| v.is_valid(instance))
-                .count()
-                == 1
-            {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                .filter(|(node, _)| node.is_valid(instance));
+            if let (Some((_, filter)), None) = (valid.next(), valid.next()) {
+                filter.mark_evaluated_indexes(instance, indexes);
             }
         }
     }
@@ -515,7 +509,9 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
+    use crate::tests_util;
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn test_unevaluated_items_with_recursion() {
@@ -591,4 +587,126 @@ mod tests {
         assert!(validator.is_valid(&valid_instance));
         assert!(validator.validate(&valid_instance).is_ok());
     }
+
+    fn contains_schema() -> Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "prefixItems": [{"type": "integer"}, {"type": "integer"}],
+            "contains": {"type": "string"},
+            "unevaluatedItems": false
+        })
+    }
+
+    // Index 2 is not covered by `prefixItems`, but `contains` evaluated it
+    #[test_case(&json!([1, 2, "a"]); "contains match after prefix")]
+    #[test_case(&json!([1, 2, "a", "b"]); "several contains matches")]
+    fn contains_marks_evaluated(instance: &Value) {
+        tests_util::is_valid(&contains_schema(), instance);
+    }
+
+    #[test_case(&json!([1, 2, "a", 3]); "item matching neither")]
+    #[test_case(&json!([1, 2, 3]); "no contains match")]
+    fn contains_leaves_unevaluated(instance: &Value) {
+        tests_util::is_not_valid(&contains_schema(), instance);
+    }
+
+    #[test_case(&json!(["a", "b"]), true; "all matched")]
+    #[test_case(&json!(["a", 1]), false; "one unmatched")]
+    fn nested_contains_marks_evaluated(instance: &Value, expected: bool) {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "allOf": [{"contains": {"type": "string"}}],
+            "unevaluatedItems": false
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+        assert_eq!(validator.apply(instance).basic().is_valid(), expected);
+    }
+
+    #[test]
+    fn contains_annotation() {
+        let schema = contains_schema();
+        let instance = json!([1, 2, "a"]);
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let crate::BasicOutput::Valid(units) = validator.apply(&instance).basic() else {
+            panic!("Instance should be valid");
+        };
+        let annotation = units
+            .iter()
+            .find(|unit| unit.keyword_location().as_str() == "/contains")
+            .expect("`contains` produces an annotation");
+        assert_eq!(annotation.value().as_ref(), &json!([2]));
+    }
+
+    #[test_case(&json!([1, 2]), true; "covered by branch")]
+    #[test_case(&json!([1]), true; "shorter than branch")]
+    #[test_case(&json!([1, 2, 3]), false; "item after branch")]
+    fn all_of_prefix_items(instance: &Value, expected: bool) {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "allOf": [{"prefixItems": [true, true]}],
+            "unevaluatedItems": false
+        });
+        assert_eq!(crate::is_valid(&schema, instance), expected);
+    }
+
+    #[test]
+    fn all_of_prefix_items_error() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "allOf": [{"prefixItems": [true, true]}],
+            "unevaluatedItems": {"type": "string"}
+        });
+        tests_util::is_valid(&schema, &json!([1, 2, "a"]));
+        tests_util::expect_errors(
+            &schema,
+            &json!([1, 2, "a", 3, 4]),
+            &["Unevaluated items are not allowed ('3', '4' were unexpected)"],
+        );
+        tests_util::assert_locations(&schema, &json!([1, 2, 3]), &["/unevaluatedItems"]);
+    }
+
+    #[test]
+    fn all_of_items_draft_2019_09() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "allOf": [{"items": [true, true]}],
+            "unevaluatedItems": false
+        });
+        tests_util::is_valid(&schema, &json!([1, 2]));
+        tests_util::is_not_valid(&schema, &json!([1, 2, 3]));
+    }
+
+    // Only valid branches contribute evaluated items
+    #[test_case(&json!(["a"]), true; "both branches")]
+    #[test_case(&json!([1]), true; "second branch")]
+    #[test_case(&json!(["a", 1]), true; "longer second branch")]
+    #[test_case(&json!(["a", 1, 2]), false; "item of invalid branch")]
+    fn any_of_valid_branches(instance: &Value, expected: bool) {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "anyOf": [
+                {"prefixItems": [{"type": "string"}]},
+                {"prefixItems": [true, true], "maxItems": 2}
+            ],
+            "unevaluatedItems": false
+        });
+        assert_eq!(crate::is_valid(&schema, instance), expected);
+    }
+
+    #[test_case(&json!([1]), true; "first branch")]
+    #[test_case(&json!(["a", "b"]), true; "second branch")]
+    #[test_case(&json!([1, "b"]), false; "item of invalid branch")]
+    fn one_of_valid_branch(instance: &Value, expected: bool) {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "oneOf": [
+                {"prefixItems": [{"type": "integer"}]},
+                {"prefixItems": [{"type": "string"}, true]}
+            ],
+            "unevaluatedItems": false
+        });
+        assert_eq!(crate::is_valid(&schema, instance), expected);
+    }
 }
@@ -1,20 +1,19 @@
 use std::{rc::Rc, sync::Arc};
 
 use ahash::AHashSet;
-use fancy_regex::Regex;
 use once_cell::sync::OnceCell;
 use referencing::{Draft, List, Registry, Resource, Uri, VocabularySet};
 use serde_json::{Map, Value};
 
 use crate::{
-    compiler, ecma,
+    compiler,
     node::SchemaNode,
     paths::{LazyLocation, Location},
     validator::Validate,
     ValidationError, ValidationOptions,
 };
 
-use super::CompilationResult;
+use super::{helpers::invalid_pattern_property, CompilationResult};
 
 pub(crate) trait PropertiesFilter: Send + Sync + Sized + 'static {
     fn new<'a>(
@@ -269,14 +268,15 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         if let Some(Value::Object(patterns)) = parent.get("patternProperties") {
             for (pattern, schema) in patterns {
                 pattern_properties.push((
-                    match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
-                        Ok(Ok(r)) => r,
-                        _ => {
-                            return Err(ValidationError::format(
-                                Location::new(),
-                                ctx.location().clone(),
-                                schema,
-                                "regex",
+                    match ctx.compile_regex(pattern) {
+                        Ok(r) => r,
+                        Err(error) => {
+                            return Err(invalid_pattern_property(
+                                ctx.location()
+                                    .join("patternProperties")
+                                    .join(pattern.as_str()),
+                                pattern,
+                                error,
                             ))
                         }
                     },
@@ -526,14 +526,15 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         if let Some(Value::Object(patterns)) = parent.get("patternProperties") {
             for (pattern, schema) in patterns {
                 pattern_properties.push((
-                    match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
-                        Ok(Ok(r)) => r,
-                        _ => {
-                            return Err(ValidationError::format(
-                                Location::new(),
-                                ctx.location().clone(),
-                                schema,
-                                "regex",
+                    match ctx.compile_regex(pattern) {
+                        Ok(r) => r,
+                        Err(error) => {
+                            return Err(invalid_pattern_property(
+                                ctx.location()
+                                    .join("patternProperties")
+                                    .join(pattern.as_str()),
+                                pattern,
+                                error,
                             ))
                         }
                     },
@@ -268,6 +268,25 @@
 //! #    Ok(())
 //! # }
 //! ```
+//!
+//! For schemas stored in memory, [`MapRetriever`] provides the same behavior out of the box:
+//!
+//! ```rust
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use jsonschema::MapRetriever;
+//! use serde_json::json;
+//!
+//! let retriever = MapRetriever::new()
+//!     .with_resource("https://example.com/name.json", json!({"type": "string"}));
+//!
+//! let validator = jsonschema::options()
+//!     .with_retriever(retriever)
+//!     .build(&json!({"$ref": "https://example.com/name.json"}))?;
+//!
+//! assert!(validator.is_valid(&json!("Alice")));
+//! #    Ok(())
+//! # }
+//! ```
 //! # Output Styles
 //!
 //! `jsonschema` supports the `basic` output style as defined in JSON Schema Draft 2019-09.
@@ -500,24 +519,43 @@
 pub(crate) mod compiler;
 mod content_encoding;
 mod content_media_type;
+mod coverage;
+mod defaults;
+mod dot;
+mod duplicate_keys;
 mod ecma;
 pub mod error;
 mod keywords;
+mod matches;
+mod migration;
 mod node;
 mod options;
 pub mod output;
+mod patch;
 pub mod paths;
 pub mod primitive_type;
+mod profile;
 pub(crate) mod properties;
 mod retriever;
+mod scalar;
+mod shape;
 mod validator;
 
-pub use error::{ErrorIterator, MaskedValidationError, ValidationError};
-pub use keywords::custom::Keyword;
+pub use coverage::CoverageCollector;
+pub use duplicate_keys::has_duplicate_keys;
+pub use error::{ErrorIterator, MaskedValidationError, SchemaParseError, ValidationError};
+pub use keywords::custom::{Keyword, KeywordContext};
 pub use options::ValidationOptions;
 pub use output::BasicOutput;
-pub use referencing::{Draft, Error as ReferencingError, Resource, Retrieve, Uri};
-pub use validator::Validator;
+pub use patch::ErrorDelta;
+#[cfg(feature = "profile")]
+pub use profile::{KeywordProfile, Profile};
+pub use referencing::{
+    Draft, Error as ReferencingError, KeywordKind, MapRetriever, RecordingRetriever, Registry,
+    Resource, Retrieval, RetrievalLog, Retrieve, Uri,
+};
+pub use shape::{Property, TypeShape};
+pub use validator::{ExampleError, TraceRecord, Validator};
 
 use serde_json::Value;
 
@@ -643,11 +681,13 @@ pub fn options() -> ValidationOptions {
 
 /// Functionality for validating JSON Schema documents against their meta-schemas.
 pub mod meta {
-    use crate::{error::ValidationError, Draft, ReferencingError};
+    use crate::{error::ValidationError, Draft, ReferencingError, Registry, Uri};
     use serde_json::Value;
 
     use crate::Validator;
 
+    pub use crate::migration::MigrationNote;
+
     pub(crate) mod validators {
         use crate::Validator;
         use once_cell::sync::Lazy;
@@ -807,15 +847,87 @@ pub mod meta {
         Ok(try_meta_validator_for(schema)?.validate(schema))
     }
 
+    /// Validate all resources stored in a registry against the meta-schemas of their drafts.
+    ///
+    /// Returns the URI and the first error of each invalid resource, sorted by URI. Resources
+    /// embedded into others via `$id` are stored separately and therefore checked on their own
+    /// as well. This is not done when building a registry, as it adds to the startup cost.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use jsonschema::{Registry, Resource};
+    /// use serde_json::json;
+    ///
+    /// let registry = Registry::try_from_resources(
+    ///     [
+    ///         (
+    ///             "urn:valid",
+    ///             Resource::from_contents(json!({"type": "string"}))?,
+    ///         ),
+    ///         (
+    ///             "urn:invalid",
+    ///             Resource::from_contents(json!({"type": "invalid_type"}))?,
+    ///         ),
+    ///     ]
+    ///     .into_iter(),
+    /// )?;
+    /// let errors = jsonschema::meta::validate_registry(&registry);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].0.as_str(), "urn:invalid");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn validate_registry(registry: &Registry) -> Vec<(&Uri<String>, ValidationError<'_>)> {
+        let mut errors: Vec<_> = registry
+            .resources()
+            .filter_map(|(uri, resource)| {
+                meta_validator_for_draft(resource.draft())
+                    .validate(resource.contents())
+                    .err()
+                    .map(|error| (uri, error))
+            })
+            .collect();
+        errors.sort_unstable_by(|(left, _), (right, _)| left.as_str().cmp(right.as_str()));
+        errors
+    }
+    /// Find keywords in `schema` whose meaning differs between the `from` and `to` drafts.
+    ///
+    /// Each [`MigrationNote`] points to a keyword that was renamed, removed, introduced, or
+    /// changed its semantics between the two drafts, in either direction. The schema itself is
+    /// not validated, and `$ref` targets are only checked where they are embedded in `schema`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use jsonschema::Draft;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "items": [{"type": "string"}],
+    ///     "additionalItems": false
+    /// });
+    /// let notes = jsonschema::meta::migration_report(&schema, Draft::Draft7, Draft::Draft202012);
+    /// let locations: Vec<_> = notes.iter().map(|note| note.location.as_str()).collect();
+    /// assert_eq!(locations, ["/additionalItems", "/items"]);
+    /// ```
+    #[must_use]
+    pub fn migration_report(schema: &Value, from: Draft, to: Draft) -> Vec<MigrationNote> {
+        crate::migration::migration_report(schema, from, to)
+    }
+
     fn try_meta_validator_for(schema: &Value) -> Result<&'static Validator, ReferencingError> {
-        Ok(match Draft::default().detect(schema)? {
+        Ok(meta_validator_for_draft(Draft::default().detect(schema)?))
+    }
+
+    fn meta_validator_for_draft(draft: Draft) -> &'static Validator {
+        match draft {
             Draft::Draft4 => &validators::DRAFT4_META_VALIDATOR,
             Draft::Draft6 => &validators::DRAFT6_META_VALIDATOR,
             Draft::Draft7 => &validators::DRAFT7_META_VALIDATOR,
             Draft::Draft201909 => &validators::DRAFT201909_META_VALIDATOR,
             Draft::Draft202012 => &validators::DRAFT202012_META_VALIDATOR,
             _ => unreachable!("Unknown draft"),
-        })
+        }
     }
 }
 
@@ -1837,6 +1949,40 @@ mod tests {
         assert!(!is_valid_fn(&invalid));
     }
 
+    #[test]
+    fn test_meta_validation_of_registry() {
+        let registry = referencing::Registry::try_from_resources(
+            [
+                (
+                    "http://example.com/valid",
+                    Draft::Draft7.create_resource(json!({
+                        "$id": "http://example.com/valid",
+                        "definitions": {
+                            "nested": {"$id": "nested", "type": "string"}
+                        }
+                    })),
+                ),
+                (
+                    "http://example.com/invalid",
+                    Draft::Draft202012.create_resource(json!({"minLength": -1})),
+                ),
+                (
+                    "http://example.com/draft4",
+                    // Valid only in Draft 4
+                    Draft::Draft4.create_resource(json!({"minimum": 1, "exclusiveMinimum": true})),
+                ),
+            ]
+            .into_iter(),
+        )
+        .expect("Invalid resources");
+        assert_eq!(registry.resources().count(), 4);
+        let errors = crate::meta::validate_registry(&registry);
+        assert_eq!(errors.len(), 1);
+        let (uri, error) = &errors[0];
+        assert_eq!(uri.as_str(), "http://example.com/invalid");
+        assert_eq!(error.instance_path.as_str(), "/minLength");
+    }
+
     #[test]
     fn test_exclusive_minimum_across_drafts() {
         // In Draft 4, exclusiveMinimum is a boolean modifier for minimum
@@ -0,0 +1,205 @@
+//! Locating the parts of an instance that a referenced subschema was applied to.
+use crate::{
+    patch::{child, parse_pointer},
+    paths::Location,
+    Validator,
+};
+use ahash::{AHashMap, AHashSet};
+use referencing::{Draft, Resolver};
+use serde_json::Value;
+
+pub(crate) fn locate_subschema_matches(
+    validator: &Validator,
+    instance: &Value,
+    pointer: &str,
+) -> Vec<Location> {
+    let Some(reference) = validator.root_reference.as_deref() else {
+        return Vec::new();
+    };
+    let Ok(resolver) = validator.registry.try_resolver(&validator.base_uri) else {
+        return Vec::new();
+    };
+    let Ok(root) = resolver.lookup(reference) else {
+        return Vec::new();
+    };
+    let target = if pointer.starts_with('#') {
+        pointer.to_string()
+    } else {
+        format!("#{pointer}")
+    };
+    let Ok(target) = root.resolver().lookup(&target) else {
+        return Vec::new();
+    };
+    let target = target.contents();
+    let (root, resolver, draft) = root.into_inner();
+    let Ok(resolver) = resolver.in_subresource(draft.create_resource_ref(root)) else {
+        return Vec::new();
+    };
+
+    // Whether the reference keyword at the given schema location points to the target
+    let mut is_match = AHashMap::new();
+    let mut seen = AHashSet::new();
+    let mut locations = Vec::new();
+    validator.trace(instance, |record| {
+        if !record.valid || !is_reference(record.keyword_location.as_str()) {
+            return;
+        }
+        let keyword_location = record.keyword_location.as_str().to_string();
+        let matches = *is_match
+            .entry(keyword_location)
+            .or_insert_with_key(|location| {
+                resolve_reference(root, &resolver, draft, location)
+                    .is_some_and(|resolved| std::ptr::eq(resolved, target))
+            });
+        if matches && seen.insert(record.instance_location.as_str().to_string()) {
+            locations.push(record.instance_location);
+        }
+    });
+    locations
+}
+
+/// Keywords whose subschema is looked up the same way as for `$ref`.
+const REFERENCE_KEYWORDS: [&str; 2] = ["$ref", "$dynamicRef"];
+
+fn is_reference(location: &str) -> bool {
+    location
+        .rsplit_once('/')
+        .is_some_and(|(_, keyword)| REFERENCE_KEYWORDS.contains(&keyword))
+}
+
+/// Follow the evaluation path `location` through `root`, resolving every reference on the way,
+/// and return the schema the final reference points to.
+fn resolve_reference<'r>(
+    root: &'r Value,
+    resolver: &Resolver<'r>,
+    mut draft: Draft,
+    location: &str,
+) -> Option<&'r Value> {
+    let mut schema = root;
+    let mut resolver = resolver.clone();
+    for segment in parse_pointer(location)? {
+        if REFERENCE_KEYWORDS.contains(&segment.as_str()) {
+            let reference = schema.get(&segment)?.as_str()?;
+            (schema, resolver, draft) = resolver.lookup(reference).ok()?.into_inner();
+        } else {
+            schema = child(schema, &segment)?;
+            if schema.is_object() {
+                draft = draft.detect(schema).unwrap_or_default();
+                resolver = resolver
+                    .in_subresource(draft.create_resource_ref(schema))
+                    .ok()?;
+            }
+        }
+    }
+    Some(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn locate(schema: &Value, instance: &Value, pointer: &str) -> Vec<String> {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        validator
+            .locate_subschema_matches(instance, pointer)
+            .iter()
+            .map(|location| location.as_str().to_string())
+            .collect()
+    }
+
+    fn people() -> Value {
+        json!({
+            "type": "array",
+            "items": {"$ref": "#/$defs/person"},
+            "$defs": {
+                "person": {
+                    "properties": {
+                        "name": {"type": "string"},
+                        "email": {"$ref": "#/$defs/PII"},
+                        "contacts": {"items": {"$ref": "#/$defs/PII"}}
+                    }
+                },
+                "PII": {"type": "string", "minLength": 3}
+            }
+        })
+    }
+
+    #[test]
+    fn array_of_objects() {
+        let instance = json!([
+            {"name": "Alice", "email": "alice@example.com"},
+            {"name": "Bob"},
+            {"name": "Carol", "email": "c", "contacts": ["dave@example.com", 42]}
+        ]);
+        assert_eq!(
+            locate(&people(), &instance, "/$defs/PII"),
+            vec!["/0/email", "/2/contacts/0"]
+        );
+        assert_eq!(
+            locate(&people(), &instance, "/$defs/person"),
+            vec!["/0", "/1"]
+        );
+    }
+
+    #[test_case("/$defs/missing"; "unknown pointer")]
+    #[test_case("/$defs/person/properties/name"; "not referenced")]
+    fn no_matches(pointer: &str) {
+        let instance = json!([{"name": "Alice", "email": "alice@example.com"}]);
+        assert!(locate(&people(), &instance, pointer).is_empty());
+    }
+
+    #[test_case(json!({"additionalProperties": {"$ref": "#/$defs/PII"}}), json!({"a": "x"}), &["/a"]; "additional properties")]
+    #[test_case(json!({"properties": {"a": {"$ref": "#/$defs/PII"}}, "additionalProperties": false}), json!({"a": "x"}), &["/a"]; "properties with additional properties")]
+    #[test_case(json!({"patternProperties": {"^a": {"$ref": "#/$defs/PII"}}, "additionalProperties": {"$ref": "#/$defs/PII"}}), json!({"ab": "x", "b": "y"}), &["/ab", "/b"]; "pattern properties with additional properties")]
+    #[test_case(json!({"patternProperties": {"^a": {"$ref": "#/$defs/PII"}}}), json!({"ab": "x", "b": "y"}), &["/ab"]; "pattern properties")]
+    #[test_case(json!({"prefixItems": [{"$ref": "#/$defs/PII"}], "items": {"$ref": "#/$defs/PII"}}), json!(["x", "y"]), &["/1", "/0"]; "prefix items")]
+    #[test_case(json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": [{"$ref": "#/$defs/PII"}], "additionalItems": {"$ref": "#/$defs/PII"}}), json!(["x", "y"]), &["/1", "/0"]; "items array")]
+    #[test_case(json!({"if": true, "then": {"$ref": "#/$defs/PII"}, "else": {"$ref": "#/$defs/PII"}}), json!("x"), &[""]; "if then else")]
+    #[test_case(json!({"dependentSchemas": {"a": {"properties": {"b": {"$ref": "#/$defs/PII"}}}}}), json!({"a": 1, "b": "x"}), &["/b"]; "dependent schemas")]
+    #[test_case(json!({"contains": {"$ref": "#/$defs/PII"}}), json!([1, "x"]), &["/1"]; "contains")]
+    #[test_case(json!({"$dynamicRef": "#/$defs/PII"}), json!("x"), &[""]; "dynamic reference")]
+    #[test_case(json!({"propertyNames": {"$ref": "#/$defs/PII"}}), json!({"x": 1}), &[]; "property names are not followed")]
+    fn applicators(mut schema: Value, instance: Value, expected: &[&str]) {
+        schema["$defs"] = json!({"PII": {"type": "string"}});
+        assert_eq!(locate(&schema, &instance, "/$defs/PII"), expected);
+    }
+
+    #[test]
+    fn reference_inside_resource_with_id() {
+        let schema = json!({
+            "$id": "https://example.com/root",
+            "properties": {
+                "user": {
+                    "$id": "user",
+                    "properties": {"ssn": {"$ref": "root#/$defs/PII"}}
+                }
+            },
+            "$defs": {"PII": {"type": "string"}}
+        });
+        assert_eq!(
+            locate(&schema, &json!({"user": {"ssn": "123"}}), "#/$defs/PII"),
+            vec!["/user/ssn"]
+        );
+    }
+
+    #[test]
+    fn recursive_reference() {
+        let schema = json!({
+            "$ref": "#/$defs/node",
+            "$defs": {
+                "node": {
+                    "properties": {
+                        "value": {"type": "integer"},
+                        "children": {"items": {"$ref": "#/$defs/node"}}
+                    }
+                }
+            }
+        });
+        let instance = json!({"value": 1, "children": [{"value": 2}, {"children": [{}]}]});
+        assert_eq!(
+            locate(&schema, &instance, "/$defs/node"),
+            vec!["/children/0", "/children/1/children/0", "/children/1", ""]
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Detection of keywords whose meaning differs between two drafts.
+use crate::{paths::Location, Draft};
+use serde_json::{Map, Value};
+
+/// A keyword that behaves differently after migrating a schema to another draft.
+///
+/// Returned by [`meta::migration_report`](crate::meta::migration_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationNote {
+    /// Location of the keyword within the schema.
+    pub location: Location,
+    /// What changed between the drafts.
+    pub description: String,
+}
+
+/// Keywords holding a single subschema.
+pub(crate) const SINGLE: &[&str] = &[
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "contentSchema",
+    "else",
+    "if",
+    "items",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+/// Keywords holding an array of subschemas.
+pub(crate) const ARRAY: &[&str] = &["allOf", "anyOf", "items", "oneOf", "prefixItems"];
+/// Keywords holding an object with subschemas as values.
+pub(crate) const MAP: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependencies",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+pub(crate) fn migration_report(schema: &Value, from: Draft, to: Draft) -> Vec<MigrationNote> {
+    let mut notes = Vec::new();
+    if from != to {
+        collect(schema, from, to, &Location::new(), &mut notes);
+    }
+    notes
+}
+
+fn collect(
+    schema: &Value,
+    from: Draft,
+    to: Draft,
+    location: &Location,
+    notes: &mut Vec<MigrationNote>,
+) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+    for (keyword, value) in object {
+        let location = location.join(keyword.as_str());
+        if let Some(description) = describe(keyword, value, object, from, to) {
+            notes.push(MigrationNote {
+                location: location.clone(),
+                description,
+            });
+        }
+        match value {
+            Value::Object(_) if SINGLE.contains(&keyword.as_str()) => {
+                collect(value, from, to, &location, notes);
+            }
+            Value::Object(subschemas) if MAP.contains(&keyword.as_str()) => {
+                for (name, subschema) in subschemas {
+                    collect(subschema, from, to, &location.join(name.as_str()), notes);
+                }
+            }
+            Value::Array(subschemas) if ARRAY.contains(&keyword.as_str()) => {
+                for (idx, subschema) in subschemas.iter().enumerate() {
+                    collect(subschema, from, to, &location.join(idx), notes);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Describe how `keyword` changes if the migration crosses the draft where its meaning changed.
+fn describe(
+    keyword: &str,
+    value: &Value,
+    schema: &Map<String, Value>,
+    from: Draft,
+    to: Draft,
+) -> Option<String> {
+    let crosses = |draft: Draft| (from < draft && draft <= to) || (to < draft && draft <= from);
+    let introduced = |draft: Draft| {
+        crosses(draft).then(|| format!("`{keyword}` is only recognized since {}", name(draft)))
+    };
+    match keyword {
+        "exclusiveMinimum" | "exclusiveMaximum" if crosses(Draft::Draft6) => Some(format!(
+            "`{keyword}` is a boolean modifier of `{}` in Draft 4 and a standalone number since Draft 6",
+            if keyword == "exclusiveMinimum" {
+                "minimum"
+            } else {
+                "maximum"
+            }
+        )),
+        "id" if value.is_string() && crosses(Draft::Draft6) => {
+            Some("`id` is replaced by `$id` since Draft 6".to_string())
+        }
+        "$id" | "const" | "contains" | "examples" | "propertyNames" => {
+            introduced(Draft::Draft6)
+        }
+        "if" | "then" | "else" | "contentMediaType" | "contentEncoding" => {
+            introduced(Draft::Draft7)
+        }
+        "$ref" if schema.len() > 1 && crosses(Draft::Draft201909) => Some(
+            "Keywords next to `$ref` are ignored before Draft 2019-09 and evaluated since"
+                .to_string(),
+        ),
+        "dependencies" if crosses(Draft::Draft201909) => Some(
+            "`dependencies` is split into `dependentRequired` and `dependentSchemas` since Draft 2019-09"
+                .to_string(),
+        ),
+        "definitions" if crosses(Draft::Draft201909) => {
+            Some("`definitions` is replaced by `$defs` since Draft 2019-09".to_string())
+        }
+        "$recursiveRef" | "$recursiveAnchor" if crosses(Draft::Draft202012) => Some(format!(
+            "`{keyword}` is replaced by `{}` since Draft 2020-12",
+            if keyword == "$recursiveRef" {
+                "$dynamicRef"
+            } else {
+                "$dynamicAnchor"
+            }
+        )),
+        "$anchor" | "$defs" | "$recursiveRef" | "$recursiveAnchor" | "dependentRequired"
+        | "dependentSchemas" | "unevaluatedItems" | "unevaluatedProperties" | "contentSchema" => {
+            introduced(Draft::Draft201909)
+        }
+        "items" if value.is_array() && crosses(Draft::Draft202012) => Some(
+            "The array form of `items` is replaced by `prefixItems` since Draft 2020-12"
+                .to_string(),
+        ),
+        "items" if schema.contains_key("prefixItems") && crosses(Draft::Draft202012) => Some(
+            "`items` applies only to items after `prefixItems` since Draft 2020-12".to_string(),
+        ),
+        "additionalItems" if crosses(Draft::Draft202012) => Some(
+            "`additionalItems` is replaced by `items` next to `prefixItems` since Draft 2020-12"
+                .to_string(),
+        ),
+        "$dynamicRef" | "$dynamicAnchor" | "prefixItems" => introduced(Draft::Draft202012),
+        _ => None,
+    }
+}
+
+fn name(draft: Draft) -> &'static str {
+    match draft {
+        Draft::Draft4 => "Draft 4",
+        Draft::Draft6 => "Draft 6",
+        Draft::Draft7 => "Draft 7",
+        Draft::Draft201909 => "Draft 2019-09",
+        _ => "Draft 2020-12",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::migration_report;
+    use crate::Draft;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn report(schema: &Value, from: Draft, to: Draft) -> Vec<(String, String)> {
+        migration_report(schema, from, to)
+            .into_iter()
+            .map(|note| (note.location.as_str().to_string(), note.description))
+            .collect()
+    }
+
+    #[test]
+    fn additional_items_to_2020_12() {
+        let schema = json!({
+            "type": "array",
+            "items": [{"type": "string"}, {"type": "integer"}],
+            "additionalItems": {"type": "boolean"},
+            "properties": {
+                "tags": {"items": [{"type": "string"}], "additionalItems": false}
+            }
+        });
+        assert_eq!(
+            report(&schema, Draft::Draft7, Draft::Draft202012),
+            vec![
+                (
+                    "/additionalItems".to_string(),
+                    "`additionalItems` is replaced by `items` next to `prefixItems` since Draft 2020-12"
+                        .to_string()
+                ),
+                (
+                    "/items".to_string(),
+                    "The array form of `items` is replaced by `prefixItems` since Draft 2020-12"
+                        .to_string()
+                ),
+                (
+                    "/properties/tags/additionalItems".to_string(),
+                    "`additionalItems` is replaced by `items` next to `prefixItems` since Draft 2020-12"
+                        .to_string()
+                ),
+                (
+                    "/properties/tags/items".to_string(),
+                    "The array form of `items` is replaced by `prefixItems` since Draft 2020-12"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn dependencies_and_reference_siblings() {
+        let schema = json!({
+            "dependencies": {"a": ["b"], "c": {"$ref": "#/definitions/c", "minProperties": 1}},
+            "definitions": {"c": {}}
+        });
+        let locations: Vec<_> = report(&schema, Draft::Draft7, Draft::Draft201909)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect();
+        assert_eq!(
+            locations,
+            ["/definitions", "/dependencies", "/dependencies/c/$ref"]
+        );
+    }
+
+    #[test]
+    fn downgrade() {
+        let schema = json!({"prefixItems": [{}], "items": false, "$defs": {}});
+        let locations: Vec<_> = report(&schema, Draft::Draft202012, Draft::Draft7)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect();
+        assert_eq!(locations, ["/$defs", "/items", "/prefixItems"]);
+    }
+
+    #[test_case(&json!({"exclusiveMinimum": true, "minimum": 5}), Draft::Draft4, Draft::Draft6, &["/exclusiveMinimum"]; "exclusive minimum")]
+    #[test_case(&json!({"id": "https://example.com"}), Draft::Draft4, Draft::Draft7, &["/id"]; "id")]
+    #[test_case(&json!({"properties": {"id": {"type": "string"}}}), Draft::Draft4, Draft::Draft7, &[]; "property named like a keyword")]
+    #[test_case(&json!({"items": [{}], "additionalItems": false}), Draft::Draft6, Draft::Draft201909, &[]; "unchanged items")]
+    #[test_case(&json!({"items": [{}], "additionalItems": false}), Draft::Draft7, Draft::Draft7, &[]; "same draft")]
+    #[test_case(&json!({"if": {"const": 1}, "then": {"const": 2}}), Draft::Draft7, Draft::Draft6, &["/if", "/then"]; "introduced keywords")]
+    fn locations(schema: &Value, from: Draft, to: Draft, expected: &[&str]) {
+        let locations: Vec<_> = report(schema, from, to)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect();
+        assert_eq!(locations, expected);
+    }
+}
@@ -1,16 +1,17 @@
 use crate::{
     compiler::Context,
     error::ErrorIterator,
-    keywords::{BoxedValidator, Keyword},
-    output::{Annotations, BasicOutput, ErrorDescription, OutputUnit},
+    keywords::{BoxedValidator, InstanceKind, Keyword},
+    output::{Annotations, BasicOutput, ErrorDescription, OutputUnit, Unit},
     paths::{LazyLocation, Location, LocationSegment},
-    validator::{PartialApplication, Validate},
+    profile,
+    validator::{Descent, PartialApplication, TraceRecord, Validate},
     ValidationError,
 };
 use ahash::AHashMap;
 use referencing::{uri, Uri};
 use serde_json::Value;
-use std::{cell::OnceCell, collections::VecDeque, fmt};
+use std::{cell::OnceCell, collections::VecDeque, fmt, sync::Arc};
 
 /// A node in the schema tree, returned by [`compiler::compile`]
 #[derive(Debug)]
@@ -56,6 +57,37 @@ struct KeywordValidators {
     // We should probably use AHashMap here but it breaks a bunch of test which assume
     // validators are in a particular order
     validators: Vec<(Keyword, BoxedValidator)>,
+    /// Indices of `validators` that may fail for each `InstanceKind`, in the original order
+    by_kind: [Box<[usize]>; InstanceKind::ALL.len()],
+}
+
+impl KeywordValidators {
+    fn new(
+        validators: Vec<(Keyword, BoxedValidator)>,
+        unmatched_keywords: Option<AHashMap<String, Value>>,
+    ) -> KeywordValidators {
+        let by_kind = InstanceKind::ALL.map(|kind| {
+            validators
+                .iter()
+                .enumerate()
+                .filter(|(_, (keyword, _))| keyword.applies_to(kind))
+                .map(|(idx, _)| idx)
+                .collect()
+        });
+        KeywordValidators {
+            unmatched_keywords,
+            validators,
+            by_kind,
+        }
+    }
+
+    /// Validators that may fail for the given instance.
+    #[inline]
+    fn applicable(&self, instance: &Value) -> impl Iterator<Item = &(Keyword, BoxedValidator)> {
+        self.by_kind[InstanceKind::of(instance) as usize]
+            .iter()
+            .map(|idx| &self.validators[*idx])
+    }
 }
 
 impl SchemaNode {
@@ -75,10 +107,10 @@ impl SchemaNode {
         SchemaNode {
             location: ctx.location().clone(),
             absolute_path: ctx.base_uri(),
-            validators: NodeValidators::Keyword(KeywordValidators {
-                unmatched_keywords,
+            validators: NodeValidators::Keyword(KeywordValidators::new(
                 validators,
-            }),
+                unmatched_keywords,
+            )),
         }
     }
 
@@ -90,6 +122,23 @@ impl SchemaNode {
         }
     }
 
+    /// Combine already compiled nodes with `allOf` semantics.
+    ///
+    /// The nodes are shared instead of being recompiled, therefore all errors and annotations keep
+    /// the locations they were compiled with.
+    pub(crate) fn from_shared(nodes: Vec<Arc<SchemaNode>>) -> SchemaNode {
+        SchemaNode {
+            location: Location::new(),
+            absolute_path: None,
+            validators: NodeValidators::Array {
+                validators: nodes
+                    .into_iter()
+                    .map(|node| Box::new(SharedNode(node)) as BoxedValidator)
+                    .collect(),
+            },
+        }
+    }
+
     pub(crate) fn validators(&self) -> impl ExactSizeIterator<Item = &BoxedValidator> {
         match &self.validators {
             NodeValidators::Boolean { validator } => {
@@ -135,6 +184,54 @@ impl SchemaNode {
         }
     }
 
+    /// Evaluate each keyword of this node separately and yield the resulting output units.
+    ///
+    /// Keywords that pass contribute their annotations and keywords that fail contribute their
+    /// errors, so both kinds are interleaved in keyword order. Keywords are evaluated lazily.
+    pub(crate) fn iter_units<'a>(
+        &'a self,
+        instance: &'a Value,
+    ) -> Box<dyn Iterator<Item = Unit<'a>> + 'a> {
+        fn into_units(application: PartialApplication<'_>) -> VecDeque<Unit<'_>> {
+            match application {
+                PartialApplication::Valid { child_results, .. } => {
+                    child_results.into_iter().map(Unit::Annotation).collect()
+                }
+                PartialApplication::Invalid { child_results, .. } => {
+                    child_results.into_iter().map(Unit::Error).collect()
+                }
+            }
+        }
+
+        if let NodeValidators::Keyword(kvals) = &self.validators {
+            let annotations = kvals.unmatched_keywords.as_ref().map(|annotations| {
+                Unit::Annotation(self.annotation_at(&LazyLocation::new(), annotations.into()))
+            });
+            Box::new(
+                annotations
+                    .into_iter()
+                    .chain(
+                        kvals
+                            .validators
+                            .iter()
+                            .flat_map(move |(keyword, validator)| {
+                                into_units(self.apply_subschemas(
+                                    instance,
+                                    &LazyLocation::new(),
+                                    std::iter::once((keyword, validator)),
+                                    None,
+                                ))
+                            }),
+                    ),
+            )
+        } else {
+            match self.apply_rooted(instance, &LazyLocation::new()) {
+                BasicOutput::Valid(units) => Box::new(units.into_iter().map(Unit::Annotation)),
+                BasicOutput::Invalid(units) => Box::new(units.into_iter().map(Unit::Error)),
+            }
+        }
+    }
+
     /// Create an error output which is marked as occurring at this schema node
     pub(crate) fn error_at(
         &self,
@@ -256,6 +353,14 @@ impl SchemaNode {
     }
 }
 
+impl SchemaNode {
+    /// Run `evaluate` for one of this node's keywords, recording it when profiling is enabled.
+    #[inline(always)]
+    fn profiled<T>(&self, keyword: &Keyword, evaluate: impl FnOnce() -> T) -> T {
+        profile::record(|| self.location.join(keyword), evaluate)
+    }
+}
+
 impl Validate for SchemaNode {
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         match &self.validators {
@@ -292,8 +397,8 @@ impl Validate for SchemaNode {
     ) -> Result<(), ValidationError<'i>> {
         match &self.validators {
             NodeValidators::Keyword(kvs) => {
-                for (_, validator) in &kvs.validators {
-                    validator.validate(instance, location)?;
+                for (keyword, validator) in &kvs.validators {
+                    self.profiled(keyword, || validator.validate(instance, location))?;
                 }
             }
             NodeValidators::Array { validators } => {
@@ -320,11 +425,14 @@ impl Validate for SchemaNode {
             // implementation. Validators at the leaf of a tree are all single node validators so
             // this optimization can have significant cumulative benefits
             NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
-                kvs.validators[0].1.is_valid(instance)
+                let (keyword, validator) = &kvs.validators[0];
+                self.profiled(keyword, || validator.is_valid(instance))
             }
+            // Run only validators that may fail for this instance type, so the type is matched
+            // once instead of inside every validator
             NodeValidators::Keyword(kvs) => {
-                for (_, v) in &kvs.validators {
-                    if !v.is_valid(instance) {
+                for (keyword, validator) in kvs.applicable(instance) {
+                    if !self.profiled(keyword, || validator.is_valid(instance)) {
                         return false;
                     }
                 }
@@ -336,6 +444,51 @@ impl Validate for SchemaNode {
         }
     }
 
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        match &self.validators {
+            NodeValidators::Keyword(kvs) => {
+                let mut is_valid = true;
+                for (keyword, validator) in &kvs.validators {
+                    let valid = validator.trace(instance, location, callback);
+                    callback(TraceRecord {
+                        keyword_location: self.location.join(keyword),
+                        instance_location: location.into(),
+                        valid,
+                    });
+                    is_valid &= valid;
+                }
+                is_valid
+            }
+            NodeValidators::Array { validators } => {
+                let mut is_valid = true;
+                for (idx, validator) in validators.iter().enumerate() {
+                    let valid = validator.trace(instance, location, callback);
+                    callback(TraceRecord {
+                        keyword_location: self.location.join(idx),
+                        instance_location: location.into(),
+                        valid,
+                    });
+                    is_valid &= valid;
+                }
+                is_valid
+            }
+            NodeValidators::Boolean { validator } => {
+                let valid = validator.is_none();
+                callback(TraceRecord {
+                    keyword_location: self.location.clone(),
+                    instance_location: location.into(),
+                    valid,
+                });
+                valid
+            }
+        }
+    }
+
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         match self.validators {
             NodeValidators::Array { ref validators } => {
@@ -355,6 +508,7 @@ impl Validate for SchemaNode {
                 let KeywordValidators {
                     ref unmatched_keywords,
                     ref validators,
+                    ..
                 } = *kvals;
                 let annotations: Option<Annotations<'a>> =
                     unmatched_keywords.as_ref().map(Annotations::from);
@@ -369,6 +523,35 @@ impl Validate for SchemaNode {
     }
 }
 
+/// A node owned by another validator.
+struct SharedNode(Arc<SchemaNode>);
+
+impl Validate for SharedNode {
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        self.0.iter_errors(instance, location)
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.0.is_valid(instance)
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        self.0.validate(instance, location)
+    }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        self.0.apply_rooted(instance, location).into()
+    }
+
+    fn descend(&self, _: &Value, _: &str) -> Option<Descent<'_>> {
+        Some(Descent::Here(&self.0))
+    }
+}
+
 enum NodeValidatorsIter<'a> {
     NoValidator,
     BooleanValidators(std::iter::Once<&'a BoxedValidator>),
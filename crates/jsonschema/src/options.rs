@@ -5,16 +5,21 @@ use crate::{
         DEFAULT_CONTENT_ENCODING_CHECKS_AND_CONVERTERS,
     },
     content_media_type::{ContentMediaTypeCheckType, DEFAULT_CONTENT_MEDIA_TYPE_CHECKS},
-    keywords::{custom::KeywordFactory, format::Format},
-    paths::Location,
+    keywords::{
+        custom::{ContextualKeywordFactory, KeywordContext, KeywordFactory},
+        format::Format,
+    },
+    paths::{InstanceLocationStyle, Location},
     retriever::DefaultRetriever,
     Keyword, ValidationError, Validator,
 };
 use ahash::AHashMap;
-use referencing::{uri, Draft, Resource, Retrieve};
+use referencing::{uri, Draft, Registry, RegistryOptions, Resource, Retrieve, Uri};
 use serde_json::Value;
 use std::{fmt, sync::Arc};
 
+const DEFAULT_MAX_INSTANCE_DEPTH: usize = 1024;
+
 /// Configuration options for JSON Schema validation.
 #[derive(Clone)]
 pub struct ValidationOptions {
@@ -26,10 +31,25 @@ pub struct ValidationOptions {
     pub(crate) retriever: Arc<dyn Retrieve>,
     /// Additional resources that should be addressable during validation.
     pub(crate) resources: AHashMap<String, Resource>,
+    /// URI schemes that external resources may be retrieved with.
+    allowed_schemes: Option<Vec<String>>,
+    /// Prefixes of external resource URIs redirected to other locations.
+    rewrites: Vec<(String, String)>,
     formats: AHashMap<String, Arc<dyn Format>>,
     validate_formats: Option<bool>,
     pub(crate) validate_schema: bool,
     ignore_unknown_formats: bool,
+    strict_formats: bool,
+    forbid_comments: bool,
+    validate_content: bool,
+    apply_ref_siblings: Option<bool>,
+    report_branch_errors: bool,
+    anchor_patterns: bool,
+    ignore_string_case: bool,
+    max_instance_depth: usize,
+    regex_size_limit: Option<usize>,
+    regex_dfa_size_limit: Option<usize>,
+    instance_location_style: InstanceLocationStyle,
     keywords: AHashMap<String, Arc<dyn KeywordFactory>>,
 }
 
@@ -41,10 +61,23 @@ impl Default for ValidationOptions {
             content_encoding_checks_and_converters: AHashMap::default(),
             retriever: Arc::new(DefaultRetriever),
             resources: AHashMap::default(),
+            allowed_schemes: None,
+            rewrites: Vec::new(),
             formats: AHashMap::default(),
             validate_formats: None,
             validate_schema: true,
             ignore_unknown_formats: true,
+            strict_formats: false,
+            forbid_comments: false,
+            validate_content: false,
+            apply_ref_siblings: None,
+            report_branch_errors: false,
+            anchor_patterns: false,
+            ignore_string_case: false,
+            max_instance_depth: DEFAULT_MAX_INSTANCE_DEPTH,
+            regex_size_limit: None,
+            regex_dfa_size_limit: None,
+            instance_location_style: InstanceLocationStyle::Pointer,
             keywords: AHashMap::default(),
         }
     }
@@ -68,9 +101,11 @@ impl ValidationOptions {
                 Ok(draft) => Ok(draft),
                 Err(referencing::Error::UnknownSpecification { specification }) => {
                     // Try to retrieve the specification and detect its draft
-                    if let Ok(Ok(retrieved)) = uri::from_str(&specification)
-                        .map(|uri| self.retriever.retrieve(&uri.borrow()))
-                    {
+                    let retrieved = uri::from_str(&self.rewritten(&specification))
+                        .ok()
+                        .filter(|uri| self.allows_scheme(uri.scheme().as_str()))
+                        .and_then(|uri| self.retriever.retrieve(&uri.borrow()).ok());
+                    if let Some(retrieved) = retrieved {
                         Ok(default.detect(&retrieved)?)
                     } else {
                         Err(referencing::Error::UnknownSpecification { specification }.into())
@@ -98,6 +133,39 @@ impl ValidationOptions {
     pub fn build(&self, schema: &Value) -> Result<Validator, ValidationError<'static>> {
         compiler::build_validator(self.clone(), schema)
     }
+    /// Build a JSON Schema validator for the resource stored in `registry` under `uri`.
+    ///
+    /// Unless set explicitly, the draft is the one the registry detected for the resource.
+    /// References are resolved against all resources in `registry`, and the options'
+    /// additional resources are not used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `uri` is not in the registry or the schema fails to compile.
+    pub fn build_from_registry(
+        &self,
+        registry: &Registry,
+        uri: &Uri<String>,
+    ) -> Result<Validator, ValidationError<'static>> {
+        compiler::build_registry_validator(self.clone(), registry, uri, None)
+    }
+    /// Build a JSON Schema validator rooted at the subschema that declares `anchor` within the
+    /// resource stored in `registry` under `base`.
+    ///
+    /// Drafts before 2019-09 declare anchors with a fragment-only `$id` or `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `base` is not in the registry, `anchor` is not defined in it, or the
+    /// subschema fails to compile.
+    pub fn build_from_anchor(
+        &self,
+        registry: &Registry,
+        base: &Uri<String>,
+        anchor: &str,
+    ) -> Result<Validator, ValidationError<'static>> {
+        compiler::build_registry_validator(self.clone(), registry, base, Some(anchor))
+    }
     /// Sets the JSON Schema draft version.
     ///
     /// ```rust
@@ -148,6 +216,94 @@ impl ValidationOptions {
         self.retriever = Arc::new(retriever);
         self
     }
+    /// Restrict URI schemes of external resources that may be retrieved.
+    ///
+    /// References to resources that are not added via [`ValidationOptions::with_resource`] and
+    /// use a scheme outside of this list fail to compile with
+    /// [`referencing::Error::SchemeNotAllowed`], without calling the retriever. Schemes are
+    /// compared case-insensitively. All schemes are allowed by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"$ref": "file:///etc/schema.json"});
+    /// let result = jsonschema::options()
+    ///     .with_allowed_schemes(["https"])
+    ///     .build(&schema);
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn with_allowed_schemes(
+        &mut self,
+        schemes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> &mut Self {
+        self.allowed_schemes = Some(
+            schemes
+                .into_iter()
+                .map(|scheme| scheme.as_ref().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+    /// Redirect external resources whose URI starts with `from` to the same path under `to`.
+    ///
+    /// Resources are still addressed by their original URIs within schemas. This also applies to
+    /// `$ref`s to the bundled meta-schemas. See [`referencing::RegistryOptions::rewrite`] for
+    /// details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::Resource;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"$ref": "https://example.com/schemas/string"});
+    /// let validator = jsonschema::options()
+    ///     .with_rewrite("https://example.com/schemas/", "file:///mirror/")
+    ///     .with_resource(
+    ///         "file:///mirror/string",
+    ///         Resource::from_contents(json!({"type": "string"})).expect("Invalid resource"),
+    ///     )
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("foo")));
+    /// assert!(!validator.is_valid(&json!(42)));
+    /// ```
+    pub fn with_rewrite(&mut self, from: impl Into<String>, to: impl Into<String>) -> &mut Self {
+        self.rewrites.push((from.into(), to.into()));
+        self
+    }
+    /// Apply the first rewrite rule matching `uri`.
+    fn rewritten(&self, uri: &str) -> String {
+        for (from, to) in &self.rewrites {
+            if let Some(rest) = uri.strip_prefix(from.as_str()) {
+                return format!("{to}{rest}");
+            }
+        }
+        uri.to_string()
+    }
+    fn allows_scheme(&self, scheme: &str) -> bool {
+        self.allowed_schemes.as_ref().map_or(true, |allowed| {
+            allowed
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+        })
+    }
+    pub(crate) fn registry_options(&self, draft: Draft) -> RegistryOptions {
+        let options = self.rewrites.iter().fold(
+            Registry::options()
+                .retriever(Box::new(Arc::clone(&self.retriever)))
+                .draft(draft),
+            |options, (from, to)| options.rewrite(from, to),
+        );
+        match &self.allowed_schemes {
+            Some(schemes) => options.allowed_schemes(schemes),
+            None => options,
+        }
+    }
     /// Remove support for a specific content media type validation.
     pub fn without_content_media_type_support(&mut self, media_type: &'static str) -> &mut Self {
         self.content_media_type_checks.insert(media_type, None);
@@ -376,6 +532,348 @@ impl ValidationOptions {
     pub(crate) const fn are_unknown_formats_ignored(&self) -> bool {
         self.ignore_unknown_formats
     }
+    /// Set whether to fail compilation on unknown formats.
+    ///
+    /// Unlike [`ValidationOptions::should_ignore_unknown_formats`], this also applies when
+    /// `format` is an annotation, as it is by default in Draft 2019-09 and 2020-12. It catches
+    /// typos like `date-tme` in schemas. Formats registered via
+    /// [`ValidationOptions::with_format`] are known. Disabled by default, as the specification
+    /// allows unknown formats.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"created": {"format": "date-tme"}}});
+    /// let error = jsonschema::options()
+    ///     .should_use_strict_formats(true)
+    ///     .build(&schema)
+    ///     .expect_err("Unknown format");
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Unknown format: 'date-tme'. Adjust configuration to ignore unrecognized formats"
+    /// );
+    /// ```
+    pub fn should_use_strict_formats(&mut self, yes: bool) -> &mut Self {
+        self.strict_formats = yes;
+        self
+    }
+    pub(crate) const fn are_formats_strict(&self) -> bool {
+        self.strict_formats
+    }
+    /// Set whether to assert content keywords in Draft 2019-09 and 2020-12.
+    ///
+    /// By default, `contentMediaType`, `contentEncoding`, and `contentSchema` are annotations
+    /// in these drafts. When enabled, string instances are checked against `contentMediaType`
+    /// and `contentEncoding`, and if the media type is `application/json`, the decoded
+    /// document is validated against `contentSchema`.
+    ///
+    /// Errors within the decoded document have the instance path of the string followed by
+    /// the path inside the document, e.g. `/payload/age`. The decoded document is not a part
+    /// of the instance, so [`ValidationError::instance_fragment`] returns `None` for them,
+    /// while [`ValidationError::instance`] holds the failing decoded value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "contentMediaType": "application/json",
+    ///     "contentEncoding": "base64",
+    ///     "contentSchema": {"required": ["name"]}
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .should_validate_content(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// // {"name": "Alice"}
+    /// assert!(validator.is_valid(&json!("eyJuYW1lIjogIkFsaWNlIn0=")));
+    /// // {}
+    /// assert!(!validator.is_valid(&json!("e30=")));
+    /// ```
+    pub fn should_validate_content(&mut self, yes: bool) -> &mut Self {
+        self.validate_content = yes;
+        self
+    }
+
+    pub(crate) const fn validates_content(&self) -> bool {
+        self.validate_content
+    }
+    /// Set whether to reject schemas containing `$comment` keywords.
+    ///
+    /// `$comment` never affects validation. By default, it is allowed anywhere in a schema.
+    /// Set to `true` to fail validator construction if any `$comment` is present. The error
+    /// points to the first occurrence and lists all of them in its message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"$comment": "Internal note", "type": "string"});
+    /// let result = jsonschema::options()
+    ///     .should_forbid_comments(true)
+    ///     .build(&schema);
+    /// assert!(result.is_err());
+    /// ```
+    pub fn should_forbid_comments(&mut self, yes: bool) -> &mut Self {
+        self.forbid_comments = yes;
+        self
+    }
+
+    pub(crate) const fn are_comments_forbidden(&self) -> bool {
+        self.forbid_comments
+    }
+    /// Set whether keywords adjacent to `$ref` are applied.
+    ///
+    /// Draft 4, 6, and 7 ignore all keywords next to `$ref`, while Draft 2019-09 and 2020-12
+    /// apply them alongside the referenced schema. This method overrides the default,
+    /// enforcing one behavior regardless of draft. It is useful for legacy schemas written
+    /// with the other semantics in mind.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "$schema": "http://json-schema.org/draft-07/schema#",
+    ///     "definitions": {"number": {"type": "number"}},
+    ///     "$ref": "#/definitions/number",
+    ///     "maximum": 10
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .should_apply_ref_siblings(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// assert!(!validator.is_valid(&json!(42)));
+    /// ```
+    #[inline]
+    pub fn should_apply_ref_siblings(&mut self, yes: bool) -> &mut Self {
+        self.apply_ref_siblings = Some(yes);
+        self
+    }
+    pub(crate) const fn applies_ref_siblings(&self) -> Option<bool> {
+        self.apply_ref_siblings
+    }
+    /// Set whether `anyOf` and `oneOf` errors include the first error of each subschema.
+    ///
+    /// By default, these errors only state that no subschema matched. When enabled, the
+    /// `context` field of [`ValidationErrorKind::AnyOf`] and [`ValidationErrorKind::OneOfNotValid`]
+    /// explains why each subschema failed. Collecting it re-evaluates every subschema on failure,
+    /// so it is disabled by default. Successful validation is not affected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::error::ValidationErrorKind;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"anyOf": [{"type": "string"}, {"minimum": 10}]});
+    /// let validator = jsonschema::options()
+    ///     .should_report_branch_errors(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// let instance = json!(5);
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// let ValidationErrorKind::AnyOf { context } = &error.kind else {
+    ///     panic!("Unexpected error kind");
+    /// };
+    /// assert_eq!(context[0].to_string(), r#"5 is not of type "string""#);
+    /// assert_eq!(context[1].to_string(), "5 is less than the minimum of 10");
+    /// ```
+    ///
+    /// [`ValidationErrorKind::AnyOf`]: crate::error::ValidationErrorKind::AnyOf
+    /// [`ValidationErrorKind::OneOfNotValid`]: crate::error::ValidationErrorKind::OneOfNotValid
+    pub fn should_report_branch_errors(&mut self, yes: bool) -> &mut Self {
+        self.report_branch_errors = yes;
+        self
+    }
+    pub(crate) const fn reports_branch_errors(&self) -> bool {
+        self.report_branch_errors
+    }
+    /// Set whether `pattern` must match the whole string.
+    ///
+    /// Per the specification, `pattern` matches if the regular expression matches anywhere in
+    /// the string, so `{"pattern": "a"}` accepts `"bab"`. Some tools implicitly anchor patterns
+    /// instead. When enabled, every `pattern` is wrapped in `^(?:...)$` to match that behavior.
+    /// `patternProperties` is not affected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"pattern": "a"});
+    /// let validator = jsonschema::options()
+    ///     .should_anchor_patterns(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("a")));
+    /// assert!(!validator.is_valid(&json!("bab")));
+    /// ```
+    pub fn should_anchor_patterns(&mut self, yes: bool) -> &mut Self {
+        self.anchor_patterns = yes;
+        self
+    }
+    pub(crate) const fn anchors_patterns(&self) -> bool {
+        self.anchor_patterns
+    }
+    /// Set whether `enum` and `const` compare strings case-insensitively.
+    ///
+    /// Per the specification, strings are equal only if they are identical, so `"GET"` does not
+    /// match `{"enum": ["get"]}`. When enabled, string instances match `enum` variants and
+    /// `const` values that differ only in case, as defined by the Unicode lowercase mapping of
+    /// each character. Strings nested in arrays or objects are still compared exactly, and
+    /// `pattern` is not affected.
+    /// Error messages list the values as written in the schema.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"enum": ["get", "post"]});
+    /// let validator = jsonschema::options()
+    ///     .should_ignore_string_case(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("GET")));
+    /// assert_eq!(
+    ///     validator.validate(&json!("PUT")).expect_err("Should fail").to_string(),
+    ///     r#""PUT" is not one of ["get","post"]"#
+    /// );
+    /// ```
+    pub fn should_ignore_string_case(&mut self, yes: bool) -> &mut Self {
+        self.ignore_string_case = yes;
+        self
+    }
+    pub(crate) const fn ignores_string_case(&self) -> bool {
+        self.ignore_string_case
+    }
+    /// Set the maximum nesting depth of instances accepted for validation.
+    ///
+    /// Validation can only recurse without bound through references that lead back into the
+    /// schema, e.g. `{"items": {"$ref": "#"}}`. Once more than `depth` of them are evaluated
+    /// within each other, validation fails with [`ValidationErrorKind::MaxDepth`] at the value
+    /// reached, which protects against stack overflows on deeply nested untrusted input. If a
+    /// schema applies one such reference per nesting level, like the one above, this is the
+    /// maximum depth of the instance, where the root instance has depth 0. Values validation
+    /// doesn't recurse into are not limited. The default limit is 1024, and `usize::MAX`
+    /// disables the check.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"items": {"$ref": "#"}});
+    /// let validator = jsonschema::options()
+    ///     .with_max_instance_depth(2)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// assert!(validator.is_valid(&json!([[1]])));
+    /// assert!(!validator.is_valid(&json!([[[1]]])));
+    /// ```
+    ///
+    /// [`ValidationErrorKind::MaxDepth`]: crate::error::ValidationErrorKind::MaxDepth
+    pub fn with_max_instance_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_instance_depth = depth;
+        self
+    }
+    pub(crate) const fn max_instance_depth(&self) -> usize {
+        self.max_instance_depth
+    }
+    /// Set the approximate size limit in bytes of each compiled regular expression from
+    /// `pattern`, `patternProperties`, and `unevaluatedProperties`.
+    ///
+    /// Patterns exceeding the limit fail compilation instead of consuming large amounts of
+    /// memory, which protects against pathological patterns in untrusted schemas. By default,
+    /// the limit of the underlying `regex` crate applies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"pattern": "\\w{1000}"});
+    /// let error = jsonschema::options()
+    ///     .with_regex_size_limit(10_000)
+    ///     .build(&schema)
+    ///     .expect_err("Pattern is too large");
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Regular expression exceeds the size limit of 10000 bytes"
+    /// );
+    /// ```
+    pub fn with_regex_size_limit(&mut self, bytes: usize) -> &mut Self {
+        self.regex_size_limit = Some(bytes);
+        self
+    }
+    pub(crate) const fn regex_size_limit(&self) -> Option<usize> {
+        self.regex_size_limit
+    }
+    /// Set the approximate size limit in bytes of the cache used by the lazy DFA of each
+    /// compiled regular expression.
+    ///
+    /// Unlike [`ValidationOptions::with_regex_size_limit`], exceeding this limit doesn't fail
+    /// compilation. Matching falls back to slower engines instead, bounding memory use at
+    /// validation time.
+    pub fn with_regex_dfa_size_limit(&mut self, bytes: usize) -> &mut Self {
+        self.regex_dfa_size_limit = Some(bytes);
+        self
+    }
+    pub(crate) const fn regex_dfa_size_limit(&self) -> Option<usize> {
+        self.regex_dfa_size_limit
+    }
+    /// Set how instance locations are rendered in output units and by
+    /// [`Validator::display_location`].
+    ///
+    /// The style applies to the `instanceLocation` of serialized output units and to
+    /// [`Validator::display_location`], which renders e.g. [`ValidationError::instance_path`].
+    /// Locations themselves are always plain JSON Pointers. Defaults to
+    /// [`InstanceLocationStyle::Pointer`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::paths::InstanceLocationStyle;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"user": {"properties": {"age": {"minimum": 0}}}}});
+    /// let validator = jsonschema::options()
+    ///     .with_instance_location_style(InstanceLocationStyle::UriFragment)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// let instance = json!({"user": {"age": -1}});
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(error.instance_path.as_str(), "/user/age");
+    /// assert_eq!(
+    ///     validator.display_location(&error.instance_path).to_string(),
+    ///     "#/user/age"
+    /// );
+    ///
+    /// let output = serde_json::to_value(validator.apply(&instance).basic())
+    ///     .expect("Serializable");
+    /// assert_eq!(output["errors"][0]["instanceLocation"], "#/user/age");
+    /// ```
+    pub fn with_instance_location_style(&mut self, style: InstanceLocationStyle) -> &mut Self {
+        self.instance_location_style = style;
+        self
+    }
+    pub(crate) const fn instance_location_style(&self) -> InstanceLocationStyle {
+        self.instance_location_style
+    }
     /// Register a custom keyword validator.
     ///
     /// ## Example
@@ -447,6 +945,28 @@ impl ValidationOptions {
         self
     }
 
+    /// Register a custom keyword validator whose factory receives a [`KeywordContext`].
+    ///
+    /// Works like [`ValidationOptions::with_keyword`], but the factory also gets the base URI of
+    /// the schema resource containing the keyword, e.g. to resolve URIs relative to it. See
+    /// [`KeywordContext`] for an example.
+    pub fn with_contextual_keyword<N, F>(&mut self, name: N, factory: F) -> &mut Self
+    where
+        N: Into<String>,
+        F: for<'a> Fn(
+                &'a serde_json::Map<String, Value>,
+                &'a Value,
+                KeywordContext,
+            ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.keywords
+            .insert(name.into(), Arc::new(ContextualKeywordFactory(factory)));
+        self
+    }
+
     pub(crate) fn get_keyword_factory(&self, name: &str) -> Option<&Arc<dyn KeywordFactory>> {
         self.keywords.get(name)
     }
@@ -473,6 +993,63 @@ mod tests {
         s.ends_with("42!")
     }
 
+    #[test]
+    fn comments_are_ignored() {
+        let schema = json!({
+            "$comment": "Top-level",
+            "type": "object",
+            "properties": {
+                "name": {"$comment": "Nested", "type": "string"}
+            }
+        });
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        crate::tests_util::is_valid_with(&validator, &json!({"name": "Alice"}));
+        crate::tests_util::is_not_valid_with(&validator, &json!({"name": 42}));
+        // `$comment` is not reported as a validation error
+        let instance = json!({"name": 42});
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].schema_path.as_str(), "/properties/name/type");
+    }
+
+    #[test]
+    fn forbidden_comments() {
+        let schema = json!({
+            "$comment": "Top-level",
+            "properties": {
+                "$comment": {"type": "string"},
+                "name": {"$comment": "Nested", "type": "string"}
+            },
+            "allOf": [{"$comment": "In array"}],
+            "$defs": {"unused": {"$comment": "Unreferenced"}},
+            "const": {"$comment": "Not a keyword"}
+        });
+        let error = crate::options()
+            .should_forbid_comments(true)
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/$comment");
+        assert_eq!(*error.instance, json!("Top-level"));
+        assert_eq!(
+            error.to_string(),
+            "`$comment` is not allowed: /$comment, /$defs/unused/$comment, /allOf/0/$comment, /properties/name/$comment"
+        );
+    }
+
+    #[test]
+    fn comments_are_allowed_by_default() {
+        let schema = json!({"$comment": "Note", "type": "string"});
+        assert!(crate::options().build(&schema).is_ok());
+        assert!(crate::options()
+            .should_forbid_comments(false)
+            .build(&schema)
+            .is_ok());
+        assert!(crate::options()
+            .should_forbid_comments(true)
+            .build(&json!({"type": "string"}))
+            .is_ok());
+    }
+
     #[test]
     fn custom_format() {
         let schema = json!({"type": "string", "format": "custom"});
@@ -484,4 +1061,51 @@ mod tests {
         assert!(!validator.is_valid(&json!("foo")));
         assert!(validator.is_valid(&json!("foo42!")));
     }
+
+    fn ref_with_sibling(schema: &str) -> serde_json::Value {
+        json!({
+            "$schema": schema,
+            "definitions": {"number": {"type": "number"}},
+            "$defs": {"number": {"type": "number"}},
+            "$ref": if schema.contains("draft-07") {
+                "#/definitions/number"
+            } else {
+                "#/$defs/number"
+            },
+            "maximum": 10
+        })
+    }
+
+    #[test]
+    fn ref_siblings_follow_draft_by_default() {
+        let draft7 = ref_with_sibling("http://json-schema.org/draft-07/schema#");
+        let validator = crate::validator_for(&draft7).expect("Valid schema");
+        assert!(validator.is_valid(&json!(42)));
+        assert!(!validator.is_valid(&json!("foo")));
+
+        let draft202012 = ref_with_sibling("https://json-schema.org/draft/2020-12/schema");
+        let validator = crate::validator_for(&draft202012).expect("Valid schema");
+        assert!(!validator.is_valid(&json!(42)));
+        assert!(!validator.is_valid(&json!("foo")));
+        assert!(validator.is_valid(&json!(5)));
+    }
+
+    #[test]
+    fn ref_siblings_override() {
+        let draft7 = ref_with_sibling("http://json-schema.org/draft-07/schema#");
+        let validator = crate::options()
+            .should_apply_ref_siblings(true)
+            .build(&draft7)
+            .expect("Valid schema");
+        assert!(!validator.is_valid(&json!(42)));
+        assert!(validator.is_valid(&json!(5)));
+
+        let draft202012 = ref_with_sibling("https://json-schema.org/draft/2020-12/schema");
+        let validator = crate::options()
+            .should_apply_ref_siblings(false)
+            .build(&draft202012)
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!(42)));
+        assert!(!validator.is_valid(&json!("foo")));
+    }
 }
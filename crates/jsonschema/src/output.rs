@@ -11,12 +11,16 @@ use std::{
     ops::AddAssign,
 };
 
-use crate::{paths::Location, validator::PartialApplication, ValidationError};
+use crate::{
+    paths::{InstanceLocationStyle, Location},
+    validator::PartialApplication,
+    ValidationError,
+};
 use ahash::AHashMap;
 use referencing::Uri;
 use serde::ser::SerializeMap;
 
-use crate::{node::SchemaNode, paths::LazyLocation, Validator};
+use crate::{paths::LazyLocation, Validator};
 
 /// The output format resulting from the application of a schema.
 ///
@@ -27,21 +31,15 @@ use crate::{node::SchemaNode, paths::LazyLocation, Validator};
 #[derive(Debug, Clone)]
 pub struct Output<'a, 'b> {
     schema: &'a Validator,
-    root_node: &'a SchemaNode,
     instance: &'b serde_json::Value,
 }
 
 impl<'a> Output<'a, '_> {
     pub(crate) const fn new<'c, 'd>(
         schema: &'c Validator,
-        root_node: &'c SchemaNode,
         instance: &'d serde_json::Value,
     ) -> Output<'c, 'd> {
-        Output {
-            schema,
-            root_node,
-            instance,
-        }
+        Output { schema, instance }
     }
 
     /// Indicates whether the schema was valid, corresponds to the "flag" output
@@ -103,8 +101,14 @@ impl<'a> Output<'a, '_> {
     /// ```
     #[must_use]
     pub fn basic(&self) -> BasicOutput<'a> {
-        self.root_node
-            .apply_rooted(self.instance, &LazyLocation::new())
+        let output = self
+            .schema
+            .root
+            .apply_rooted(self.instance, &LazyLocation::new());
+        match self.schema.config.instance_location_style() {
+            InstanceLocationStyle::Pointer => output,
+            style => output.with_instance_location_style(style),
+        }
     }
 }
 
@@ -127,6 +131,23 @@ impl BasicOutput<'_> {
             BasicOutput::Invalid(..) => false,
         }
     }
+
+    fn with_instance_location_style(self, style: InstanceLocationStyle) -> Self {
+        match self {
+            BasicOutput::Valid(units) => BasicOutput::Valid(
+                units
+                    .into_iter()
+                    .map(|unit| unit.with_instance_location_style(style))
+                    .collect(),
+            ),
+            BasicOutput::Invalid(units) => BasicOutput::Invalid(
+                units
+                    .into_iter()
+                    .map(|unit| unit.with_instance_location_style(style))
+                    .collect(),
+            ),
+        }
+    }
 }
 
 impl<'a> From<OutputUnit<Annotations<'a>>> for BasicOutput<'a> {
@@ -191,6 +212,15 @@ impl<'a> FromIterator<BasicOutput<'a>> for PartialApplication<'a> {
     }
 }
 
+/// A single output unit produced by [`Validator::iter_units`], either an annotation or an error.
+#[derive(Debug, PartialEq)]
+pub enum Unit<'a> {
+    /// Annotations produced by a keyword that passed
+    Annotation(OutputUnit<Annotations<'a>>),
+    /// An error produced by a keyword that failed
+    Error(OutputUnit<ErrorDescription>),
+}
+
 /// A reference to a place in a schema and a place in an instance along with some value associated to that place.
 ///
 /// For annotations the value will be an [`Annotations`] and for errors it will be an
@@ -200,6 +230,7 @@ impl<'a> FromIterator<BasicOutput<'a>> for PartialApplication<'a> {
 pub struct OutputUnit<T> {
     keyword_location: Location,
     instance_location: Location,
+    instance_location_style: InstanceLocationStyle,
     absolute_keyword_location: Option<Uri<String>>,
     value: T,
 }
@@ -214,6 +245,7 @@ impl<T> OutputUnit<T> {
         OutputUnit {
             keyword_location,
             instance_location,
+            instance_location_style: InstanceLocationStyle::Pointer,
             absolute_keyword_location,
             value: annotations,
         }
@@ -228,6 +260,7 @@ impl<T> OutputUnit<T> {
         OutputUnit {
             keyword_location,
             instance_location,
+            instance_location_style: InstanceLocationStyle::Pointer,
             absolute_keyword_location,
             value: error,
         }
@@ -250,6 +283,11 @@ impl<T> OutputUnit<T> {
     pub const fn instance_location(&self) -> &Location {
         &self.instance_location
     }
+
+    pub(crate) fn with_instance_location_style(mut self, style: InstanceLocationStyle) -> Self {
+        self.instance_location_style = style;
+        self
+    }
 }
 
 impl OutputUnit<Annotations<'_>> {
@@ -383,7 +421,13 @@ impl serde::Serialize for OutputUnit<Annotations<'_>> {
     {
         let mut map_ser = serializer.serialize_map(Some(4))?;
         map_ser.serialize_entry("keywordLocation", self.keyword_location.as_str())?;
-        map_ser.serialize_entry("instanceLocation", self.instance_location.as_str())?;
+        map_ser.serialize_entry(
+            "instanceLocation",
+            &format_args!(
+                "{}",
+                self.instance_location.display(self.instance_location_style)
+            ),
+        )?;
         if let Some(absolute) = &self.absolute_keyword_location {
             map_ser.serialize_entry("absoluteKeywordLocation", &absolute)?;
         }
@@ -399,7 +443,13 @@ impl serde::Serialize for OutputUnit<ErrorDescription> {
     {
         let mut map_ser = serializer.serialize_map(Some(4))?;
         map_ser.serialize_entry("keywordLocation", self.keyword_location.as_str())?;
-        map_ser.serialize_entry("instanceLocation", self.instance_location.as_str())?;
+        map_ser.serialize_entry(
+            "instanceLocation",
+            &format_args!(
+                "{}",
+                self.instance_location.display(self.instance_location_style)
+            ),
+        )?;
         if let Some(absolute) = &self.absolute_keyword_location {
             map_ser.serialize_entry("absoluteKeywordLocation", &absolute)?;
         }
@@ -407,3 +457,15 @@ impl serde::Serialize for OutputUnit<ErrorDescription> {
         map_ser.end()
     }
 }
+
+impl serde::Serialize for Unit<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Unit::Annotation(unit) => unit.serialize(serializer),
+            Unit::Error(unit) => unit.serialize(serializer),
+        }
+    }
+}
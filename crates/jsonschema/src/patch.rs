@@ -0,0 +1,399 @@
+//! Incremental re-validation of instances that changed at a single location.
+use crate::{
+    node::SchemaNode,
+    paths::LazyLocation,
+    validator::{Descent, Validate},
+    ValidationError, Validator,
+};
+use ahash::AHashMap;
+use serde_json::Value;
+
+/// Errors that appeared or disappeared after a change of an instance.
+///
+/// Returned by [`Validator::validate_patch`].
+#[derive(Debug)]
+pub struct ErrorDelta {
+    /// Errors of the new instance that the previous instance did not have.
+    pub added: Vec<ValidationError<'static>>,
+    /// Errors of the previous instance that the new instance no longer has.
+    pub removed: Vec<ValidationError<'static>>,
+}
+
+impl ErrorDelta {
+    /// Returns `true` if the change did not affect validation errors.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+type Errors = Vec<ValidationError<'static>>;
+
+pub(crate) fn validate_patch(
+    validator: &Validator,
+    previous: &Value,
+    pointer: &str,
+    new: &Value,
+) -> ErrorDelta {
+    let (previous_errors, new_errors) = match parse_pointer(pointer) {
+        Some(segments) => affected_errors(validator, &segments, previous, new),
+        // Not a valid pointer, compare complete results instead
+        None => (
+            validator.iter_errors_owned(previous).collect(),
+            validator.iter_errors_owned(new).collect(),
+        ),
+    };
+    let previous_keys: Vec<_> = previous_errors.iter().map(key).collect();
+    let new_keys: Vec<_> = new_errors.iter().map(key).collect();
+    let added = subtract(new_errors, &new_keys, &previous_keys);
+    let removed = subtract(previous_errors, &previous_keys, &new_keys);
+    ErrorDelta { added, removed }
+}
+
+/// Collect errors of both instances that may depend on the value at `segments`.
+///
+/// Keywords that apply subschemas to separate properties or items are followed only along the
+/// changed path, all other keywords on the path are evaluated as a whole.
+fn affected_errors(
+    validator: &Validator,
+    segments: &[String],
+    previous: &Value,
+    new: &Value,
+) -> (Errors, Errors) {
+    let mut nodes = vec![&*validator.root];
+    let mut previous = Some(previous);
+    let mut new = Some(new);
+    let mut previous_errors = Vec::new();
+    let mut new_errors = Vec::new();
+
+    for depth in 0..=segments.len() {
+        let Some(instance) = new.or(previous) else {
+            break;
+        };
+        let path = &segments[..depth];
+        let segment = segments.get(depth).map(String::as_str);
+        let mut children = Vec::new();
+        let mut seen: Vec<*const SchemaNode> = Vec::new();
+        while let Some(node) = nodes.pop() {
+            // Circular references do not apply new keywords
+            if seen.contains(&std::ptr::addr_of!(*node)) {
+                continue;
+            }
+            seen.push(node);
+            for validator in node.validators() {
+                match segment.and_then(|segment| validator.descend(instance, segment)) {
+                    Some(Descent::Child(child)) => children.extend(child),
+                    Some(Descent::Here(target)) => nodes.push(target),
+                    None => {
+                        if let Some(instance) = previous {
+                            previous_errors.extend(errors_at(&**validator, instance, path));
+                        }
+                        if let Some(instance) = new {
+                            new_errors.extend(errors_at(&**validator, instance, path));
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(segment) = segment {
+            previous = previous.and_then(|instance| child(instance, segment));
+            new = new.and_then(|instance| child(instance, segment));
+        }
+        nodes = children;
+    }
+    (previous_errors, new_errors)
+}
+
+fn errors_at(schema: &dyn Validate, instance: &Value, path: &[String]) -> Errors {
+    fn inner(
+        schema: &dyn Validate,
+        instance: &Value,
+        path: &[String],
+        location: &LazyLocation,
+    ) -> Errors {
+        if let Some((segment, rest)) = path.split_first() {
+            inner(schema, instance, rest, &location.push(segment))
+        } else {
+            schema
+                .iter_errors(instance, location)
+                .map(ValidationError::to_owned)
+                .collect()
+        }
+    }
+    inner(schema, instance, path, &LazyLocation::new())
+}
+
+pub(crate) fn child<'v>(instance: &'v Value, segment: &str) -> Option<&'v Value> {
+    match instance {
+        Value::Object(object) => object.get(segment),
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+/// Split a JSON pointer into unescaped segments.
+pub(crate) fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    Some(
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Errors from `errors` whose keys are not in `other`, counting repeated errors separately.
+fn subtract(errors: Errors, keys: &[ErrorKey], other: &[ErrorKey]) -> Errors {
+    let mut counts = AHashMap::new();
+    for key in other {
+        *counts.entry(key).or_insert(0_usize) += 1;
+    }
+    errors
+        .into_iter()
+        .zip(keys)
+        .filter_map(|(error, key)| match counts.get_mut(key) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                None
+            }
+            _ => Some(error),
+        })
+        .collect()
+}
+
+type ErrorKey = (String, String, String);
+
+fn key(error: &ValidationError<'_>) -> ErrorKey {
+    (
+        error.instance_path.as_str().to_string(),
+        error.schema_path.as_str().to_string(),
+        error.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn keys(errors: &[crate::ValidationError<'_>]) -> Vec<(String, String, String)> {
+        let mut keys: Vec<_> = errors
+            .iter()
+            .map(|error| {
+                (
+                    error.instance_path.to_string(),
+                    error.schema_path.to_string(),
+                    error.to_string(),
+                )
+            })
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn paths(errors: &[(String, String, String)]) -> Vec<(&str, &str)> {
+        errors
+            .iter()
+            .map(|(instance, schema, _)| (instance.as_str(), schema.as_str()))
+            .collect()
+    }
+
+    /// Check the delta against the difference of complete validation results.
+    fn assert_delta(
+        validator: &crate::Validator,
+        previous: &Value,
+        pointer: &str,
+        new: &Value,
+    ) -> (Vec<(String, String, String)>, Vec<(String, String, String)>) {
+        let delta = validator.validate_patch(previous, pointer, new);
+        let previous_errors = keys(&validator.iter_errors(previous).collect::<Vec<_>>());
+        let new_errors = keys(&validator.iter_errors(new).collect::<Vec<_>>());
+        let added: Vec<_> = new_errors
+            .iter()
+            .filter(|error| !previous_errors.contains(error))
+            .cloned()
+            .collect();
+        let removed: Vec<_> = previous_errors
+            .iter()
+            .filter(|error| !new_errors.contains(error))
+            .cloned()
+            .collect();
+        assert_eq!(keys(&delta.added), added);
+        assert_eq!(keys(&delta.removed), removed);
+        (added, removed)
+    }
+
+    fn person() -> crate::Validator {
+        crate::validator_for(&json!({
+            "type": "object",
+            "required": ["name", "age"],
+            "maxProperties": 3,
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "address": {"$ref": "#/$defs/address"}
+            },
+            "$defs": {
+                "address": {
+                    "required": ["city"],
+                    "properties": {"city": {"type": "string"}}
+                }
+            }
+        }))
+        .expect("Invalid schema")
+    }
+
+    #[test]
+    fn single_property_edit() {
+        let validator = person();
+        // `name` stays invalid, only the `age` error changes
+        let previous = json!({"name": "", "age": 30});
+        let new = json!({"name": "", "age": -1});
+        let (added, removed) = assert_delta(&validator, &previous, "/age", &new);
+        assert_eq!(paths(&added), vec![("/age", "/properties/age/minimum")]);
+        assert!(removed.is_empty());
+        let (added, removed) = assert_delta(&validator, &new, "/age", &previous);
+        assert!(added.is_empty());
+        assert_eq!(paths(&removed), vec![("/age", "/properties/age/minimum")]);
+    }
+
+    #[test]
+    fn skips_unaffected_values() {
+        let validator = person();
+        let previous = json!({"name": "", "age": 30, "address": {}});
+        let new = json!({"name": "", "age": -1, "address": {}});
+        let (previous_errors, new_errors) =
+            super::affected_errors(&validator, &["age".to_string()], &previous, &new);
+        assert!(previous_errors.is_empty());
+        assert_eq!(
+            keys(&new_errors),
+            vec![(
+                "/age".to_string(),
+                "/properties/age/minimum".to_string(),
+                "-1 is less than the minimum of 0".to_string()
+            )]
+        );
+    }
+
+    #[test_case(&json!({"name": "Alice", "age": 30}), "/name", &json!({"age": 30}); "removed required property")]
+    #[test_case(&json!({"name": "Alice", "age": 30}), "/extra", &json!({"name": "Alice", "age": 30, "extra": 1}); "added property")]
+    #[test_case(&json!({"name": "Alice", "age": 30, "address": {}}), "/address/city", &json!({"name": "Alice", "age": 30, "address": {"city": 1}}); "through reference")]
+    #[test_case(&json!({"name": "Alice", "age": 30, "address": {"city": 1}}), "/address", &json!({"name": "Alice", "age": 30, "address": {"city": "Paris"}}); "replaced object")]
+    #[test_case(&json!({"name": "Alice", "age": 30}), "", &json!([]); "root")]
+    #[test_case(&json!({"name": "Alice", "age": 30}), "/age", &json!({"name": "Alice", "age": 30}); "no change")]
+    fn person_edits(previous: &Value, pointer: &str, new: &Value) {
+        assert_delta(&person(), previous, pointer, new);
+    }
+
+    #[test_case(
+        &json!({
+            "if": {"properties": {"kind": {"const": "a"}}},
+            "then": {"properties": {"value": {"type": "string"}}}
+        }),
+        &json!({"kind": "b", "value": 1}),
+        "/kind",
+        &json!({"kind": "a", "value": 1});
+        "conditional on sibling"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"type": "integer"}}, "additionalProperties": false}),
+        &json!({"a": 1, "b": 2}),
+        "/c",
+        &json!({"a": 1, "b": 2, "c": 3});
+        "additional properties"
+    )]
+    #[test_case(
+        &json!({"patternProperties": {"^a": {"type": "integer"}}, "unevaluatedProperties": false}),
+        &json!({"ab": 1, "b": 2}),
+        "/ab",
+        &json!({"ab": "x", "b": 2});
+        "pattern properties"
+    )]
+    #[test_case(
+        &json!({"prefixItems": [{"type": "string"}], "items": {"type": "integer"}, "uniqueItems": true}),
+        &json!(["a", 1, 2]),
+        "/2",
+        &json!(["a", 1, 1]);
+        "items"
+    )]
+    #[test_case(
+        &json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "items": [{"type": "string"}],
+            "additionalItems": false
+        }),
+        &json!(["a"]),
+        "/1",
+        &json!(["a", 1]);
+        "additional items"
+    )]
+    #[test_case(
+        &json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {"a": {"$ref": "#/definitions/a", "minimum": 10}},
+            "definitions": {"a": {"type": "integer"}}
+        }),
+        &json!({"a": 1}),
+        "/a",
+        &json!({"a": "x"});
+        "reference siblings in draft 7"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"items": {"anyOf": [{"type": "string"}, {"minimum": 5}]}}}}),
+        &json!({"a": [1, "x", 7]}),
+        "/a/0",
+        &json!({"a": [6, "x", 7]});
+        "nested applicator"
+    )]
+    #[test_case(
+        &json!({"properties": {"a~/b": {"type": "string"}}}),
+        &json!({"a~/b": "x"}),
+        "/a~0~1b",
+        &json!({"a~/b": 1});
+        "escaped pointer"
+    )]
+    #[test_case(
+        &json!({
+            "$id": "https://example.com/root",
+            "$ref": "list",
+            "$defs": {
+                "items": {"$dynamicAnchor": "items", "type": "string"},
+                "list": {
+                    "$id": "list",
+                    "items": {"$dynamicRef": "#items"},
+                    "$defs": {"items": {"$dynamicAnchor": "items"}}
+                }
+            }
+        }),
+        &json!(["a", "b"]),
+        "/1",
+        &json!(["a", 1]);
+        "dynamic reference"
+    )]
+    #[test_case(
+        &json!({"properties": {"value": {"type": "integer"}, "child": {"$ref": "#"}}}),
+        &json!({"child": {"child": {"value": 1}}}),
+        "/child/child/value",
+        &json!({"child": {"child": {"value": "x"}}});
+        "recursive reference"
+    )]
+    fn matches_complete_validation(schema: &Value, previous: &Value, pointer: &str, new: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let (added, removed) = assert_delta(&validator, previous, pointer, new);
+        assert!(!added.is_empty() || !removed.is_empty());
+    }
+
+    #[test]
+    fn combined_validators() {
+        let left = crate::validator_for(&json!({"properties": {"a": {"minimum": 1}}}))
+            .expect("Invalid schema");
+        let right = crate::validator_for(&json!({"properties": {"a": {"maximum": 5}}}))
+            .expect("Invalid schema");
+        let validator = left.and(&right);
+        let (added, _) = assert_delta(&validator, &json!({"a": 3}), "/a", &json!({"a": 9}));
+        assert_eq!(paths(&added), vec![("/a", "/properties/a/maximum")]);
+    }
+}
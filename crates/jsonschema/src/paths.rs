@@ -2,6 +2,7 @@
 use std::{fmt, sync::Arc};
 
 use crate::keywords::Keyword;
+use percent_encoding::{percent_encode, AsciiSet, CONTROLS};
 
 /// A location segment.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -135,6 +136,34 @@ impl From<usize> for LocationSegment<'_> {
     }
 }
 
+/// How instance locations are rendered in validation errors and output units.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InstanceLocationStyle {
+    /// A plain JSON Pointer, e.g. `/user/age`.
+    #[default]
+    Pointer,
+    /// A JSON Pointer in a URI fragment, e.g. `#/user/age`. Characters not allowed in URI
+    /// fragments are percent-encoded as described in RFC 6901, Section 6.
+    UriFragment,
+}
+
+/// Characters that are percent-encoded in the URI fragment representation of a JSON Pointer.
+const FRAGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
 /// A cheap to clone JSON pointer that represents location with a JSON value.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Location(Arc<String>);
@@ -169,6 +198,59 @@ impl Location {
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_bytes()
     }
+    /// Returns `true` if the location points to the root, i.e. has no segments.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Get the location with the last segment removed, or `None` for the root location.
+    #[must_use]
+    pub fn parent(&self) -> Option<Location> {
+        // Slashes within segments are escaped, so the last one separates the last segment
+        let idx = self.0.rfind('/')?;
+        Some(Self(Arc::new(self.0[..idx].to_string())))
+    }
+    /// Get this location appended to `prefix`.
+    pub(crate) fn prefixed(&self, prefix: &Location) -> Location {
+        if prefix.is_empty() {
+            return self.clone();
+        }
+        Self(Arc::new(format!("{}{}", prefix.as_str(), self.as_str())))
+    }
+    /// Get a value that displays the location in the given style.
+    ///
+    /// ```rust
+    /// use jsonschema::paths::{InstanceLocationStyle, Location};
+    ///
+    /// let location = Location::new();
+    /// assert_eq!(location.display(InstanceLocationStyle::UriFragment).to_string(), "#");
+    /// ```
+    pub fn display(&self, style: InstanceLocationStyle) -> impl fmt::Display + '_ {
+        StyledLocation {
+            location: self,
+            style,
+        }
+    }
+}
+
+struct StyledLocation<'a> {
+    location: &'a Location,
+    style: InstanceLocationStyle,
+}
+
+impl fmt::Display for StyledLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            InstanceLocationStyle::Pointer => f.write_str(self.location.as_str()),
+            InstanceLocationStyle::UriFragment => {
+                f.write_str("#")?;
+                for chunk in percent_encode(self.location.as_bytes(), FRAGMENT) {
+                    f.write_str(chunk)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 fn write_escaped_str(buffer: &mut String, value: &str) {
@@ -217,7 +299,7 @@ impl Default for Location {
 
 impl fmt::Display for Location {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.as_str())
+        f.write_str(&self.0)
     }
 }
 
@@ -297,6 +379,41 @@ mod tests {
         assert_eq!(loc.as_str(), "/property/0");
     }
 
+    #[test]
+    fn test_is_empty() {
+        assert!(Location::new().is_empty());
+        assert!(!Location::new().join("").is_empty());
+        assert!(!Location::new().join(0).is_empty());
+    }
+
+    #[test]
+    fn test_parent_of_root() {
+        assert_eq!(Location::new().parent(), None);
+    }
+
+    #[test_case(Location::new().join("a"), ""; "one segment")]
+    #[test_case(Location::new().join(0), ""; "one index")]
+    #[test_case(Location::new().join("a").join(0).join("b"), "/a/0"; "multiple segments")]
+    #[test_case(Location::new().join("a/b").join("c~d"), "/a~1b"; "escaped segments")]
+    #[test_case(Location::new().join("a").join("x/y"), "/a"; "escaped last segment")]
+    #[test_case(Location::new().join("a").join(""), "/a"; "empty last segment")]
+    fn test_parent(location: Location, expected: &str) {
+        let parent = location.parent().expect("Not a root location");
+        assert_eq!(parent.as_str(), expected);
+    }
+
+    #[test]
+    fn test_parent_chain_reaches_root() {
+        let mut location = Location::new().join("a").join(1).join("b~/c");
+        let mut depth = 0;
+        while let Some(parent) = location.parent() {
+            location = parent;
+            depth += 1;
+        }
+        assert_eq!(depth, 3);
+        assert!(location.is_empty());
+    }
+
     #[test]
     fn test_as_bytes() {
         let loc = Location::new().join("test");
@@ -319,6 +436,23 @@ mod tests {
         assert_eq!(loc.as_str(), expected);
     }
 
+    #[test_case(&[], "#"; "root")]
+    #[test_case(&["user", "age"], "#/user/age"; "plain")]
+    #[test_case(&["a b", "c/d~"], "#/a%20b/c~1d~0"; "space and pointer escapes")]
+    #[test_case(&["100%", "\"#{}"], "#/100%25/%22%23%7B%7D"; "reserved characters")]
+    #[test_case(&["ключ"], "#/%D0%BA%D0%BB%D1%8E%D1%87"; "non-ascii")]
+    fn test_uri_fragment_display(segments: &[&str], expected: &str) {
+        let loc = segments
+            .iter()
+            .fold(Location::new(), |loc, segment| loc.join(*segment));
+        let style = InstanceLocationStyle::UriFragment;
+        assert_eq!(loc.display(style).to_string(), expected);
+        assert_eq!(
+            loc.join(0).display(style).to_string(),
+            format!("{expected}/0")
+        );
+    }
+
     #[test_case("/a/b/c", vec![LocationSegment::Property("a"), LocationSegment::Property("b"), LocationSegment::Property("c")]; "location with properties")]
     #[test_case("/1/2/3", vec![LocationSegment::Index(1), LocationSegment::Index(2), LocationSegment::Index(3)]; "location with indices")]
     #[test_case("/a/1/b/2", vec![
@@ -0,0 +1,108 @@
+//! Per-keyword profiling of validation runs, enabled by the `profile` feature.
+//!
+//! Without the feature, [`record`] and [`collect`] only call the given closure, so profiling
+//! hooks compile away entirely.
+use crate::paths::Location;
+
+#[cfg(feature = "profile")]
+pub(crate) use enabled::{collect, record};
+#[cfg(feature = "profile")]
+pub use enabled::{KeywordProfile, Profile};
+
+#[cfg(not(feature = "profile"))]
+#[inline(always)]
+pub(crate) fn record<T>(_: impl FnOnce() -> Location, run: impl FnOnce() -> T) -> T {
+    run()
+}
+
+#[cfg(feature = "profile")]
+mod enabled {
+    use super::Location;
+    use ahash::AHashMap;
+    use std::{
+        cell::RefCell,
+        time::{Duration, Instant},
+    };
+
+    thread_local! {
+        /// Statistics of the validation run in progress on this thread, keyed by schema location.
+        static ACTIVE: RefCell<Option<AHashMap<String, KeywordProfile>>> = const { RefCell::new(None) };
+    }
+
+    /// Statistics of a single keyword collected during one validation run.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct KeywordProfile {
+        /// Location of the keyword within the schema.
+        pub location: Location,
+        /// How many times the keyword was evaluated.
+        pub calls: u64,
+        /// Total time spent evaluating the keyword, including its subschemas.
+        pub time: Duration,
+    }
+
+    /// Per-keyword statistics of the last validation run, returned by
+    /// [`Validator::last_profile`](crate::Validator::last_profile).
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Profile {
+        keywords: Vec<KeywordProfile>,
+        total: Duration,
+    }
+
+    impl Profile {
+        /// Statistics of all evaluated keywords, sorted by their location.
+        #[must_use]
+        pub fn keywords(&self) -> &[KeywordProfile] {
+            &self.keywords
+        }
+        /// Statistics of the keyword at the given schema location, if it was evaluated.
+        #[must_use]
+        pub fn get(&self, location: &str) -> Option<&KeywordProfile> {
+            self.keywords
+                .iter()
+                .find(|keyword| keyword.location.as_str() == location)
+        }
+        /// Total duration of the validation run.
+        #[must_use]
+        pub fn total_time(&self) -> Duration {
+            self.total
+        }
+    }
+
+    pub(crate) fn record<T>(location: impl FnOnce() -> Location, run: impl FnOnce() -> T) -> T {
+        if !ACTIVE.with(|active| active.borrow().is_some()) {
+            return run();
+        }
+        let start = Instant::now();
+        let result = run();
+        let elapsed = start.elapsed();
+        ACTIVE.with(|active| {
+            if let Some(keywords) = active.borrow_mut().as_mut() {
+                let location = location();
+                let keyword = keywords
+                    .entry(location.as_str().to_string())
+                    .or_insert_with(|| KeywordProfile {
+                        location,
+                        calls: 0,
+                        time: Duration::ZERO,
+                    });
+                keyword.calls += 1;
+                keyword.time += elapsed;
+            }
+        });
+        result
+    }
+
+    /// Run `validate` while collecting statistics of every evaluated keyword.
+    pub(crate) fn collect<T>(validate: impl FnOnce() -> T) -> (T, Profile) {
+        // Validators may run other validators, e.g. from custom keywords, keep their runs apart
+        let outer = ACTIVE.with(|active| active.borrow_mut().replace(AHashMap::new()));
+        let start = Instant::now();
+        let result = validate();
+        let total = start.elapsed();
+        let keywords = ACTIVE.with(|active| std::mem::replace(&mut *active.borrow_mut(), outer));
+        let mut keywords: Vec<_> = keywords.unwrap_or_default().into_values().collect();
+        keywords
+            .sort_unstable_by(|left, right| left.location.as_str().cmp(right.location.as_str()));
+        (result, Profile { keywords, total })
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{compiler, ecma, node::SchemaNode, paths::Location, validator::Validate as _};
+use crate::{
+    compiler, keywords::helpers::invalid_pattern_property, node::SchemaNode,
+    validator::Validate as _,
+};
 use ahash::AHashMap;
 use fancy_regex::Regex;
 use serde_json::{Map, Value};
@@ -112,18 +115,18 @@ pub(crate) fn compile_patterns<'a>(
     let mut compiled_patterns = Vec::with_capacity(obj.len());
     for (pattern, subschema) in obj {
         let pctx = kctx.new_at_location(pattern.as_str());
-        if let Ok(Ok(compiled_pattern)) =
-            ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern))
-        {
-            let node = compiler::compile(&pctx, pctx.as_resource_ref(subschema))?;
-            compiled_patterns.push((compiled_pattern, node));
-        } else {
-            return Err(ValidationError::format(
-                Location::new(),
-                kctx.location().clone(),
-                subschema,
-                "regex",
-            ));
+        match ctx.compile_regex(pattern) {
+            Ok(compiled_pattern) => {
+                let node = compiler::compile(&pctx, pctx.as_resource_ref(subschema))?;
+                compiled_patterns.push((compiled_pattern, node));
+            }
+            Err(error) => {
+                return Err(invalid_pattern_property(
+                    pctx.location().clone(),
+                    pattern,
+                    error,
+                ));
+            }
         }
     }
     Ok(compiled_patterns)
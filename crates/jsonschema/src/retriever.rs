@@ -16,7 +16,8 @@ impl Retrieve for DefaultRetriever {
             "http" | "https" => {
                 #[cfg(any(feature = "resolve-http", test))]
                 {
-                    Ok(reqwest::blocking::get(uri.as_str())?.json()?)
+                    let response = reqwest::blocking::get(uri.as_str())?.error_for_status()?;
+                    Ok(serde_json::from_slice(&response.bytes()?)?)
                 }
                 #[cfg(not(any(feature = "resolve-http", test)))]
                 Err("`resolve-http` feature or a custom resolver is required to resolve external schemas via HTTP".into())
@@ -149,6 +150,26 @@ mod tests {
         assert!(!validator.is_valid(&invalid));
     }
 
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_retrieve_invalid_json_from_file() {
+        let mut temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        write!(temp_file, "<html><body>Not Found</body></html>")
+            .expect("Failed to write to temp file");
+
+        let uri = path_to_uri(temp_file.path());
+        let schema = json!({"$ref": uri});
+
+        let error = crate::validator_for(&schema).expect_err("Should fail");
+        let crate::error::ValidationErrorKind::Referencing(
+            referencing::Error::InvalidRetrievedSchema { uri: retrieved, .. },
+        ) = &error.kind
+        else {
+            panic!("Unexpected error: {error}");
+        };
+        assert_eq!(retrieved, &uri);
+    }
+
     #[test]
     fn test_unknown_scheme() {
         let schema = json!({
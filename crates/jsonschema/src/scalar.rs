@@ -0,0 +1,260 @@
+//! A specialized `is_valid` for trivial schemas that only constrain scalars.
+//!
+//! Schemas like `{"type": "integer", "minimum": 0}` are often used to validate large streams of
+//! scalar values. Evaluating them through the schema tree costs a dynamic dispatch per keyword,
+//! which dominates the cost of such cheap checks. Instead, the constraints are checked inline.
+use crate::{
+    compiler::Context,
+    keywords::helpers::{is_integer, saturating_f64},
+    primitive_type::PrimitiveType,
+};
+use num_cmp::NumCmp;
+use referencing::{Draft, Vocabulary};
+use serde_json::{Number, Value};
+
+/// Keywords that may appear in a schema handled by [`ScalarSchema`].
+const KEYWORDS: [&str; 6] = [
+    "$schema",
+    "type",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
+impl Bound {
+    fn new(number: &Number) -> Bound {
+        if let Some(limit) = number.as_u64() {
+            Bound::U64(limit)
+        } else if let Some(limit) = number.as_i64() {
+            Bound::I64(limit)
+        } else {
+            Bound::F64(saturating_f64(number))
+        }
+    }
+}
+
+macro_rules! compare {
+    ($method:ident, $number:expr, $bound:expr) => {{
+        let number = $number;
+        match $bound {
+            Bound::U64(limit) => compare!(@number $method, number, limit),
+            Bound::I64(limit) => compare!(@number $method, number, limit),
+            Bound::F64(limit) => compare!(@number $method, number, limit),
+        }
+    }};
+    (@number $method:ident, $number:expr, $limit:expr) => {
+        if let Some(item) = $number.as_u64() {
+            NumCmp::$method(item, $limit)
+        } else if let Some(item) = $number.as_i64() {
+            NumCmp::$method(item, $limit)
+        } else {
+            NumCmp::$method(saturating_f64($number), $limit)
+        }
+    };
+}
+
+/// Constraints of a schema that can only reject scalars by their type or numeric range.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ScalarSchema {
+    type_: Option<PrimitiveType>,
+    minimum: Option<Bound>,
+    maximum: Option<Bound>,
+    exclusive_minimum: Option<Bound>,
+    exclusive_maximum: Option<Bound>,
+}
+
+impl ScalarSchema {
+    /// Detect whether `schema` can be checked inline.
+    ///
+    /// The detection is conservative: only a single scalar `type` and numeric limits are
+    /// supported, none of them may be overridden by a custom keyword, and any other keyword
+    /// (including `$ref`) disables the specialization.
+    pub(crate) fn compile(ctx: &Context, schema: &Value) -> Option<ScalarSchema> {
+        let Value::Object(schema) = schema else {
+            return None;
+        };
+        // Draft 4 has boolean `exclusiveMinimum` / `exclusiveMaximum` and a stricter `integer`
+        if ctx.draft() == Draft::Draft4
+            || !ctx.has_vocabulary(&Vocabulary::Validation)
+            || !schema.keys().all(|keyword| {
+                KEYWORDS.contains(&keyword.as_str()) && ctx.get_keyword_factory(keyword).is_none()
+            })
+        {
+            return None;
+        }
+        let type_ = match schema.get("type") {
+            None => None,
+            Some(Value::String(name)) => match PrimitiveType::try_from(name.as_str()) {
+                Ok(PrimitiveType::Array | PrimitiveType::Object) | Err(()) => return None,
+                Ok(type_) => Some(type_),
+            },
+            Some(_) => return None,
+        };
+        let bound = |keyword: &str| -> Result<Option<Bound>, ()> {
+            match schema.get(keyword) {
+                None => Ok(None),
+                Some(Value::Number(number)) => Ok(Some(Bound::new(number))),
+                // E.g. boolean `exclusiveMinimum` in Draft 4
+                Some(_) => Err(()),
+            }
+        };
+        Some(ScalarSchema {
+            type_,
+            minimum: bound("minimum").ok()?,
+            maximum: bound("maximum").ok()?,
+            exclusive_minimum: bound("exclusiveMinimum").ok()?,
+            exclusive_maximum: bound("exclusiveMaximum").ok()?,
+        })
+    }
+
+    /// Check a scalar `instance`. Arrays and objects must be validated via the schema tree.
+    #[inline]
+    pub(crate) fn is_valid(&self, instance: &Value) -> bool {
+        if let Some(type_) = self.type_ {
+            let matches = match (type_, instance) {
+                (PrimitiveType::Null, Value::Null)
+                | (PrimitiveType::Boolean, Value::Bool(_))
+                | (PrimitiveType::String, Value::String(_))
+                | (PrimitiveType::Number, Value::Number(_)) => true,
+                (PrimitiveType::Integer, Value::Number(number)) => is_integer(number),
+                _ => false,
+            };
+            if !matches {
+                return false;
+            }
+        }
+        let Value::Number(number) = instance else {
+            return true;
+        };
+        self.minimum
+            .map_or(true, |bound| !compare!(num_lt, number, bound))
+            && self
+                .maximum
+                .map_or(true, |bound| !compare!(num_gt, number, bound))
+            && self
+                .exclusive_minimum
+                .map_or(true, |bound| compare!(num_gt, number, bound))
+            && self
+                .exclusive_maximum
+                .map_or(true, |bound| compare!(num_lt, number, bound))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{validator::Validate, Draft};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn instances() -> Vec<Value> {
+        vec![
+            json!(null),
+            json!(true),
+            json!("foo"),
+            json!(0),
+            json!(-1),
+            json!(42),
+            json!(1.0),
+            json!(1.5),
+            json!(-0.5),
+            json!(u64::MAX),
+            json!(i64::MIN),
+            json!(1e300),
+            json!([1]),
+            json!({"a": 1}),
+        ]
+    }
+
+    #[test_case(&json!({"type": "integer", "minimum": 0}))]
+    #[test_case(&json!({"type": "number", "maximum": 1.5}))]
+    #[test_case(&json!({"type": "integer", "exclusiveMinimum": -1, "exclusiveMaximum": 42}))]
+    #[test_case(&json!({"minimum": 0.5, "maximum": u64::MAX}))]
+    #[test_case(&json!({"exclusiveMaximum": i64::MIN}))]
+    #[test_case(&json!({"type": "string"}))]
+    #[test_case(&json!({"type": "null"}))]
+    #[test_case(&json!({"type": "boolean"}))]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "type": "integer"}))]
+    #[test_case(&json!({}))]
+    fn matches_schema_tree(schema: &Value) {
+        for draft in [
+            Draft::Draft6,
+            Draft::Draft7,
+            Draft::Draft201909,
+            Draft::Draft202012,
+        ] {
+            let validator = crate::options()
+                .with_draft(draft)
+                .build(schema)
+                .expect("Valid schema");
+            assert!(validator.scalar.is_some(), "{schema} in {draft:?}");
+            for instance in instances() {
+                assert_eq!(
+                    validator.is_valid(&instance),
+                    validator.root.is_valid(&instance),
+                    "{schema} in {draft:?} for {instance}"
+                );
+            }
+        }
+    }
+
+    #[test_case(&json!({"type": "integer", "minimum": 0, "multipleOf": 2}), Draft::Draft202012)]
+    #[test_case(&json!({"type": ["integer", "string"]}), Draft::Draft202012)]
+    #[test_case(&json!({"type": "object"}), Draft::Draft202012)]
+    #[test_case(&json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "integer"}}}), Draft::Draft202012)]
+    #[test_case(&json!({"type": "integer", "minimum": 0}), Draft::Draft4)]
+    #[test_case(&json!(true), Draft::Draft202012)]
+    fn not_specialized(schema: &Value, draft: Draft) {
+        let validator = crate::options()
+            .with_draft(draft)
+            .build(schema)
+            .expect("Valid schema");
+        assert!(validator.scalar.is_none());
+    }
+
+    #[test]
+    fn custom_keyword_disables_specialization() {
+        let validator = crate::options()
+            .with_keyword("minimum", |_, _, _| Ok(Box::new(AlwaysValid)))
+            .build(&json!({"type": "integer", "minimum": 0}))
+            .expect("Valid schema");
+        assert!(validator.scalar.is_none());
+        assert!(validator.is_valid(&json!(-1)));
+    }
+
+    struct AlwaysValid;
+
+    impl crate::Keyword for AlwaysValid {
+        fn validate<'i>(
+            &self,
+            _: &'i Value,
+            _: &crate::paths::LazyLocation,
+        ) -> Result<(), crate::ValidationError<'i>> {
+            Ok(())
+        }
+        fn is_valid(&self, _: &Value) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn subschema_validator() {
+        let validator =
+            crate::validator_for(&json!({"$defs": {"a": {"type": "integer", "minimum": 0}}}))
+                .expect("Valid schema");
+        assert!(validator.scalar.is_none());
+        let sub = validator
+            .compile_subschema("/$defs/a")
+            .expect("Valid pointer");
+        assert!(sub.scalar.is_some());
+        assert!(sub.is_valid(&json!(1)));
+        assert!(!sub.is_valid(&json!(-1)));
+    }
+}
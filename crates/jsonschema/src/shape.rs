@@ -0,0 +1,536 @@
+//! Language-agnostic description of the data a schema accepts, e.g. for code generation.
+use crate::{ValidationOptions, Validator};
+use referencing::{Draft, Resolver};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// A simplified, language-agnostic description of the values accepted by a schema.
+///
+/// Built by [`Validator::type_shape`]. Only keywords that define the structure of the data are
+/// considered: `type`, `properties`, `required`, `additionalProperties`, `items`, `prefixItems`,
+/// `additionalItems`, `const`, `enum`, `allOf`, `anyOf`, `oneOf`, `$ref`, and `$dynamicRef`.
+/// Keywords next to a reference are merged into the referenced shape, and keywords next to
+/// `oneOf` or `anyOf` into every variant. Constraints like `minimum` or `pattern` are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeShape {
+    /// Any value, e.g. for `true` or schemas without structural keywords.
+    Any,
+    /// `null`.
+    Null,
+    /// A boolean.
+    Boolean,
+    /// An integer.
+    Integer,
+    /// Any number.
+    Number,
+    /// A string.
+    String,
+    /// A single value from `const`.
+    Const(Value),
+    /// One of the values from `enum`.
+    Enum(Vec<Value>),
+    /// An array with items of the given shape.
+    Array(Box<TypeShape>),
+    /// An array whose first items have the given shapes, from `prefixItems` or the array form
+    /// of `items`.
+    Tuple {
+        /// The shapes of the leading items, in order.
+        prefix: Vec<TypeShape>,
+        /// The shape of any further items, or `None` if there can be no further items.
+        rest: Option<Box<TypeShape>>,
+    },
+    /// An object with the given properties, sorted by name.
+    Object(BTreeMap<String, Property>),
+    /// An object with arbitrary property names and values of the given shape, from
+    /// `additionalProperties` without `properties`.
+    Map(Box<TypeShape>),
+    /// One of several shapes, from `oneOf`, `anyOf`, or `type` with multiple values.
+    Union {
+        /// A required property with a distinct `const` value in every variant, if there is one.
+        tag: Option<String>,
+        /// The possible shapes, in schema order.
+        variants: Vec<TypeShape>,
+    },
+    /// A `$ref` to a schema that is already being described, e.g. in recursive schemas.
+    Recursive(String),
+}
+
+/// A property of an object in a [`TypeShape`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    /// Whether the property is listed in `required`.
+    pub required: bool,
+    /// The shape of the property value.
+    pub shape: TypeShape,
+}
+
+pub(crate) fn type_shape(validator: &Validator) -> TypeShape {
+    let Some(reference) = validator.root_reference.as_deref() else {
+        return TypeShape::Any;
+    };
+    let Ok(resolver) = validator.registry.try_resolver(&validator.base_uri) else {
+        return TypeShape::Any;
+    };
+    let Ok(resolved) = resolver.lookup(reference) else {
+        return TypeShape::Any;
+    };
+    let (root, resolver, draft) = resolved.into_inner();
+    let mut ctx = Context {
+        config: &validator.config,
+        stack: vec![std::ptr::addr_of!(*root)],
+    };
+    describe(root, &resolver, draft, &mut ctx)
+}
+
+struct Context<'c> {
+    config: &'c ValidationOptions,
+    /// The addresses of the `$ref` targets being described.
+    stack: Vec<*const Value>,
+}
+
+/// Describe `schema`.
+fn describe(
+    schema: &Value,
+    resolver: &Resolver<'_>,
+    draft: Draft,
+    ctx: &mut Context<'_>,
+) -> TypeShape {
+    let Value::Object(object) = schema else {
+        return TypeShape::Any;
+    };
+    let draft = draft.detect(schema).unwrap_or(draft);
+    let Ok(resolver) = resolver.in_subresource(draft.create_resource_ref(schema)) else {
+        return TypeShape::Any;
+    };
+    if let Some(value) = object.get("const") {
+        return TypeShape::Const(value.clone());
+    }
+    if let Some(Value::Array(values)) = object.get("enum") {
+        return TypeShape::Enum(values.clone());
+    }
+    let mut shape = match object.get("type") {
+        Some(Value::String(type_)) => describe_type(type_, object, &resolver, draft, ctx),
+        Some(Value::Array(types)) => TypeShape::Union {
+            tag: None,
+            variants: types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|type_| describe_type(type_, object, &resolver, draft, ctx))
+                .collect(),
+        },
+        _ if ["properties", "required", "additionalProperties"]
+            .iter()
+            .any(|keyword| object.contains_key(*keyword)) =>
+        {
+            describe_type("object", object, &resolver, draft, ctx)
+        }
+        _ if object.contains_key("items") || object.contains_key("prefixItems") => {
+            describe_type("array", object, &resolver, draft, ctx)
+        }
+        _ => TypeShape::Any,
+    };
+    for keyword in ["$ref", "$dynamicRef"] {
+        if let Some(Value::String(reference)) = object.get(keyword) {
+            let target = describe_reference(reference, &resolver, ctx);
+            let applies_siblings = ctx
+                .config
+                .applies_ref_siblings()
+                .unwrap_or(draft >= Draft::Draft201909);
+            if !applies_siblings {
+                return target;
+            }
+            shape = merge(shape, target);
+        }
+    }
+    if let Some(Value::Array(subschemas)) = object.get("allOf") {
+        for subschema in subschemas {
+            shape = merge(shape, describe(subschema, &resolver, draft, ctx));
+        }
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(subschemas)) = object.get(keyword) {
+            // Sibling keywords apply to every variant
+            let variants: Vec<_> = subschemas
+                .iter()
+                .map(|subschema| merge(shape.clone(), describe(subschema, &resolver, draft, ctx)))
+                .collect();
+            return TypeShape::Union {
+                tag: find_tag(&variants),
+                variants,
+            };
+        }
+    }
+    shape
+}
+
+fn describe_reference(
+    reference: &str,
+    resolver: &Resolver<'_>,
+    ctx: &mut Context<'_>,
+) -> TypeShape {
+    let Ok(resolved) = resolver.lookup(reference) else {
+        return TypeShape::Any;
+    };
+    let (target, resolver, draft) = resolved.into_inner();
+    let address = std::ptr::addr_of!(*target);
+    if ctx.stack.contains(&address) {
+        return TypeShape::Recursive(reference.to_string());
+    }
+    ctx.stack.push(address);
+    let shape = describe(target, &resolver, draft, ctx);
+    ctx.stack.pop();
+    shape
+}
+
+fn describe_type(
+    type_: &str,
+    object: &Map<String, Value>,
+    resolver: &Resolver<'_>,
+    draft: Draft,
+    ctx: &mut Context<'_>,
+) -> TypeShape {
+    match type_ {
+        "null" => TypeShape::Null,
+        "boolean" => TypeShape::Boolean,
+        "integer" => TypeShape::Integer,
+        "number" => TypeShape::Number,
+        "string" => TypeShape::String,
+        "array" => describe_array(object, resolver, draft, ctx),
+        "object" => {
+            let required: Vec<_> = object
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|required| required.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let mut properties = BTreeMap::new();
+            match (object.get("properties"), object.get("additionalProperties")) {
+                (Some(Value::Object(subschemas)), _) => {
+                    for (name, subschema) in subschemas {
+                        properties.insert(
+                            name.clone(),
+                            Property {
+                                required: required.contains(&name.as_str()),
+                                shape: describe(subschema, resolver, draft, ctx),
+                            },
+                        );
+                    }
+                }
+                (None, Some(values @ Value::Object(_))) if required.is_empty() => {
+                    return TypeShape::Map(Box::new(describe(values, resolver, draft, ctx)));
+                }
+                _ => {}
+            }
+            for name in required {
+                properties
+                    .entry(name.to_string())
+                    .or_insert_with(|| Property {
+                        required: true,
+                        shape: TypeShape::Any,
+                    });
+            }
+            TypeShape::Object(properties)
+        }
+        _ => TypeShape::Any,
+    }
+}
+
+fn describe_array(
+    object: &Map<String, Value>,
+    resolver: &Resolver<'_>,
+    draft: Draft,
+    ctx: &mut Context<'_>,
+) -> TypeShape {
+    let (prefix, rest) = match (object.get("prefixItems"), object.get("items")) {
+        (Some(Value::Array(prefix)), items) if draft >= Draft::Draft202012 => (Some(prefix), items),
+        (_, Some(Value::Array(prefix))) => (Some(prefix), object.get("additionalItems")),
+        (_, items) => (None, items),
+    };
+    let rest = match rest {
+        Some(Value::Bool(false)) => None,
+        Some(rest @ (Value::Object(_) | Value::Bool(_))) => {
+            Some(describe(rest, resolver, draft, ctx))
+        }
+        _ => Some(TypeShape::Any),
+    };
+    match prefix {
+        Some(prefix) => TypeShape::Tuple {
+            prefix: prefix
+                .iter()
+                .map(|item| describe(item, resolver, draft, ctx))
+                .collect(),
+            rest: rest.map(Box::new),
+        },
+        None => TypeShape::Array(Box::new(rest.unwrap_or(TypeShape::Any))),
+    }
+}
+
+/// Combine the shapes of an `allOf`. Only objects are merged, other shapes take precedence over
+/// `Any` or are kept as is.
+fn merge(left: TypeShape, right: TypeShape) -> TypeShape {
+    match (left, right) {
+        (TypeShape::Object(mut left), TypeShape::Object(right)) => {
+            for (name, property) in right {
+                match left.get_mut(&name) {
+                    Some(existing) => {
+                        existing.required |= property.required;
+                        if existing.shape == TypeShape::Any {
+                            existing.shape = property.shape;
+                        }
+                    }
+                    None => {
+                        left.insert(name, property);
+                    }
+                }
+            }
+            TypeShape::Object(left)
+        }
+        (TypeShape::Any, right) => right,
+        (left, _) => left,
+    }
+}
+
+/// Find a property that is required and has a distinct `const` value in every variant.
+fn find_tag(variants: &[TypeShape]) -> Option<String> {
+    let TypeShape::Object(first) = variants.first()? else {
+        return None;
+    };
+    first.keys().find_map(|name| {
+        let mut values = Vec::with_capacity(variants.len());
+        for variant in variants {
+            let TypeShape::Object(properties) = variant else {
+                return None;
+            };
+            match properties.get(name) {
+                Some(Property {
+                    required: true,
+                    shape: TypeShape::Const(value),
+                }) if !values.contains(&value) => values.push(value),
+                _ => return None,
+            }
+        }
+        Some(name.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Property, TypeShape};
+    use serde_json::{json, Value};
+    use std::collections::BTreeMap;
+    use test_case::test_case;
+
+    fn shape(schema: &Value) -> TypeShape {
+        crate::validator_for(schema)
+            .expect("Invalid schema")
+            .type_shape()
+    }
+
+    fn object<const N: usize>(properties: [(&str, bool, TypeShape); N]) -> TypeShape {
+        TypeShape::Object(
+            properties
+                .into_iter()
+                .map(|(name, required, shape)| (name.to_string(), Property { required, shape }))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn nested_object() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "address": {"$ref": "#/$defs/address"}
+            },
+            "required": ["name"],
+            "$defs": {
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "city": {"type": "string"},
+                        "lines": {"type": "array", "items": {"type": "string"}}
+                    }
+                }
+            }
+        });
+        assert_eq!(
+            shape(&schema),
+            object([
+                (
+                    "address",
+                    false,
+                    object([
+                        ("city", false, TypeShape::String),
+                        (
+                            "lines",
+                            false,
+                            TypeShape::Array(Box::new(TypeShape::String))
+                        )
+                    ])
+                ),
+                ("name", true, TypeShape::String),
+            ])
+        );
+    }
+
+    #[test]
+    fn tagged_union() {
+        let schema = json!({
+            "oneOf": [
+                {
+                    "properties": {"kind": {"const": "cat"}, "lives": {"type": "integer"}},
+                    "required": ["kind"]
+                },
+                {
+                    "properties": {"kind": {"const": "dog"}, "good": {"type": "boolean"}},
+                    "required": ["kind", "good"]
+                }
+            ]
+        });
+        assert_eq!(
+            shape(&schema),
+            TypeShape::Union {
+                tag: Some("kind".to_string()),
+                variants: vec![
+                    object([
+                        ("kind", true, TypeShape::Const(json!("cat"))),
+                        ("lives", false, TypeShape::Integer)
+                    ]),
+                    object([
+                        ("good", true, TypeShape::Boolean),
+                        ("kind", true, TypeShape::Const(json!("dog")))
+                    ]),
+                ]
+            }
+        );
+    }
+
+    #[test_case(&json!({"oneOf": [{"type": "string"}, {"type": "integer"}]}); "scalars")]
+    #[test_case(&json!({"oneOf": [{"properties": {"kind": {"const": "a"}}, "required": ["kind"]}, {"properties": {"kind": {"const": "a"}}, "required": ["kind"]}]}); "same tag value")]
+    #[test_case(&json!({"oneOf": [{"properties": {"kind": {"const": "a"}}, "required": ["kind"]}, {"properties": {"kind": {"const": "b"}}}]}); "optional tag")]
+    fn untagged_union(schema: &Value) {
+        let TypeShape::Union { tag, .. } = shape(schema) else {
+            panic!("Expected a union");
+        };
+        assert_eq!(tag, None);
+    }
+
+    #[test]
+    fn recursive() {
+        let schema = json!({
+            "properties": {"children": {"items": {"$ref": "#"}}}
+        });
+        assert_eq!(
+            shape(&schema),
+            object([(
+                "children",
+                false,
+                TypeShape::Array(Box::new(TypeShape::Recursive("#".to_string())))
+            )])
+        );
+    }
+
+    #[test_case(&json!({"type": ["string", "null"]}), &TypeShape::Union { tag: None, variants: vec![TypeShape::String, TypeShape::Null] }; "type array")]
+    #[test_case(&json!({"enum": ["a", "b"]}), &TypeShape::Enum(vec![json!("a"), json!("b")]); "enum")]
+    #[test_case(&json!({"minimum": 5}), &TypeShape::Any; "constraints only")]
+    #[test_case(&json!(true), &TypeShape::Any; "boolean schema")]
+    #[test_case(&json!({"type": "object", "required": ["id"]}), &object([("id", true, TypeShape::Any)]); "required without properties")]
+    fn simple(schema: &Value, expected: &TypeShape) {
+        assert_eq!(&shape(schema), expected);
+    }
+
+    #[test]
+    fn all_of() {
+        let schema = json!({
+            "allOf": [
+                {"properties": {"id": {"type": "integer"}}, "required": ["id"]},
+                {"properties": {"name": {"type": "string"}}}
+            ],
+            "required": ["name"]
+        });
+        assert_eq!(
+            shape(&schema),
+            object([
+                ("id", true, TypeShape::Integer),
+                ("name", true, TypeShape::String)
+            ])
+        );
+    }
+
+    #[test]
+    fn reference_siblings() {
+        let schema = json!({
+            "$ref": "#/$defs/base",
+            "properties": {"name": {"type": "string"}},
+            "$defs": {"base": {"properties": {"id": {"type": "integer"}}, "required": ["id"]}}
+        });
+        assert_eq!(
+            shape(&schema),
+            object([
+                ("id", true, TypeShape::Integer),
+                ("name", false, TypeShape::String)
+            ])
+        );
+    }
+
+    #[test]
+    fn reference_siblings_in_draft_7() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "$ref": "#/definitions/base",
+            "properties": {"name": {"type": "string"}},
+            "definitions": {"base": {"properties": {"id": {"type": "integer"}}}}
+        });
+        assert_eq!(shape(&schema), object([("id", false, TypeShape::Integer)]));
+    }
+
+    #[test]
+    fn union_siblings() {
+        let schema = json!({
+            "properties": {"id": {"type": "integer"}},
+            "required": ["id"],
+            "oneOf": [
+                {"properties": {"kind": {"const": "a"}}, "required": ["kind"]},
+                {"properties": {"kind": {"const": "b"}}, "required": ["kind"]}
+            ]
+        });
+        assert_eq!(
+            shape(&schema),
+            TypeShape::Union {
+                tag: Some("kind".to_string()),
+                variants: vec![
+                    object([
+                        ("id", true, TypeShape::Integer),
+                        ("kind", true, TypeShape::Const(json!("a")))
+                    ]),
+                    object([
+                        ("id", true, TypeShape::Integer),
+                        ("kind", true, TypeShape::Const(json!("b")))
+                    ]),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn dynamic_reference() {
+        let schema = json!({
+            "properties": {"child": {"$dynamicRef": "#node"}},
+            "$defs": {"node": {"$dynamicAnchor": "node", "type": "string"}}
+        });
+        assert_eq!(
+            shape(&schema),
+            object([("child", false, TypeShape::String)])
+        );
+    }
+
+    #[test_case(&json!({"additionalProperties": {"type": "integer"}}), &TypeShape::Map(Box::new(TypeShape::Integer)); "map")]
+    #[test_case(&json!({"type": "object", "additionalProperties": true}), &object([]); "additional properties allowed")]
+    #[test_case(&json!({"properties": {"a": {"type": "null"}}, "additionalProperties": {"type": "integer"}}), &object([("a", false, TypeShape::Null)]); "additional properties with properties")]
+    #[test_case(&json!({"prefixItems": [{"type": "string"}, {"type": "integer"}]}), &TypeShape::Tuple { prefix: vec![TypeShape::String, TypeShape::Integer], rest: Some(Box::new(TypeShape::Any)) }; "prefix items")]
+    #[test_case(&json!({"prefixItems": [{"type": "string"}], "items": false}), &TypeShape::Tuple { prefix: vec![TypeShape::String], rest: None }; "closed tuple")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": [{"type": "string"}], "additionalItems": {"type": "null"}}), &TypeShape::Tuple { prefix: vec![TypeShape::String], rest: Some(Box::new(TypeShape::Null)) }; "items array")]
+    fn collections(schema: &Value, expected: &TypeShape) {
+        assert_eq!(&shape(schema), expected);
+    }
+}
@@ -2,14 +2,24 @@
 //! The main idea is to create a tree from the input JSON Schema. This tree will contain
 //! everything needed to perform such validation in runtime.
 use crate::{
+    compiler::{self, RequiredKeys},
+    defaults, dot,
     error::{error, no_error, ErrorIterator},
+    matches,
     node::SchemaNode,
-    output::{Annotations, ErrorDescription, Output, OutputUnit},
-    paths::LazyLocation,
-    Draft, ValidationError, ValidationOptions,
+    output::{Annotations, BasicOutput, ErrorDescription, Output, OutputUnit, Unit},
+    patch,
+    paths::{LazyLocation, Location},
+    scalar::ScalarSchema,
+    shape::{self, TypeShape},
+    Draft, ErrorDelta, SchemaParseError, ValidationError, ValidationOptions,
 };
+use ahash::AHashSet;
+use referencing::{Registry, Uri};
+#[cfg(feature = "raw-value")]
+use serde_json::value::RawValue;
 use serde_json::Value;
-use std::{collections::VecDeque, sync::Arc};
+use std::{collections::VecDeque, fmt, str::FromStr, sync::Arc};
 
 /// The Validate trait represents a predicate over some JSON value. Some validators are very simple
 /// predicates such as "a value which is a string", whereas others may be much more complex,
@@ -43,6 +53,34 @@ pub(crate) trait Validate: Send + Sync {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>>;
 
+    /// Check `instance` like `is_valid`, reporting a [`TraceRecord`] to `callback` for every
+    /// keyword evaluated along the way.
+    ///
+    /// Leaf validators don't know their own schema location, so the default implementation only
+    /// returns the outcome and the enclosing `SchemaNode` records it. Validators that hold
+    /// subschemas override this to trace into them as well. Unlike `is_valid`, tracing does not
+    /// short-circuit, so every reachable keyword is reported.
+    fn trace(
+        &self,
+        instance: &Value,
+        location: &LazyLocation,
+        callback: &mut dyn FnMut(TraceRecord),
+    ) -> bool {
+        let _ = (location, callback);
+        self.is_valid(instance)
+    }
+
+    /// The subschema this keyword applies to the value at `segment` within `instance`.
+    ///
+    /// Used to evaluate only the subschemas on a changed path in [`Validator::validate_patch`].
+    /// `None` means that the keyword has to be evaluated as a whole, which is always correct and
+    /// is the default. Keywords that only apply subschemas to separate properties or items
+    /// override this.
+    fn descend(&self, instance: &Value, segment: &str) -> Option<Descent<'_>> {
+        let _ = (instance, segment);
+        None
+    }
+
     /// `apply` applies this validator and any sub-validators it is composed of to the value in
     /// question and collects the resulting annotations or errors. Note that the result of `apply`
     /// is a `PartialApplication`.
@@ -99,6 +137,38 @@ pub(crate) trait Validate: Send + Sync {
     }
 }
 
+/// An example from the schema's `examples` keyword that is not valid against its subschema.
+///
+/// Returned by [`Validator::check_examples`].
+#[derive(Debug)]
+pub struct ExampleError {
+    /// Location of the example within the schema, e.g. `/properties/age/examples/1`.
+    pub location: Location,
+    /// The invalid example.
+    pub example: Value,
+    /// The first error produced when validating the example.
+    pub error: ValidationError<'static>,
+}
+
+/// A single keyword evaluation reported by [`Validator::trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRecord {
+    /// Location of the keyword within the schema.
+    pub keyword_location: Location,
+    /// Location of the evaluated value within the instance.
+    pub instance_location: Location,
+    /// Whether the value satisfied the keyword.
+    pub valid: bool,
+}
+
+/// How a keyword applies its subschemas along a path, returned by [`Validate::descend`].
+pub(crate) enum Descent<'a> {
+    /// The subschema applied to the value at the segment, if any.
+    Child(Option<&'a SchemaNode>),
+    /// A subschema applied to the instance itself, e.g. the target of a reference.
+    Here(&'a SchemaNode),
+}
+
 /// The result of applying a validator to an instance. As explained in the documentation for
 /// `Validate::apply` this is a "partial" result because it does not include information about
 /// where the error or annotation occurred.
@@ -167,8 +237,44 @@ impl<'a> PartialApplication<'a> {
 /// of the schema tree and the configuration options used during compilation.
 #[derive(Debug)]
 pub struct Validator {
-    pub(crate) root: SchemaNode,
+    pub(crate) root: Arc<SchemaNode>,
+    /// Inline checks for schemas that only constrain scalars.
+    pub(crate) scalar: Option<ScalarSchema>,
+    /// Property names required regardless of the instance values.
+    pub(crate) required: Arc<RequiredKeys>,
     pub(crate) config: Arc<ValidationOptions>,
+    pub(crate) registry: Arc<Registry>,
+    pub(crate) base_uri: Arc<str>,
+    /// Reference to the root schema within the `base_uri` resource.
+    ///
+    /// `None` if the root was not compiled from a single registry lookup, e.g. for combined
+    /// validators.
+    pub(crate) root_reference: Option<Arc<str>>,
+    #[cfg(feature = "profile")]
+    pub(crate) last_profile: std::sync::Mutex<Option<crate::Profile>>,
+}
+
+impl FromStr for Validator {
+    type Err = SchemaParseError;
+
+    /// Parse `schema` as JSON and create a validator for it using the default options.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use jsonschema::Validator;
+    /// use serde_json::json;
+    ///
+    /// let validator: Validator = r#"{"type": "string"}"#.parse()?;
+    ///
+    /// assert!(validator.is_valid(&json!("foo")));
+    /// assert!(r#"{"type": "#.parse::<Validator>().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn from_str(schema: &str) -> Result<Validator, Self::Err> {
+        let schema: Value = serde_json::from_str(schema)?;
+        Ok(Self::new(&schema)?)
+    }
 }
 
 impl Validator {
@@ -193,23 +299,645 @@ impl Validator {
     pub fn new(schema: &Value) -> Result<Validator, ValidationError<'static>> {
         Self::options().build(schema)
     }
+    /// Create a validator for the resource stored in `registry` under `uri`, using the default
+    /// options.
+    ///
+    /// The draft is taken from the registry, and `$ref`s are resolved against all resources in
+    /// it. Use [`ValidationOptions::build_from_registry`] to customize the options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `uri` is not in the registry or the schema fails to compile.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use jsonschema::{Registry, Resource, Uri};
+    /// use serde_json::json;
+    ///
+    /// let registry = Registry::try_from_resources(
+    ///     [
+    ///         (
+    ///             "https://example.com/person",
+    ///             Resource::from_contents(json!({
+    ///                 "properties": {"name": {"$ref": "https://example.com/name"}}
+    ///             }))?,
+    ///         ),
+    ///         (
+    ///             "https://example.com/name",
+    ///             Resource::from_contents(json!({"type": "string"}))?,
+    ///         ),
+    ///     ]
+    ///     .into_iter(),
+    /// )?;
+    /// let uri = Uri::parse("https://example.com/person".to_string())?;
+    /// let validator = jsonschema::Validator::from_registry(&registry, &uri)?;
+    ///
+    /// assert!(validator.is_valid(&json!({"name": "Alice"})));
+    /// assert!(!validator.is_valid(&json!({"name": 42})));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_registry(
+        registry: &Registry,
+        uri: &Uri<String>,
+    ) -> Result<Validator, ValidationError<'static>> {
+        Self::options().build_from_registry(registry, uri)
+    }
+    /// Create a validator rooted at the subschema that declares `anchor` within the resource
+    /// stored in `registry` under `base`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `base` is not in the registry, `anchor` is not defined in it, or the
+    /// subschema fails to compile.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use jsonschema::{Registry, Resource, Uri};
+    /// use serde_json::json;
+    ///
+    /// let registry = Registry::try_new(
+    ///     "https://example.com/customer",
+    ///     Resource::from_contents(json!({
+    ///         "$schema": "https://json-schema.org/draft/2020-12/schema",
+    ///         "$defs": {
+    ///             "address": {
+    ///                 "$anchor": "address",
+    ///                 "required": ["city"]
+    ///             }
+    ///         }
+    ///     }))?,
+    /// )?;
+    /// let base = Uri::parse("https://example.com/customer".to_string())?;
+    /// let validator = jsonschema::Validator::from_anchor(&registry, &base, "address")?;
+    ///
+    /// assert!(validator.is_valid(&json!({"city": "Prague"})));
+    /// assert!(!validator.is_valid(&json!({})));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_anchor(
+        registry: &Registry,
+        base: &Uri<String>,
+        anchor: &str,
+    ) -> Result<Validator, ValidationError<'static>> {
+        Self::options().build_from_anchor(registry, base, anchor)
+    }
+    /// Parse `schema` as JSON and create a validator for it, forcing the given draft instead of
+    /// detecting it from `$schema`.
+    ///
+    /// Use [`str::parse`] to detect the draft as [`Validator::new`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SchemaParseError::Json`] if `schema` is not valid JSON, or
+    /// [`SchemaParseError::Schema`] with any error [`Validator::new`] may return.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use jsonschema::{Draft, Validator};
+    /// use serde_json::json;
+    ///
+    /// let schema = r#"{"minimum": 5, "exclusiveMinimum": true}"#;
+    /// let validator = Validator::from_str_with_draft(schema, Draft::Draft4)?;
+    ///
+    /// assert!(!validator.is_valid(&json!(5)));
+    /// assert!(validator.is_valid(&json!(6)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_str_with_draft(schema: &str, draft: Draft) -> Result<Validator, SchemaParseError> {
+        let schema: Value = serde_json::from_str(schema)?;
+        Ok(Self::options().with_draft(draft).build(&schema)?)
+    }
     /// Validate `instance` against `schema` and return the first error if any.
     #[inline]
     pub fn validate<'i>(&self, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        self.root.validate(instance, &LazyLocation::new())
+        self.profiled(|| self.root.validate(instance, &LazyLocation::new()))
     }
     /// Run validation against `instance` and return an iterator over [`ValidationError`] in the error case.
+    ///
+    /// Errors are collected per keyword before they are yielded, e.g. `additionalProperties`
+    /// checks every property of an object. Use [`Validator::validate`] if only the first error is
+    /// needed, it stops at the first failing value.
+    ///
+    /// Errors are yielded in a deterministic order:
+    ///
+    /// - Keywords of a schema are evaluated in the lexicographic order of their names.
+    /// - Subschemas of applicators such as `allOf` or `prefixItems` follow their order in the
+    ///   schema, and array items are checked by index.
+    /// - Keywords applying subschemas to object properties follow the property order of the
+    ///   instance, which is the key order of [`serde_json::Map`]. If `properties`,
+    ///   `patternProperties` and `additionalProperties` are evaluated together, the errors of a
+    ///   property under `properties` come before those under `patternProperties`, and the
+    ///   `additionalProperties: false` error listing all unexpected properties comes last.
     #[inline]
     pub fn iter_errors<'i>(&'i self, instance: &'i Value) -> ErrorIterator<'i> {
         self.root.iter_errors(instance, &LazyLocation::new())
     }
+    /// Get a value that displays an instance `location`, e.g. [`ValidationError::instance_path`],
+    /// in the style set by [`ValidationOptions::with_instance_location_style`].
+    ///
+    /// ```rust
+    /// use jsonschema::paths::InstanceLocationStyle;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_instance_location_style(InstanceLocationStyle::UriFragment)
+    ///     .build(&json!({"items": {"type": "string"}}))
+    ///     .expect("Invalid schema");
+    /// let instance = json!([42]);
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(
+    ///     validator.display_location(&error.instance_path).to_string(),
+    ///     "#/0"
+    /// );
+    /// ```
+    pub fn display_location<'a>(&self, location: &'a Location) -> impl fmt::Display + 'a {
+        location.display(self.config.instance_location_style())
+    }
+    /// Validate `instance` like [`Validator::validate`], with `schema_prefix` prepended to the
+    /// schema path of the error.
+    ///
+    /// Useful when this validator checks a part of a larger document, e.g. one built with
+    /// [`Validator::compile_subschema`], so that errors point to keywords of the whole document.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use jsonschema::paths::{Location, LocationSegment};
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"$defs": {"address": {"properties": {"zip": {"type": "string"}}}}});
+    /// let address = jsonschema::validator_for(&schema)?.compile_subschema("/$defs/address")?;
+    /// let prefix: Location = [LocationSegment::from("$defs"), LocationSegment::from("address")]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let instance = json!({"zip": 12345});
+    /// let error = address
+    ///     .validate_with_base(&instance, &prefix)
+    ///     .expect_err("Should fail");
+    /// assert_eq!(error.schema_path.as_str(), "/$defs/address/properties/zip/type");
+    /// assert_eq!(error.instance_path.as_str(), "/zip");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_with_base<'i>(
+        &self,
+        instance: &'i Value,
+        schema_prefix: &Location,
+    ) -> Result<(), ValidationError<'i>> {
+        self.validate(instance)
+            .map_err(|error| error.with_schema_path_prefix(schema_prefix))
+    }
+    /// Validate `instance` against `schema` and return the first error if any, as an owned value.
+    ///
+    /// Unlike [`Validator::validate`], the returned error does not borrow from `instance` and can
+    /// outlive it. This requires cloning the failing part of the instance, which adds an
+    /// allocation in the error case.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "string"}))
+    ///     .expect("Invalid schema");
+    /// let error = {
+    ///     let instance = json!(42);
+    ///     validator.validate_owned(&instance).expect_err("Should fail")
+    /// };
+    /// assert_eq!(error.to_string(), r#"42 is not of type "string""#);
+    /// ```
+    pub fn validate_owned(&self, instance: &Value) -> Result<(), ValidationError<'static>> {
+        self.validate(instance).map_err(ValidationError::to_owned)
+    }
+    /// Run validation against `instance` and return an iterator over owned [`ValidationError`]s.
+    ///
+    /// All errors are collected eagerly and the failing parts of the instance are cloned, so the
+    /// returned iterator does not borrow from `instance`. Prefer [`Validator::iter_errors`] when
+    /// the instance outlives the errors.
+    pub fn iter_errors_owned(&self, instance: &Value) -> ErrorIterator<'static> {
+        let errors: Vec<_> = self
+            .iter_errors(instance)
+            .map(ValidationError::to_owned)
+            .collect();
+        Box::new(errors.into_iter())
+    }
+    /// Return the instance location of every error in `instance`, without the errors themselves.
+    ///
+    /// Each location is reported once, in the order its first error is yielded by
+    /// [`Validator::iter_errors`]. Useful when only the invalid values matter, e.g. to highlight
+    /// form fields.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {
+    ///         "name": {"type": "string"},
+    ///         "age": {"type": "integer", "minimum": 0}
+    ///     }
+    /// }))
+    /// .expect("Invalid schema");
+    /// let locations = validator.invalid_instance_locations(&json!({"age": -1.5, "name": 1}));
+    /// let locations: Vec<_> = locations.iter().map(|location| location.as_str()).collect();
+    /// assert_eq!(locations, ["/age", "/name"]);
+    /// ```
+    #[must_use]
+    pub fn invalid_instance_locations(&self, instance: &Value) -> Vec<Location> {
+        let mut seen = AHashSet::new();
+        let mut locations = Vec::new();
+        for error in self.iter_errors(instance) {
+            if seen.insert(error.instance_path.as_str().to_string()) {
+                locations.push(error.instance_path);
+            }
+        }
+        locations
+    }
+    /// Validate each of `instances` in parallel and return the results in the input order.
+    ///
+    /// Instances are distributed across the global `rayon` thread pool, each is validated as
+    /// with [`Validator::validate`]. Useful for large batches, for a few small instances the
+    /// scheduling overhead outweighs the gain. Requires the `rayon` feature.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "integer"}))
+    ///     .expect("Invalid schema");
+    /// let instances = [json!(1), json!("a"), json!(2)];
+    /// let results = validator.validate_par(&instances);
+    /// assert!(results[0].is_ok());
+    /// assert!(results[1].is_err());
+    /// assert!(results[2].is_ok());
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn validate_par<'i>(&self, instances: &'i [Value]) -> Vec<Result<(), ValidationError<'i>>> {
+        use rayon::prelude::*;
+
+        instances
+            .par_iter()
+            .map(|instance| self.validate(instance))
+            .collect()
+    }
+    /// Compare the errors of `previous` and `new`, where `new` differs from `previous` only in
+    /// the value at `changed_pointer`.
+    ///
+    /// Only the errors that may depend on the changed value are re-computed: the changed value
+    /// is validated against the subschemas that apply to it, and every schema applying to one of
+    /// its parents is validated without descending into other properties or items. The changed
+    /// value may be missing in either instance, e.g. when a property was added or removed.
+    ///
+    /// # Limitations
+    ///
+    /// - In-place applicators like `allOf`, `anyOf`, `oneOf`, `not`, `if`, `dependentSchemas`
+    ///   or `$dynamicRef` in a schema that applies to a parent of the changed value are evaluated
+    ///   against the whole parent, including its unchanged parts. The same applies to
+    ///   `patternProperties`, `additionalProperties`, `additionalItems`, `unevaluatedProperties`
+    ///   and `unevaluatedItems`. Properties covered by `properties` next to
+    ///   `additionalProperties` are evaluated with it.
+    /// - If the instances differ outside of `changed_pointer`, the result is unspecified.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {"age": {"minimum": 0}, "name": {"type": "string"}}
+    /// }))
+    /// .expect("Invalid schema");
+    /// let previous = json!({"age": 30, "name": 42});
+    /// let new = json!({"age": -1, "name": 42});
+    /// let delta = validator.validate_patch(&previous, "/age", &new);
+    /// assert_eq!(delta.added.len(), 1);
+    /// assert_eq!(delta.added[0].instance_path.as_str(), "/age");
+    /// // The error for `name` did not change
+    /// assert!(delta.removed.is_empty());
+    /// ```
+    #[must_use]
+    pub fn validate_patch(
+        &self,
+        previous: &Value,
+        changed_pointer: &str,
+        new: &Value,
+    ) -> ErrorDelta {
+        patch::validate_patch(self, previous, changed_pointer, new)
+    }
     /// Run validation against `instance` but return a boolean result instead of an iterator.
     /// It is useful for cases, where it is important to only know the fact if the data is valid or not.
     /// This approach is much faster, than [`Validator::validate`].
     #[must_use]
     #[inline]
     pub fn is_valid(&self, instance: &Value) -> bool {
-        self.root.is_valid(instance)
+        // Inline scalar checks bypass keyword validators, so they are not profiled
+        if let Some(scalar) = &self.scalar {
+            if !cfg!(feature = "profile") && !instance.is_array() && !instance.is_object() {
+                return scalar.is_valid(instance);
+            }
+        }
+        self.profiled(|| self.root.is_valid(instance))
+    }
+    /// Parse `raw` once and check whether the resulting instance is valid.
+    ///
+    /// Useful when instances arrive as [`RawValue`] fragments, e.g. from a larger document
+    /// deserialized lazily. Requires the `raw-value` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `raw` can't be parsed into a [`Value`].
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use serde_json::{json, value::RawValue};
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"required": ["id"]}))?;
+    /// let raw = RawValue::from_string(r#"{"id": 1}"#.to_string())?;
+    ///
+    /// assert!(validator.is_valid_raw(&raw)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "raw-value")]
+    pub fn is_valid_raw(&self, raw: &RawValue) -> Result<bool, serde_json::Error> {
+        let instance: Value = serde_json::from_str(raw.get())?;
+        Ok(self.is_valid(&instance))
+    }
+    /// Per-keyword statistics of the last [`Validator::is_valid`] or [`Validator::validate`] call.
+    ///
+    /// Returns `None` if neither was called yet. Requires the `profile` feature.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "integer", "minimum": 5}))
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!(7)));
+    /// let profile = validator.last_profile().expect("Profile is recorded");
+    /// assert_eq!(profile.get("/minimum").map(|keyword| keyword.calls), Some(1));
+    /// ```
+    #[cfg(feature = "profile")]
+    #[must_use]
+    pub fn last_profile(&self) -> Option<crate::Profile> {
+        self.last_profile
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+    #[cfg(feature = "profile")]
+    fn profiled<T>(&self, run: impl FnOnce() -> T) -> T {
+        let (result, profile) = crate::profile::collect(run);
+        *self
+            .last_profile
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(profile);
+        result
+    }
+    #[cfg(not(feature = "profile"))]
+    #[inline(always)]
+    fn profiled<T>(&self, run: impl FnOnce() -> T) -> T {
+        run()
+    }
+    /// Check only that `instance` has the properties the schema requires unconditionally.
+    ///
+    /// These come from `required` in the root schema and in the schemas it applies
+    /// unconditionally via `allOf` and `$ref`. Conditional requirements, e.g. inside `if`,
+    /// `anyOf` or `dependentSchemas`, are not checked, and neither are property values. Instances
+    /// other than objects always pass, as `required` does not apply to them.
+    ///
+    /// This is a cheap gate before full validation, not a replacement for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the missing property names in the order they appear in the schema.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "required": ["id"],
+    ///     "allOf": [{"required": ["name"]}],
+    ///     "properties": {"id": {"type": "integer"}}
+    /// }))
+    /// .expect("Invalid schema");
+    /// assert!(validator.check_required(&json!({"id": "not validated", "name": "Alice"})).is_ok());
+    /// assert_eq!(
+    ///     validator.check_required(&json!({"id": 1})),
+    ///     Err(vec!["name".to_string()])
+    /// );
+    /// ```
+    pub fn check_required(&self, instance: &Value) -> Result<(), Vec<String>> {
+        let Value::Object(object) = instance else {
+            return Ok(());
+        };
+        let missing: Vec<_> = self
+            .required
+            .get()
+            .iter()
+            .filter(|key| !object.contains_key(key.as_str()))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+    /// Return a copy of `instance` with `default` values filled in for missing object properties.
+    ///
+    /// This is a convenience outside of the JSON Schema specification, where `default` is only
+    /// an annotation. For every property declared in `properties` but absent from an object, the
+    /// first `default` found in the property subschema, its `$ref` targets or its `allOf`
+    /// subschemas is inserted. Defaults are filled in recursively, including within inserted
+    /// defaults, and for array items matched by a single `items` schema. Subschemas are followed
+    /// through `$ref` and `allOf` only, as other applicators such as `anyOf` or `if` apply
+    /// conditionally. Existing values are never overwritten and the instance is not validated.
+    ///
+    /// The instance is returned unchanged if the validator was not built from a single schema,
+    /// e.g. by [`Validator::and`] or [`Validator::compile_subschema`].
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {
+    ///         "theme": {"default": "light"},
+    ///         "editor": {"$ref": "#/$defs/editor"}
+    ///     },
+    ///     "$defs": {
+    ///         "editor": {
+    ///             "default": {},
+    ///             "properties": {"tabSize": {"default": 4}}
+    ///         }
+    ///     }
+    /// }))
+    /// .expect("Invalid schema");
+    /// assert_eq!(
+    ///     validator.apply_defaults(&json!({"theme": "dark"})),
+    ///     json!({"theme": "dark", "editor": {"tabSize": 4}})
+    /// );
+    /// ```
+    #[must_use]
+    pub fn apply_defaults(&self, instance: &Value) -> Value {
+        defaults::apply_defaults(self, instance)
+    }
+    /// Run validation against `instance` and call `callback` for every keyword evaluated.
+    ///
+    /// Each [`TraceRecord`] holds the keyword location, the instance location and whether the
+    /// keyword passed. Records are reported after the keyword finished, so the keywords of a
+    /// subschema come before the applicator that contains them. Evaluation does not stop at the
+    /// first failure, which makes the trace suitable for building keyword coverage maps.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"minimum": 5, "maxLength": 3});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let mut records = Vec::new();
+    /// assert!(!validator.trace(&json!(3), |record| records.push(record)));
+    /// let minimum = records
+    ///     .iter()
+    ///     .find(|record| record.keyword_location.as_str() == "/minimum")
+    ///     .expect("Keyword was evaluated");
+    /// assert!(!minimum.valid);
+    /// ```
+    pub fn trace(&self, instance: &Value, mut callback: impl FnMut(TraceRecord)) -> bool {
+        self.root
+            .trace(instance, &LazyLocation::new(), &mut callback)
+    }
+    /// Find every location in `instance` where the subschema at `subschema_pointer` was applied
+    /// through a `$ref` and passed.
+    ///
+    /// The pointer is relative to the root schema, e.g. `/$defs/PII`. Matches are found through
+    /// `$ref` and `$dynamicRef` under all in-place and child applicators, except
+    /// `propertyNames`, `contentSchema`, `unevaluatedProperties`, `unevaluatedItems` and
+    /// `contains` together with `minContains` or `maxContains`. References made with
+    /// `$recursiveRef` are not followed either. A location is reported once, in the order of
+    /// evaluation, even if several references apply the subschema to it. Returns an empty list if the
+    /// pointer does not resolve, or if the validator was not built from a single schema, e.g.
+    /// by [`Validator::and`] or [`Validator::compile_subschema`].
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "email": {"$ref": "#/$defs/PII"},
+    ///         "phone": {"$ref": "#/$defs/PII"}
+    ///     },
+    ///     "$defs": {"PII": {"type": "string"}}
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let matches =
+    ///     validator.locate_subschema_matches(&json!({"email": "a@b.c", "phone": 42}), "/$defs/PII");
+    /// let matches: Vec<_> = matches.iter().map(|location| location.as_str()).collect();
+    /// assert_eq!(matches, ["/email"]);
+    /// ```
+    #[must_use]
+    pub fn locate_subschema_matches(
+        &self,
+        instance: &Value,
+        subschema_pointer: &str,
+    ) -> Vec<Location> {
+        matches::locate_subschema_matches(self, instance, subschema_pointer)
+    }
+    /// Render the structure of the schema as a Graphviz DOT graph.
+    ///
+    /// Every subschema becomes a node labeled by its location, and edges are labeled by the
+    /// keyword leading to it, e.g. `items` or `properties/name`. `$ref` edges are dashed and
+    /// point to the node of the referenced subschema, so each subschema is drawn once and
+    /// recursive references appear as back-edges. The graph is empty if the validator was not
+    /// built from a single schema, e.g. by [`Validator::and`] or
+    /// [`Validator::compile_subschema`].
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"items": {"$ref": "#"}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// assert_eq!(
+    ///     validator.to_dot(),
+    ///     r##"digraph schema {
+    ///   n0 [label="#"];
+    ///   n1 [label="#/items"];
+    ///   n1 -> n0 [label="$ref", style=dashed];
+    ///   n0 -> n1 [label="items"];
+    /// }
+    /// "##
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        dot::to_dot(self)
+    }
+    /// Describe the structure of the values accepted by the schema, e.g. for code generation.
+    ///
+    /// `$ref`s are resolved, and recursive references become [`TypeShape::Recursive`]. A `oneOf`
+    /// whose variants share a required property with distinct `const` values is reported as a
+    /// tagged union. See [`TypeShape`] for the keywords taken into account.
+    ///
+    /// ```rust
+    /// use jsonschema::{Property, TypeShape};
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {"id": {"type": "integer"}},
+    ///     "required": ["id"]
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let TypeShape::Object(properties) = validator.type_shape() else {
+    ///     panic!("Expected an object");
+    /// };
+    /// assert_eq!(
+    ///     properties["id"],
+    ///     Property {
+    ///         required: true,
+    ///         shape: TypeShape::Integer
+    ///     }
+    /// );
+    /// ```
+    #[must_use]
+    pub fn type_shape(&self) -> TypeShape {
+        shape::type_shape(self)
+    }
+    /// Explain why `instance` is invalid, or return `None` if it is valid.
+    ///
+    /// The explanation describes the first error and where it occurred in the instance, which is
+    /// handy for quick debugging. Use [`Validator::iter_errors`] to get all errors in a
+    /// structured form.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"age": {"minimum": 18}}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// assert_eq!(validator.explain(&json!({"age": 42})), None);
+    /// assert_eq!(
+    ///     validator.explain(&json!({"age": 16})).as_deref(),
+    ///     Some("at /age: 16 is less than the minimum of 18")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn explain(&self, instance: &Value) -> Option<String> {
+        let error = self.validate(instance).err()?;
+        if error.instance_path.as_str().is_empty() {
+            Some(error.to_string())
+        } else {
+            Some(format!(
+                "at {}: {}",
+                self.display_location(&error.instance_path),
+                error
+            ))
+        }
     }
     /// Apply the schema and return an [`Output`]. No actual work is done at this point, the
     /// evaluation of the schema is deferred until a method is called on the `Output`. This is
@@ -224,36 +952,271 @@ impl Validator {
     /// use serde_json::json;
     ///
     /// let schema = json!({
-    ///     "title": "string value",
-    ///     "type": "string"
+    ///     "title": "string value",
+    ///     "type": "string"
+    /// });
+    /// let instance = json!("some string");
+    ///
+    /// let validator = jsonschema::validator_for(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// let output = validator.apply(&instance).basic();
+    /// assert_eq!(
+    ///     serde_json::to_value(output)?,
+    ///     json!({
+    ///         "valid": true,
+    ///         "annotations": [
+    ///             {
+    ///                 "keywordLocation": "",
+    ///                 "instanceLocation": "",
+    ///                 "annotations": {
+    ///                     "title": "string value"
+    ///                 }
+    ///             }
+    ///         ]
+    ///     })
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn apply<'a, 'b>(&'a self, instance: &'b Value) -> Output<'a, 'b> {
+        Output::new(self, instance)
+    }
+
+    /// Validate `instance` and return the annotations collected by the "basic" output format if
+    /// it is valid, or the first error otherwise.
+    ///
+    /// Each [`OutputUnit`] holds the annotations of one subschema along with the instance
+    /// location it was applied to. Subschemas are only applied to values present in the
+    /// instance, e.g. `properties` yields no annotations for missing properties.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "role": {"title": "Role", "default": "user", "type": "string"}
+    ///     }
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let instance = json!({"role": "admin"});
+    /// let annotations = validator
+    ///     .validate_with_annotations(&instance)
+    ///     .expect("Valid instance");
+    /// let role = annotations
+    ///     .iter()
+    ///     .find(|unit| unit.instance_location().as_str() == "/role")
+    ///     .expect("Annotations of the `role` subschema");
+    /// assert_eq!(*role.value(), json!({"title": "Role", "default": "user"}));
+    ///
+    /// assert!(validator.validate_with_annotations(&json!({"role": 1})).is_err());
+    /// ```
+    pub fn validate_with_annotations<'a, 'i>(
+        &'a self,
+        instance: &'i Value,
+    ) -> Result<Vec<OutputUnit<Annotations<'a>>>, ValidationError<'i>> {
+        self.validate(instance)?;
+        match self.apply(instance).basic() {
+            BasicOutput::Valid(annotations) => Ok(annotations.into()),
+            // `apply` accepts every instance accepted by `validate`
+            BasicOutput::Invalid(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// Iterate over annotations and errors together, in evaluation order.
+    ///
+    /// Each keyword of the root schema is evaluated when the iterator reaches it. Keywords that
+    /// pass yield their annotations and keywords that fail yield their errors, so annotations are
+    /// kept even if the instance is invalid overall. Annotations produced inside a failing
+    /// keyword are dropped, as in the "basic" output format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use jsonschema::output::Unit;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {"name": {"type": "string"}},
+    ///     "additionalProperties": true
+    /// });
+    /// let instance = json!({"name": 42, "age": 1});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// for unit in validator.iter_units(&instance) {
+    ///     match unit {
+    ///         Unit::Annotation(annotation) => {
+    ///             println!("Annotation at {}: {}", annotation.keyword_location(), annotation.value())
+    ///         }
+    ///         Unit::Error(error) => {
+    ///             println!("Error at {}: {}", error.keyword_location(), error.error_description())
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn iter_units<'a>(&'a self, instance: &'a Value) -> impl Iterator<Item = Unit<'a>> + 'a {
+        let units = self.root.iter_units(instance);
+        let style = self.config.instance_location_style();
+        units.map(move |unit| match unit {
+            Unit::Annotation(unit) => Unit::Annotation(unit.with_instance_location_style(style)),
+            Unit::Error(unit) => Unit::Error(unit.with_instance_location_style(style)),
+        })
+    }
+    /// Combine this validator with `other` using `allOf` semantics.
+    ///
+    /// An instance is valid only if it is valid against both validators. The already compiled
+    /// schema trees are shared between the validators, so no JSON is parsed or compiled again and
+    /// errors are reported with the schema locations of the side they come from.
+    ///
+    /// Each side keeps resolving references against its own base URI and resources, so `$ref`s
+    /// are effectively namespaced per validator. If both schemas are supposed to point to the same
+    /// resources, make sure they were built with compatible base URIs.
+    ///
+    /// The combined validator uses the options of `self`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use serde_json::json;
+    ///
+    /// let base = jsonschema::validator_for(&json!({"type": "object", "required": ["id"]}))?;
+    /// let tenant = jsonschema::validator_for(&json!({"properties": {"id": {"type": "integer"}}}))?;
+    /// let validator = base.and(&tenant);
+    ///
+    /// assert!(validator.is_valid(&json!({"id": 1})));
+    /// assert!(!validator.is_valid(&json!({"id": "1"})));
+    /// assert!(!validator.is_valid(&json!({})));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn and(&self, other: &Validator) -> Validator {
+        Validator {
+            root: Arc::new(SchemaNode::from_shared(vec![
+                Arc::clone(&self.root),
+                Arc::clone(&other.root),
+            ])),
+            scalar: None,
+            required: RequiredKeys::both(&self.required, &other.required),
+            config: Arc::clone(&self.config),
+            registry: Arc::clone(&self.registry),
+            base_uri: Arc::clone(&self.base_uri),
+            root_reference: None,
+            #[cfg(feature = "profile")]
+            last_profile: std::sync::Mutex::default(),
+        }
+    }
+
+    /// Compile a standalone validator for the subschema located at `pointer`.
+    ///
+    /// The pointer is resolved against the root of this validator's schema and may optionally
+    /// start with `#`. All resources known to this validator remain available, so references
+    /// pointing outside of the subschema are still resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pointer can't be resolved or the subschema fails to compile.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "$defs": {
+    ///         "address": {
+    ///             "properties": {"zip": {"$ref": "#/$defs/zip"}},
+    ///             "required": ["zip"]
+    ///         },
+    ///         "zip": {"type": "string"}
+    ///     }
+    /// });
+    /// let validator = jsonschema::validator_for(&schema)?;
+    /// let address = validator.compile_subschema("/$defs/address")?;
+    ///
+    /// assert!(address.is_valid(&json!({"zip": "12345"})));
+    /// assert!(!address.is_valid(&json!({"zip": 12345})));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile_subschema(&self, pointer: &str) -> Result<Validator, ValidationError<'static>> {
+        compiler::build_subschema_validator(self, pointer)
+    }
+
+    /// Validate every value of every `examples` keyword in the schema against the subschema
+    /// that contains it, and return the examples that fail.
+    ///
+    /// Subschemas in `$defs`, `properties`, applicators, and other keywords are checked as
+    /// well. This is a tool for catching schema authoring mistakes and is not part of regular
+    /// validation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a subschema with `examples` cannot be compiled, for example if it is
+    /// only reachable from `$defs` and contains an unresolvable reference.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "age": {"type": "integer", "minimum": 0, "examples": [42, -1]}
+    ///     }
     /// });
-    /// let instance = json!("some string");
+    /// let validator = jsonschema::validator_for(&schema)?;
+    /// let errors = validator.check_examples()?;
     ///
-    /// let validator = jsonschema::validator_for(&schema)
-    ///     .expect("Invalid schema");
-    ///
-    /// let output = validator.apply(&instance).basic();
-    /// assert_eq!(
-    ///     serde_json::to_value(output)?,
-    ///     json!({
-    ///         "valid": true,
-    ///         "annotations": [
-    ///             {
-    ///                 "keywordLocation": "",
-    ///                 "instanceLocation": "",
-    ///                 "annotations": {
-    ///                     "title": "string value"
-    ///                 }
-    ///             }
-    ///         ]
-    ///     })
-    /// );
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].location.as_str(), "/properties/age/examples/1");
+    /// assert_eq!(errors[0].example, json!(-1));
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use]
-    pub const fn apply<'a, 'b>(&'a self, instance: &'b Value) -> Output<'a, 'b> {
-        Output::new(self, &self.root, instance)
+    pub fn check_examples(&self) -> Result<Vec<ExampleError>, ValidationError<'static>> {
+        let Ok(resolver) = self.registry.try_resolver(&self.base_uri) else {
+            return Ok(Vec::new());
+        };
+        let Ok(root) = resolver.lookup("#") else {
+            return Ok(Vec::new());
+        };
+        let mut examples = Vec::new();
+        compiler::walk_subschemas(
+            root.contents(),
+            &Location::new(),
+            &mut |schema, location| {
+                if let Some(Value::Array(values)) = schema.get("examples") {
+                    examples.push((location.clone(), values));
+                }
+            },
+        );
+        let mut errors = Vec::new();
+        for (location, values) in examples {
+            let subschema;
+            let validator = if location.is_empty() {
+                self
+            } else {
+                subschema = self.compile_subschema(location.as_str())?;
+                &subschema
+            };
+            let location = location.join("examples");
+            for (idx, example) in values.iter().enumerate() {
+                if let Err(error) = validator.validate(example) {
+                    errors.push(ExampleError {
+                        location: location.join(idx),
+                        example: example.clone(),
+                        error: error.to_owned(),
+                    });
+                }
+            }
+        }
+        Ok(errors)
     }
 
     /// The [`Draft`] which was used to build this validator.
@@ -282,6 +1245,7 @@ mod tests {
     use num_cmp::NumCmp;
     use once_cell::sync::Lazy;
     use serde_json::{json, Map, Value};
+    use test_case::test_case;
 
     #[cfg(not(target_arch = "wasm32"))]
     fn load(path: &str, idx: usize) -> Value {
@@ -319,6 +1283,137 @@ mod tests {
         assert!(validator.validate(&value).is_err());
     }
 
+    #[test]
+    fn owned_errors_outlive_instance() {
+        let schema = json!({"properties": {"name": {"type": "string"}, "age": {"minimum": 0}}});
+        let validator = crate::validator_for(&schema).unwrap();
+        let (error, errors) = {
+            let instance = json!({"name": 42, "age": -1});
+            (
+                validator
+                    .validate_owned(&instance)
+                    .expect_err("Should fail"),
+                validator.iter_errors_owned(&instance).collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(error.instance_path.as_str(), "/age");
+        assert_eq!(*error.instance, json!(-1));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].to_string(), r#"42 is not of type "string""#);
+        assert!(validator.validate_owned(&json!({"name": "Alice"})).is_ok());
+        assert_eq!(validator.iter_errors_owned(&json!({})).count(), 0);
+    }
+
+    #[test_case("", "/properties/zip/anyOf"; "empty prefix")]
+    #[test_case("/$defs/address", "/$defs/address/properties/zip/anyOf"; "nested prefix")]
+    fn validate_with_base(prefix: &str, expected: &str) {
+        let validator = crate::options()
+            .should_report_branch_errors(true)
+            .build(&json!({
+                "properties": {"zip": {"anyOf": [{"type": "string"}, {"minimum": 10000}]}}
+            }))
+            .unwrap();
+        let prefix: Location = prefix
+            .split('/')
+            .skip(1)
+            .map(crate::paths::LocationSegment::Property)
+            .collect();
+        let instance = json!({"zip": 42});
+        let error = validator
+            .validate_with_base(&instance, &prefix)
+            .expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), expected);
+        assert_eq!(error.instance_path.as_str(), "/zip");
+        let crate::error::ValidationErrorKind::AnyOf { context } = &error.kind else {
+            panic!("Unexpected error: {error:?}");
+        };
+        let branches: Vec<_> = context
+            .iter()
+            .map(|error| error.schema_path.as_str())
+            .collect();
+        assert_eq!(
+            branches,
+            [
+                format!("{expected}/0/type"),
+                format!("{expected}/1/minimum")
+            ]
+        );
+        assert!(validator
+            .validate_with_base(&json!({"zip": "123"}), &prefix)
+            .is_ok());
+    }
+
+    #[test_case(&json!({"id": 1, "tags": ["a"]}), &[]; "valid")]
+    #[test_case(&json!({"id": "1", "tags": ["a"]}), &["/id"]; "single error")]
+    #[test_case(&json!({"id": 1.5, "tags": ["a"]}), &["/id"]; "several errors at the same location")]
+    #[test_case(&json!({"id": -1.5, "tags": [1, "a", 2], "extra": 1}), &["/id", "/tags/0", "/tags/2", ""]; "several locations")]
+    fn invalid_instance_locations(instance: &Value, expected: &[&str]) {
+        let validator = crate::validator_for(&json!({
+            "properties": {
+                "id": {"type": "integer", "minimum": 0, "multipleOf": 1},
+                "tags": {"items": {"type": "string"}}
+            },
+            "additionalProperties": false
+        }))
+        .unwrap();
+        let locations = validator.invalid_instance_locations(instance);
+        let locations: Vec<_> = locations.iter().map(Location::as_str).collect();
+        assert_eq!(locations, expected);
+    }
+
+    #[test]
+    fn validate_with_annotations() {
+        let validator = crate::validator_for(&json!({
+            "title": "Settings",
+            "properties": {
+                "theme": {"title": "Theme", "default": "light", "type": "string"},
+                "size": {"default": 12, "minimum": 1}
+            }
+        }))
+        .unwrap();
+        let instance = json!({"theme": "dark", "size": 14});
+        let annotations = validator
+            .validate_with_annotations(&instance)
+            .expect("Valid instance");
+        let annotations: Vec<_> = annotations
+            .iter()
+            .map(|unit| (unit.instance_location().as_str(), unit.value().into_owned()))
+            .collect();
+        assert_eq!(
+            annotations,
+            [
+                ("", json!({"title": "Settings"})),
+                // Properties matched by `properties`
+                ("", json!(["size", "theme"])),
+                ("/size", json!({"default": 12})),
+                ("/theme", json!({"title": "Theme", "default": "light"})),
+            ]
+        );
+        let instance = json!({"theme": "dark", "size": 0});
+        let error = validator
+            .validate_with_annotations(&instance)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/size");
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn validate_par_preserves_order() {
+        let validator = crate::validator_for(&json!({"items": {"maximum": 100}})).unwrap();
+        let instances: Vec<_> = (0..1000)
+            .map(|idx| json!([idx % 7, idx, idx % 13]))
+            .collect();
+        let results = validator.validate_par(&instances);
+        assert_eq!(results.len(), instances.len());
+        for (idx, (instance, result)) in instances.iter().zip(results).enumerate() {
+            assert_eq!(result.is_ok(), idx <= 100, "{instance}");
+            if let Err(error) = result {
+                assert!(std::ptr::eq(error.instance.as_ref(), &instance[1]));
+                assert_eq!(error.instance_path.as_str(), "/1");
+            }
+        }
+    }
+
     #[test]
     fn wrong_schema_type() {
         let schema = json!([1]);
@@ -571,9 +1666,778 @@ mod tests {
         assert_eq!(error.to_string(), "\"foo\" is not of type \"number\"");
     }
 
+    #[test_case(&json!({"base": true}), "/base", "none")]
+    #[test_case(
+        &json!({"$id": "https://example.com/root.json", "base": true}),
+        "/base",
+        "https://example.com/root.json"
+    )]
+    #[test_case(
+        &json!({
+            "$id": "https://example.com/root.json",
+            "items": {"$id": "nested/item.json", "base": true}
+        }),
+        "/items/base",
+        "https://example.com/nested/item.json"
+    )]
+    fn contextual_keyword(schema: &Value, expected_location: &str, expected_base_uri: &str) {
+        /// Fails with the base URI of the schema resource containing the keyword.
+        struct BaseUri {
+            base_uri: String,
+            location: Location,
+        }
+
+        impl Keyword for BaseUri {
+            fn validate<'i>(
+                &self,
+                instance: &'i Value,
+                location: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Err(ValidationError::custom(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    self.base_uri.clone(),
+                ))
+            }
+
+            fn is_valid(&self, _: &Value) -> bool {
+                false
+            }
+        }
+
+        let validator = crate::options()
+            .with_contextual_keyword("base", |_, _, context| {
+                Ok(Box::new(BaseUri {
+                    base_uri: context
+                        .base_uri()
+                        .map_or_else(|| "none".to_string(), ToString::to_string),
+                    location: context.location().clone(),
+                }))
+            })
+            .build(schema)
+            .expect("Invalid schema");
+        let instance = json!([1]);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), expected_location);
+        assert_eq!(error.to_string(), expected_base_uri);
+    }
+
+    #[test]
+    fn compile_subschema() {
+        let schema = json!({
+            "$id": "https://example.com/root",
+            "$defs": {
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "street": {"type": "string"},
+                        "country": {"$ref": "#/$defs/country"}
+                    },
+                    "required": ["street"]
+                },
+                "country": {"enum": ["DE", "UA"]}
+            },
+            "type": "array"
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        for pointer in ["/$defs/address", "#/$defs/address"] {
+            let address = validator
+                .compile_subschema(pointer)
+                .expect("Invalid subschema");
+            assert!(address.is_valid(&json!({"street": "Main St", "country": "UA"})));
+            assert!(!address.is_valid(&json!({"street": "Main St", "country": "US"})));
+            assert!(!address.is_valid(&json!([])));
+            let instance = json!({"country": "DE"});
+            let error = address.validate(&instance).expect_err("Should fail");
+            assert_eq!(error.schema_path.as_str(), "/required");
+        }
+    }
+
+    #[test]
+    fn compile_subschema_invalid_pointer() {
+        let validator = crate::validator_for(&json!({"$defs": {}})).expect("Invalid schema");
+        let error = validator
+            .compile_subschema("/$defs/missing")
+            .expect_err("Should fail");
+        assert_eq!(error.to_string(), "Pointer '/$defs/missing' does not exist");
+    }
+
+    fn registry() -> crate::Registry {
+        crate::Registry::try_from_resources(
+            [
+                (
+                    "https://example.com/a",
+                    crate::Resource::from_contents(json!({
+                        "$schema": "http://json-schema.org/draft-07/schema#",
+                        "type": "object",
+                        "properties": {"b": {"$ref": "https://example.com/b"}},
+                        "required": ["b"]
+                    }))
+                    .expect("Valid resource"),
+                ),
+                (
+                    "https://example.com/b",
+                    crate::Resource::from_contents(json!({
+                        "type": "integer",
+                        "minimum": 1
+                    }))
+                    .expect("Valid resource"),
+                ),
+            ]
+            .into_iter(),
+        )
+        .expect("Valid registry")
+    }
+
+    #[test]
+    fn from_registry() {
+        let registry = registry();
+        let uri = referencing::uri::from_str("https://example.com/a").expect("Valid URI");
+        let validator = Validator::from_registry(&registry, &uri).expect("Valid schema");
+        assert_eq!(validator.draft(), crate::Draft::Draft7);
+        assert!(validator.is_valid(&json!({"b": 5})));
+        assert!(!validator.is_valid(&json!({"b": 0})));
+        assert!(!validator.is_valid(&json!({"b": "5"})));
+        assert!(!validator.is_valid(&json!({})));
+        let instance = json!({"b": 0});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/b");
+        assert_eq!(error.schema_path.as_str(), "/properties/b/$ref/minimum");
+    }
+
+    #[test]
+    fn from_registry_explicit_draft() {
+        let registry = registry();
+        let uri = referencing::uri::from_str("https://example.com/b").expect("Valid URI");
+        let validator = crate::options()
+            .with_draft(crate::Draft::Draft4)
+            .build_from_registry(&registry, &uri)
+            .expect("Valid schema");
+        assert_eq!(validator.draft(), crate::Draft::Draft4);
+        assert!(validator.is_valid(&json!(2)));
+        // Draft 4 does not treat `2.0` as an integer
+        assert!(!validator.is_valid(&json!(2.0)));
+    }
+
+    #[test]
+    fn from_registry_unknown_uri() {
+        let registry = registry();
+        let uri = referencing::uri::from_str("https://example.com/missing").expect("Valid URI");
+        assert!(Validator::from_registry(&registry, &uri).is_err());
+    }
+
+    fn required_validator() -> Validator {
+        crate::validator_for(&json!({
+            "required": ["id"],
+            "allOf": [
+                {"required": ["name"]},
+                {"allOf": [{"$ref": "#/$defs/timestamps"}]}
+            ],
+            "properties": {"address": {"required": ["city"]}},
+            "if": {"properties": {"kind": {"const": "company"}}},
+            "then": {"required": ["vat"]},
+            "anyOf": [{"required": ["email"]}, {"required": ["phone"]}],
+            "dependentRequired": {"email": ["verified"]},
+            "$defs": {"timestamps": {"required": ["created", "id"]}}
+        }))
+        .expect("Invalid schema")
+    }
+
+    #[test_case(&json!({"id": 1, "name": "a", "created": 0}); "all present")]
+    #[test_case(&json!({"id": "1", "name": 2, "created": null}); "values are not validated")]
+    #[test_case(&json!({"id": 1, "name": "a", "created": 0, "kind": "company", "email": "a"}); "conditional requirements")]
+    #[test_case(&json!({"id": 1, "name": "a", "created": 0, "address": {}}); "nested requirements")]
+    #[test_case(&json!(["id"]); "not an object")]
+    fn check_required_present(instance: &Value) {
+        assert_eq!(required_validator().check_required(instance), Ok(()));
+    }
+
+    #[test_case(&json!({}), &["id", "name", "created"]; "empty")]
+    #[test_case(&json!({"name": "a"}), &["id", "created"]; "partial")]
+    #[test_case(&json!({"id": 1, "name": "a"}), &["created"]; "through reference")]
+    fn check_required_missing(instance: &Value, expected: &[&str]) {
+        assert_eq!(
+            required_validator().check_required(instance),
+            Err(expected.iter().map(ToString::to_string).collect())
+        );
+    }
+
+    #[test]
+    fn check_required_ignores_reference_siblings_in_draft7() {
+        let validator = crate::validator_for(&json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "$ref": "#/definitions/base",
+            "required": ["ignored"],
+            "definitions": {"base": {"required": ["id"]}}
+        }))
+        .expect("Invalid schema");
+        assert_eq!(
+            validator.check_required(&json!({})),
+            Err(vec!["id".to_string()])
+        );
+    }
+
+    #[test]
+    fn check_required_combined() {
+        let left = crate::validator_for(&json!({"required": ["a", "b"]})).expect("Invalid schema");
+        let right = crate::validator_for(&json!({"required": ["b", "c"]})).expect("Invalid schema");
+        assert_eq!(
+            left.and(&right).check_required(&json!({"b": 1})),
+            Err(vec!["a".to_string(), "c".to_string()])
+        );
+    }
+
+    #[test]
+    fn check_required_subschema() {
+        let validator = crate::validator_for(&json!({
+            "required": ["id"],
+            "$defs": {"address": {"required": ["city"]}}
+        }))
+        .expect("Invalid schema");
+        let address = validator
+            .compile_subschema("/$defs/address")
+            .expect("Invalid subschema");
+        assert_eq!(
+            address.check_required(&json!({"id": 1})),
+            Err(vec!["city".to_string()])
+        );
+    }
+
+    fn anchor_registry() -> crate::Registry {
+        crate::Registry::try_new(
+            "https://example.com/customer",
+            crate::Resource::from_contents(json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "required": ["name"],
+                "$defs": {
+                    "address": {
+                        "$anchor": "address",
+                        "type": "object",
+                        "properties": {
+                            "street": {"type": "string"},
+                            "city": {"$ref": "#/$defs/city"}
+                        },
+                        "required": ["street", "city"]
+                    },
+                    "city": {"type": "string", "minLength": 1}
+                }
+            }))
+            .expect("Valid resource"),
+        )
+        .expect("Valid registry")
+    }
+
+    #[test]
+    fn from_anchor() {
+        let registry = anchor_registry();
+        let base = referencing::uri::from_str("https://example.com/customer").expect("Valid URI");
+        let validator = Validator::from_anchor(&registry, &base, "address").expect("Valid schema");
+        assert!(validator.is_valid(&json!({"street": "Main St", "city": "Springfield"})));
+        // The root `required` does not apply
+        assert!(!validator.is_valid(&json!({"street": "Main St"})));
+        let instance = json!({"street": "Main St", "city": ""});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.schema_path.as_str(),
+            "/properties/city/$ref/minLength"
+        );
+    }
+
+    #[test_case("missing", "Anchor 'missing' does not exist"; "undefined")]
+    #[test_case("/$defs/city", "Anchor '/$defs/city' is invalid"; "pointer")]
+    #[test_case("", "Anchor '' is invalid"; "empty")]
+    fn from_anchor_invalid(anchor: &str, expected: &str) {
+        let registry = anchor_registry();
+        let base = referencing::uri::from_str("https://example.com/customer").expect("Valid URI");
+        let error = Validator::from_anchor(&registry, &base, anchor).expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn check_examples() {
+        let schema = json!({
+            "type": "object",
+            "examples": [{"name": "Alice"}, []],
+            "properties": {
+                "name": {"$ref": "#/$defs/name"},
+                "tags": {"items": {"type": "string", "examples": ["a", 1]}}
+            },
+            "$defs": {
+                "name": {"type": "string", "minLength": 2, "examples": ["Bob", "B"]}
+            }
+        });
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        let mut errors: Vec<_> = validator
+            .check_examples()
+            .expect("Subschemas compile")
+            .into_iter()
+            .map(|error| {
+                (
+                    error.location.as_str().to_string(),
+                    error.example,
+                    error.error.to_string(),
+                )
+            })
+            .collect();
+        errors.sort_by(|left, right| left.0.cmp(&right.0));
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "/$defs/name/examples/1".to_string(),
+                    json!("B"),
+                    r#""B" is shorter than 2 characters"#.to_string()
+                ),
+                (
+                    "/examples/1".to_string(),
+                    json!([]),
+                    r#"[] is not of type "object""#.to_string()
+                ),
+                (
+                    "/properties/tags/items/examples/1".to_string(),
+                    json!(1),
+                    r#"1 is not of type "string""#.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_examples_all_valid() {
+        let validator = crate::validator_for(&json!({
+            "properties": {"id": {"type": "integer", "examples": [1, 2]}}
+        }))
+        .expect("Valid schema");
+        assert!(validator
+            .check_examples()
+            .expect("Subschemas compile")
+            .is_empty());
+        let validator = crate::validator_for(&json!({"type": "integer"})).expect("Valid schema");
+        assert!(validator
+            .check_examples()
+            .expect("Subschemas compile")
+            .is_empty());
+    }
+
+    #[test]
+    fn check_examples_invalid_subschema() {
+        // The unused definition is not compiled when building the validator
+        let validator = crate::validator_for(&json!({
+            "$defs": {"a": {"$ref": "#/$defs/missing", "examples": [1]}}
+        }))
+        .expect("Valid schema");
+        let error = validator.check_examples().expect_err("Should fail");
+        assert_eq!(error.to_string(), "Pointer '/$defs/missing' does not exist");
+    }
+
+    #[test]
+    fn combined_validators() {
+        let base = crate::validator_for(&json!({"type": "object", "required": ["id"]})).unwrap();
+        let tenant = crate::validator_for(&json!({
+            "properties": {"id": {"type": "integer", "minimum": 1}}
+        }))
+        .unwrap();
+        let validator = base.and(&tenant);
+
+        crate::tests_util::is_valid_with(&validator, &json!({"id": 1}));
+        crate::tests_util::is_not_valid_with(&validator, &json!({"id": 0}));
+        crate::tests_util::is_not_valid_with(&validator, &json!([]));
+
+        let instance = json!({"id": 0});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/properties/id/minimum");
+        assert_eq!(error.instance_path.as_str(), "/id");
+
+        let instance = json!(42);
+        let paths: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| error.schema_path.as_str().to_string())
+            .collect();
+        assert_eq!(paths, vec!["/type"]);
+
+        let instance = json!("abc");
+        let output = serde_json::to_value(validator.apply(&instance).basic()).unwrap();
+        assert_eq!(output["errors"][0]["keywordLocation"], json!("/type"));
+    }
+
+    #[test]
+    fn combined_validators_keep_references_separate() {
+        let left = crate::validator_for(&json!({
+            "$defs": {"item": {"type": "string"}},
+            "$ref": "#/$defs/item"
+        }))
+        .unwrap();
+        let right = crate::validator_for(&json!({
+            "$defs": {"item": {"maxLength": 2}},
+            "$ref": "#/$defs/item"
+        }))
+        .unwrap();
+        let validator = left.and(&right);
+        assert!(validator.is_valid(&json!("ab")));
+        assert!(!validator.is_valid(&json!("abc")));
+        assert!(!validator.is_valid(&json!(1)));
+    }
+
+    #[test_case(&json!({"type": "integer"}), &json!(42), None)]
+    #[test_case(&json!({"type": "integer"}), &json!("a"), Some(r#""a" is not of type "integer""#))]
+    #[test_case(
+        &json!({"properties": {"user": {"properties": {"age": {"minimum": 18}}}}}),
+        &json!({"user": {"age": 16}}),
+        Some("at /user/age: 16 is less than the minimum of 18")
+    )]
+    #[test_case(
+        &json!({"items": {"required": ["id"]}}),
+        &json!([{"id": 1}, {}]),
+        Some(r#"at /1: "id" is a required property"#)
+    )]
+    #[test_case(
+        &json!({"properties": {"a/b": {"type": "string"}}}),
+        &json!({"a/b": 1}),
+        Some(r#"at /a~1b: 1 is not of type "string""#)
+    )]
+    fn explain(schema: &Value, instance: &Value, expected: Option<&str>) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert_eq!(validator.explain(instance).as_deref(), expected);
+    }
+
+    fn nested_arrays(depth: usize) -> Value {
+        (0..depth).fold(json!(1), |inner, _| json!([inner]))
+    }
+
+    #[test]
+    fn max_instance_depth() {
+        let schema = json!({"items": {"$ref": "#"}});
+        let validator = crate::options()
+            .with_max_instance_depth(3)
+            .build(&schema)
+            .expect("Valid schema");
+        // The innermost `1` is at depth 3
+        assert!(validator.is_valid(&nested_arrays(3)));
+        let instance = nested_arrays(4);
+        assert!(!validator.is_valid(&instance));
+        assert!(!validator.apply(&instance).basic().is_valid());
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/0/0/0/0");
+        assert_eq!(
+            error.schema_path.as_str(),
+            "/items/$ref/items/$ref/items/$ref/items/$ref"
+        );
+        assert_eq!(
+            error.to_string(),
+            "Instance is nested deeper than the maximum depth of 3"
+        );
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn max_instance_depth_in_objects() {
+        let validator = crate::options()
+            .with_max_instance_depth(1)
+            .build(&json!({"additionalProperties": {"$ref": "#"}}))
+            .expect("Valid schema");
+        let instance = json!({"a": 1, "b": {"c": {}}});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/b/c");
+        assert!(validator.is_valid(&json!({"a": 1, "b": 2})));
+    }
+
+    #[test_case(&json!({}); "empty")]
+    #[test_case(&json!(true); "true")]
+    #[test_case(&json!({"type": "array", "items": {"type": "array"}}); "not recursive")]
+    #[test_case(&json!({"properties": {"child": {"$ref": "#"}}}); "not reached by recursion")]
+    fn max_instance_depth_without_recursion(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        let instance = nested_arrays(1100);
+        assert!(validator.is_valid(&instance));
+        assert!(validator.validate(&instance).is_ok());
+        assert!(validator.apply(&instance).basic().is_valid());
+    }
+
+    #[test]
+    fn unlimited_instance_depth() {
+        let validator = crate::options()
+            .with_max_instance_depth(usize::MAX)
+            .build(&json!({"items": {"$ref": "#"}}))
+            .expect("Valid schema");
+        assert!(validator.is_valid(&nested_arrays(100)));
+    }
+
+    #[test]
+    fn default_max_instance_depth() {
+        // Frames of unoptimized builds don't fit the default stack of test threads
+        std::thread::Builder::new()
+            .stack_size(64 * 1024 * 1024)
+            .spawn(|| {
+                let validator =
+                    crate::validator_for(&json!({"items": {"$ref": "#"}})).expect("Valid schema");
+                assert!(validator.is_valid(&nested_arrays(1024)));
+                let instance = nested_arrays(1100);
+                assert!(!validator.is_valid(&instance));
+                let error = validator.validate(&instance).expect_err("Should fail");
+                assert_eq!(
+                    error.to_string(),
+                    "Instance is nested deeper than the maximum depth of 1024"
+                );
+            })
+            .expect("Failed to spawn a thread")
+            .join()
+            .expect("Test thread panicked");
+    }
+
     #[test]
     fn test_validator_is_send_and_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
         assert_send_sync::<Validator>();
     }
+
+    fn trace(schema: &Value, instance: &Value) -> (bool, Vec<(String, String, bool)>) {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        let mut records = Vec::new();
+        let is_valid = validator.trace(instance, |record| {
+            records.push((
+                record.keyword_location.as_str().to_string(),
+                record.instance_location.as_str().to_string(),
+                record.valid,
+            ));
+        });
+        assert_eq!(is_valid, validator.is_valid(instance));
+        (is_valid, records)
+    }
+
+    #[test_case(&json!(3), false, true; "number below minimum")]
+    #[test_case(&json!(7), true, true; "number above minimum")]
+    #[test_case(&json!("ab"), true, true; "short string")]
+    #[test_case(&json!("abcd"), true, false; "long string")]
+    fn trace_scalar_keywords(instance: &Value, minimum: bool, max_length: bool) {
+        let (is_valid, mut records) = trace(&json!({"minimum": 5, "maxLength": 3}), instance);
+        records.sort();
+        assert_eq!(is_valid, minimum && max_length);
+        assert_eq!(
+            records,
+            vec![
+                ("/maxLength".to_string(), String::new(), max_length),
+                ("/minimum".to_string(), String::new(), minimum),
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_does_not_short_circuit() {
+        let (is_valid, records) = trace(
+            &json!({"items": {"minimum": 5}, "maxItems": 1}),
+            &json!([1, 10]),
+        );
+        assert!(!is_valid);
+        let records: Vec<_> = records
+            .iter()
+            .map(|(keyword, instance, valid)| (keyword.as_str(), instance.as_str(), *valid))
+            .collect();
+        assert!(records.contains(&("/items/minimum", "/0", false)));
+        assert!(records.contains(&("/items/minimum", "/1", true)));
+        assert!(records.contains(&("/items", "", false)));
+        assert!(records.contains(&("/maxItems", "", false)));
+    }
+
+    #[test]
+    fn trace_applicators() {
+        let (is_valid, records) = trace(
+            &json!({
+                "properties": {"name": {"anyOf": [{"maxLength": 2}, {"minLength": 4}]}},
+                "not": {"required": ["id"]}
+            }),
+            &json!({"name": "abc"}),
+        );
+        assert!(!is_valid);
+        let records: Vec<_> = records
+            .iter()
+            .map(|(keyword, instance, valid)| (keyword.as_str(), instance.as_str(), *valid))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                ("/not/required", "", false),
+                ("/not", "", true),
+                ("/properties/name/anyOf/0/maxLength", "/name", false),
+                ("/properties/name/anyOf/1/minLength", "/name", false),
+                ("/properties/name/anyOf", "/name", false),
+                ("/properties", "", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        let validator: Validator = r#"{"type": "string", "minLength": 2}"#
+            .parse()
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!("ab")));
+        assert!(!validator.is_valid(&json!("a")));
+    }
+
+    #[test_case(r#"{"type": "#; "truncated")]
+    #[test_case(r#"{"type": "string"} {}"#; "trailing characters")]
+    fn from_str_invalid_json(schema: &str) {
+        let error = schema.parse::<Validator>().expect_err("Invalid JSON");
+        assert!(matches!(error, crate::SchemaParseError::Json(_)));
+        let error =
+            Validator::from_str_with_draft(schema, crate::Draft::Draft7).expect_err("Invalid JSON");
+        assert!(matches!(error, crate::SchemaParseError::Json(_)));
+    }
+
+    #[test]
+    fn from_str_invalid_schema() {
+        let error = r#"{"type": 42}"#.parse::<Validator>().expect_err("Invalid schema");
+        let crate::SchemaParseError::Schema(error) = error else {
+            panic!("Unexpected error: {error:?}")
+        };
+        assert_eq!(error.instance_path.as_str(), "/type");
+    }
+
+    #[test_case(crate::Draft::Draft4, false)]
+    #[test_case(crate::Draft::Draft6, false)]
+    #[test_case(crate::Draft::Draft7, false)]
+    #[test_case(crate::Draft::Draft201909, true)]
+    #[test_case(crate::Draft::Draft202012, true)]
+    fn from_str_with_draft(draft: crate::Draft, applies_ref_siblings: bool) {
+        // Keywords next to `$ref` are ignored before Draft 2019-09
+        let schema = r##"{"$ref": "#/definitions/a", "minimum": 5, "definitions": {"a": {}}}"##;
+        let validator = Validator::from_str_with_draft(schema, draft).expect("Valid schema");
+        assert_eq!(validator.is_valid(&json!(3)), !applies_ref_siblings);
+        assert!(validator.is_valid(&json!(6)));
+    }
+
+    #[cfg(feature = "raw-value")]
+    #[test_case(r#"{"id": 1, "tags": ["a", "b"]}"#, true)]
+    #[test_case(r#"{"id": "1", "tags": ["a", "b"]}"#, false)]
+    #[test_case(r#"{"tags": []}"#, false)]
+    #[test_case("42", false)]
+    fn is_valid_raw(raw: &str, expected: bool) {
+        let validator = crate::validator_for(&json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {"id": {"type": "integer"}, "tags": {"items": {"type": "string"}}}
+        }))
+        .expect("Invalid schema");
+        let raw = serde_json::value::RawValue::from_string(raw.to_string()).expect("Valid JSON");
+        assert_eq!(validator.is_valid_raw(&raw).expect("Valid JSON"), expected);
+    }
+
+    #[test]
+    fn iter_errors_order() {
+        let schema = json!({
+            "type": "object",
+            "required": ["z", "y"],
+            "properties": {
+                "list": {"items": {"type": "integer"}, "maxItems": 1},
+                "nested": {
+                    "properties": {"b": {"type": "string"}},
+                    "patternProperties": {"^a": {"minimum": 5}},
+                    "additionalProperties": false
+                }
+            },
+            "patternProperties": {"^c": {"type": "string"}, "x$": {"type": "string"}},
+            "allOf": [{"minProperties": 10}, {"maxProperties": 1}]
+        });
+        let instance = json!({
+            "ax": 1,
+            "cy": 2,
+            "list": [1, "a", "b"],
+            "nested": {"b": 1, "ab": 1, "c": 1, "d": 1}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| format!("{} @ {}", error.schema_path, error.instance_path))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "/allOf/0/minProperties @ ",
+                "/allOf/1/maxProperties @ ",
+                "/patternProperties/x$/type @ /ax",
+                "/patternProperties/^c/type @ /cy",
+                "/properties/list/items/type @ /list/1",
+                "/properties/list/items/type @ /list/2",
+                "/properties/list/maxItems @ /list",
+                "/properties/nested/patternProperties/^a/minimum @ /nested/ab",
+                "/properties/nested/properties/b/type @ /nested/b",
+                "/properties/nested/additionalProperties @ /nested",
+                "/required @ ",
+                "/required @ ",
+            ]
+        );
+        // Repeated runs yield the same order
+        for _ in 0..3 {
+            let again: Vec<_> = validator
+                .iter_errors(&instance)
+                .map(|error| format!("{} @ {}", error.schema_path, error.instance_path))
+                .collect();
+            assert_eq!(again, errors);
+        }
+    }
+
+    #[cfg(feature = "profile")]
+    #[test]
+    fn last_profile_counts_keywords() {
+        let validator = crate::validator_for(&json!({
+            "properties": {"age": {"type": "integer", "minimum": 5}}
+        }))
+        .expect("Invalid schema");
+        assert!(validator.last_profile().is_none());
+        assert!(validator.is_valid(&json!({"age": 7})));
+        let profile = validator.last_profile().expect("Profile is recorded");
+        let counts: Vec<_> = profile
+            .keywords()
+            .iter()
+            .map(|keyword| (keyword.location.as_str(), keyword.calls))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("/properties", 1),
+                ("/properties/age/minimum", 1),
+                ("/properties/age/type", 1),
+            ]
+        );
+        assert!(profile.total_time() >= profile.get("/properties").expect("Evaluated").time);
+        // Every run replaces the previous profile
+        assert!(!validator.is_valid(&json!({"age": 3})));
+        let profile = validator.last_profile().expect("Profile is recorded");
+        assert_eq!(
+            profile
+                .get("/properties/age/minimum")
+                .map(|keyword| keyword.calls),
+            Some(1)
+        );
+    }
+
+    #[cfg(feature = "profile")]
+    #[test]
+    fn last_profile_zero_sized_keywords() {
+        struct Noop;
+        impl Keyword for Noop {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                true
+            }
+        }
+        let validator = crate::options()
+            .with_keyword("noop", |_, _, _| Ok(Box::new(Noop)))
+            .build(&json!({"properties": {"a": {"noop": 1}, "b": {"noop": 2}}}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": 1, "b": 2})));
+        let profile = validator.last_profile().expect("Profile is recorded");
+        for location in ["/properties/a/noop", "/properties/b/noop"] {
+            assert_eq!(profile.get(location).map(|keyword| keyword.calls), Some(1));
+        }
+    }
 }
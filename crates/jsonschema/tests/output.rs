@@ -1,3 +1,4 @@
+use jsonschema::{paths::InstanceLocationStyle, Draft};
 use serde_json::json;
 use test_case::test_case;
 
@@ -394,6 +395,69 @@ use test_case::test_case;
         ]
     }); "invalid items"
 }]
+#[test_case{
+    &json!({
+        "prefixItems": [{"type": "integer"}, {"type": "string"}],
+        "items": {"type": "boolean"}
+    }),
+    &json!([1, "a", true]),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/prefixItems",
+                "instanceLocation": "",
+                "annotations": 1
+            },
+            {
+                "keywordLocation": "/items",
+                "instanceLocation": "",
+                "annotations": true
+            },
+        ]
+    }); "valid prefixItems with items"
+}]
+#[test_case{
+    &json!({
+        "$schema": "https://json-schema.org/draft/2019-09/schema",
+        "items": [{"type": "integer"}, {"type": "string"}],
+        "additionalItems": {"type": "boolean"}
+    }),
+    &json!([1, "a", true]),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/items",
+                "instanceLocation": "",
+                "annotations": 1
+            },
+            {
+                "keywordLocation": "/additionalItems",
+                "instanceLocation": "",
+                "annotations": true
+            },
+        ]
+    }); "valid items array with additionalItems"
+}]
+#[test_case{
+    &json!({
+        "$schema": "https://json-schema.org/draft/2019-09/schema",
+        "items": [{"type": "integer"}, {"type": "string"}],
+        "additionalItems": {"type": "boolean"}
+    }),
+    &json!([1]),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/items",
+                "instanceLocation": "",
+                "annotations": true
+            },
+        ]
+    }); "valid items array without additional items"
+}]
 #[test_case{
     &json!({
         "contains": {
@@ -579,6 +643,80 @@ use test_case::test_case;
         ]
     }); "valid propertyNames"
 }]
+#[test_case{
+    &json!({"minimum": 5}),
+    &json!(7),
+    &json!({
+        "valid": true,
+        "annotations": []
+    }); "valid minimum"
+}]
+#[test_case{
+    &json!({"minimum": 5}),
+    &json!(3),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/minimum",
+                "instanceLocation": "",
+                "error": "3 is less than the minimum of 5"
+            },
+        ]
+    }); "invalid minimum"
+}]
+#[test_case{
+    &json!({"properties": {"age": {"minimum": 5, "title": "Age"}}}),
+    &json!({"age": 7}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["age"]
+            },
+            {
+                "keywordLocation": "/properties/age",
+                "instanceLocation": "/age",
+                "annotations": {"title": "Age"}
+            },
+        ]
+    }); "valid nested minimum"
+}]
+#[test_case{
+    &json!({"properties": {"bio": {"type": "string", "x-ui-widget": "textarea"}}}),
+    &json!({"bio": "Hi"}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["bio"]
+            },
+            {
+                "keywordLocation": "/properties/bio",
+                "instanceLocation": "/bio",
+                "annotations": {"x-ui-widget": "textarea"}
+            },
+        ]
+    }); "unknown keyword"
+}]
+#[test_case{
+    &json!({"x-ui-widget": "textarea"}),
+    &json!(42),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "",
+                "instanceLocation": "",
+                "annotations": {"x-ui-widget": "textarea"}
+            },
+        ]
+    }); "only unknown keywords"
+}]
 fn test_basic_output(
     schema: &serde_json::Value,
     instance: &serde_json::Value,
@@ -897,6 +1035,11 @@ fn test_basic_output(
                 "keywordLocation": "/additionalProperties",
                 "instanceLocation": "",
                 "annotations": ["otherprop"]
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["name"]
             }
         ]
     }); "valid AdditionalPropertiesWithPatternsNotEmptyValidator"
@@ -924,6 +1067,16 @@ fn test_basic_output(
                 "annotations": {
                     "prop": "annotation"
                 }
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["name"]
+            },
+            {
+                "keywordLocation": "/patternProperties",
+                "instanceLocation": "",
+                "annotations": ["stringProp1"]
             }
         ]
     }); "valid AdditionalPropertiesWithPatternsNotEmptyFalseValidator"
@@ -1004,6 +1157,80 @@ fn test_basic_output(
         ]
     }); "invalid AdditionalPropertiesWithPatternsFalseValidator"
 }]
+#[test_case{
+    &json!({
+        "properties": {
+            "foo": {"type": "integer", "prop": "annotation"}
+        },
+        "patternProperties": {
+            "^f": {"minimum": 0, "patternio": "annotation"}
+        },
+        "additionalProperties": {"type": "string"}
+    }),
+    &json!({
+        "foo": 1,
+        "bar": "baz"
+    }),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/additionalProperties",
+                "instanceLocation": "",
+                "annotations": ["bar"]
+            },
+            {
+                "keywordLocation": "/properties/foo",
+                "instanceLocation": "/foo",
+                "annotations": {"prop": "annotation"}
+            },
+            {
+                "keywordLocation": "/patternProperties/^f",
+                "instanceLocation": "/foo",
+                "annotations": {"patternio": "annotation"}
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["foo"]
+            },
+            {
+                "keywordLocation": "/patternProperties",
+                "instanceLocation": "",
+                "annotations": ["foo"]
+            }
+        ]
+    }); "overlapping properties and patternProperties in AdditionalPropertiesWithPatternsNotEmptyValidator"
+}]
+#[test_case{
+    &json!({
+        "properties": {
+            "foo": {"type": "integer"}
+        },
+        "patternProperties": {
+            "^f": {"minimum": 0}
+        },
+        "additionalProperties": false
+    }),
+    &json!({
+        "foo": 1
+    }),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["foo"]
+            },
+            {
+                "keywordLocation": "/patternProperties",
+                "instanceLocation": "",
+                "annotations": ["foo"]
+            }
+        ]
+    }); "overlapping properties and patternProperties in AdditionalPropertiesWithPatternsNotEmptyFalseValidator"
+}]
 fn test_additional_properties_basic_output(
     schema: &serde_json::Value,
     instance: &serde_json::Value,
@@ -1017,3 +1244,148 @@ fn test_additional_properties_basic_output(
         panic!("\nExpected:\n{}\n\nGot:\n{}\n", expected_str, actual_str);
     }
 }
+
+#[test]
+fn test_units_interleave_annotations_and_errors() {
+    let schema = json!({
+        "title": "Person",
+        "additionalProperties": {"type": "integer"},
+        "allOf": [{
+            "properties": {
+                "name": {"type": "string"}
+            }
+        }]
+    });
+    let instance = json!({"name": 42, "age": 1});
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let units = validator
+        .iter_units(&instance)
+        .map(|unit| serde_json::to_value(unit).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        units,
+        vec![
+            json!({
+                "keywordLocation": "",
+                "instanceLocation": "",
+                "annotations": {"title": "Person"}
+            }),
+            json!({
+                "keywordLocation": "/additionalProperties",
+                "instanceLocation": "",
+                "annotations": ["age", "name"]
+            }),
+            json!({
+                "keywordLocation": "/allOf/0/properties/name/type",
+                "instanceLocation": "/name",
+                "error": "42 is not of type \"string\""
+            }),
+        ]
+    );
+    assert!(matches!(
+        validator.iter_units(&instance).last(),
+        Some(jsonschema::output::Unit::Error(_))
+    ));
+}
+
+#[test]
+fn test_units_for_valid_instance_match_basic_output() {
+    let schema = json!({
+        "additionalProperties": {"type": "integer"},
+        "properties": {
+            "name": {"type": "string"}
+        }
+    });
+    let instance = json!({"name": "Alice", "age": 1});
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let units = validator
+        .iter_units(&instance)
+        .map(|unit| serde_json::to_value(unit).unwrap())
+        .collect::<Vec<_>>();
+    let jsonschema::BasicOutput::Valid(annotations) = validator.apply(&instance).basic() else {
+        panic!("Should be valid")
+    };
+    let mut expected = annotations
+        .iter()
+        .map(|unit| serde_json::to_value(unit).unwrap())
+        .collect::<Vec<_>>();
+    let mut units_sorted = units.clone();
+    let key = |value: &serde_json::Value| value["keywordLocation"].to_string();
+    expected.sort_by_key(key);
+    units_sorted.sort_by_key(key);
+    assert_eq!(units_sorted, expected);
+}
+
+/// Assertion keywords produce no annotations when they pass, but every failing one is reported
+/// as a separate unit at its own location.
+#[test_case(&json!({"minimum": 5}), &json!(3), "/minimum")]
+#[test_case(&json!({"maximum": 5}), &json!(7), "/maximum")]
+#[test_case(&json!({"exclusiveMinimum": 5}), &json!(5), "/exclusiveMinimum")]
+#[test_case(&json!({"exclusiveMaximum": 5}), &json!(5), "/exclusiveMaximum")]
+#[test_case(&json!({"multipleOf": 2}), &json!(3), "/multipleOf")]
+#[test_case(&json!({"minLength": 2}), &json!("a"), "/minLength")]
+#[test_case(&json!({"maxLength": 1}), &json!("ab"), "/maxLength")]
+#[test_case(&json!({"pattern": "^a"}), &json!("b"), "/pattern")]
+#[test_case(&json!({"format": "ipv4"}), &json!("x"), "/format")]
+#[test_case(&json!({"const": true}), &json!(false), "/const")]
+#[test_case(&json!({"enum": [1, 2]}), &json!(3), "/enum")]
+#[test_case(&json!({"type": "boolean"}), &json!(1), "/type")]
+#[test_case(&json!({"properties": {"a": false}}), &json!({"a": 1}), "/properties/a")]
+fn test_scalar_keywords_basic_output(
+    schema: &serde_json::Value,
+    instance: &serde_json::Value,
+    location: &str,
+) {
+    let validator = jsonschema::options()
+        .should_validate_formats(true)
+        .build(schema)
+        .unwrap();
+    let jsonschema::BasicOutput::Invalid(errors) = validator.apply(instance).basic() else {
+        panic!("Should be invalid")
+    };
+    let locations: Vec<_> = errors
+        .iter()
+        .map(|unit| unit.keyword_location().as_str())
+        .collect();
+    assert_eq!(locations, vec![location]);
+}
+
+#[test_case(Draft::Draft7, &json!({"x-ui-widget": "list", "prefixItems": [{"type": "string"}]}); "unknown in draft 7")]
+#[test_case(Draft::Draft202012, &json!({"x-ui-widget": "list"}); "known in draft 2020-12")]
+fn test_unknown_keywords_depend_on_draft(draft: Draft, expected: &serde_json::Value) {
+    let validator = jsonschema::options()
+        .with_draft(draft)
+        .build(&json!({"x-ui-widget": "list", "prefixItems": [{"type": "string"}]}))
+        .unwrap();
+    let jsonschema::BasicOutput::Valid(annotations) = validator.apply(&json!(["a"])).basic() else {
+        panic!("Unknown keywords should not assert")
+    };
+    let root = annotations
+        .iter()
+        .find(|unit| unit.keyword_location().as_str().is_empty())
+        .expect("Missing annotations");
+    assert_eq!(root.value().as_ref(), expected);
+}
+
+#[test_case(InstanceLocationStyle::Pointer, "/user/a b~1c~0%/0"; "pointer")]
+#[test_case(InstanceLocationStyle::UriFragment, "#/user/a%20b~1c~0%25/0"; "uri fragment")]
+fn test_instance_location_style(style: InstanceLocationStyle, expected: &str) {
+    let schema = json!({
+        "properties": {
+            "user": {"properties": {"a b/c~%": {"items": {"type": "string"}}}}
+        }
+    });
+    let instance = json!({"user": {"a b/c~%": [42]}});
+    let validator = jsonschema::options()
+        .with_instance_location_style(style)
+        .build(&schema)
+        .unwrap();
+    let error = validator.validate(&instance).expect_err("Should fail");
+    assert_eq!(
+        validator.display_location(&error.instance_path).to_string(),
+        expected
+    );
+    assert_eq!(error.instance_path.to_string(), "/user/a b~1c~0%/0");
+    let output = serde_json::to_value(validator.apply(&instance).basic()).unwrap();
+    assert_eq!(output["errors"][0]["instanceLocation"], expected);
+}